@@ -0,0 +1,7 @@
+-- Secret Settings (encrypted at rest, never returned to the frontend)
+CREATE TABLE IF NOT EXISTS secret_settings (
+    key TEXT PRIMARY KEY NOT NULL,
+    nonce BLOB NOT NULL,
+    ciphertext BLOB NOT NULL,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
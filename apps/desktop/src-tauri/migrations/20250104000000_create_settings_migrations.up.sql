@@ -0,0 +1,6 @@
+-- Applied settings-key migrations (see infra/settings_migrations.rs)
+CREATE TABLE IF NOT EXISTS settings_migrations (
+    version INTEGER PRIMARY KEY NOT NULL,
+    description TEXT NOT NULL,
+    applied_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_downloads_state;
+DROP TABLE IF EXISTS downloads;
@@ -0,0 +1,20 @@
+-- Download manager queue (resumable downloads)
+CREATE TABLE IF NOT EXISTS downloads (
+    id TEXT PRIMARY KEY NOT NULL,
+    url TEXT NOT NULL,
+    path TEXT NOT NULL,
+    -- queued, running, paused, completed, failed, cancelled
+    state TEXT NOT NULL DEFAULT 'queued',
+    bytes_done INTEGER NOT NULL DEFAULT 0,
+    -- NULL = size unknown
+    total_bytes INTEGER,
+    -- Sent as If-Range when resuming
+    etag TEXT,
+    -- Expected hex-encoded SHA-256, NULL = not verified
+    sha256 TEXT,
+    error TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_downloads_state ON downloads (state, created_at);
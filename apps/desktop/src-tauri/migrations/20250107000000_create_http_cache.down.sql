@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_http_cache_stored_at;
+DROP TABLE IF EXISTS http_cache;
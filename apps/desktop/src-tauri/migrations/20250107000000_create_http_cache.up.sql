@@ -0,0 +1,21 @@
+-- Cached HTTP responses (see infra/http.rs)
+CREATE TABLE IF NOT EXISTS http_cache (
+    -- "<METHOD> <url>"
+    key TEXT PRIMARY KEY NOT NULL,
+    method TEXT NOT NULL,
+    url TEXT NOT NULL,
+    status INTEGER NOT NULL,
+    -- JSON object of the response headers
+    headers TEXT NOT NULL,
+    body BLOB NOT NULL,
+    -- JSON object of the request headers named by the response's Vary header
+    vary TEXT NOT NULL DEFAULT '{}',
+    -- Validators sent as If-None-Match / If-Modified-Since once stale
+    etag TEXT,
+    last_modified TEXT,
+    -- Unix seconds; served without revalidation until then
+    expires_at INTEGER NOT NULL,
+    stored_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_http_cache_stored_at ON http_cache (stored_at);
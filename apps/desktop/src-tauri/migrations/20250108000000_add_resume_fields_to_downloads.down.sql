@@ -0,0 +1,2 @@
+ALTER TABLE downloads DROP COLUMN overwrite;
+ALTER TABLE downloads DROP COLUMN last_modified;
@@ -0,0 +1,4 @@
+-- Sent as If-Range when resuming a download without an ETag
+ALTER TABLE downloads ADD COLUMN last_modified TEXT;
+-- Replace an existing file at path when the download completes
+ALTER TABLE downloads ADD COLUMN overwrite BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS system_settings;
+DROP TABLE IF EXISTS users;
@@ -0,0 +1,7 @@
+-- Secret Settings (encrypted at rest, never returned to the frontend)
+CREATE TABLE IF NOT EXISTS secret_settings (
+    key TEXT PRIMARY KEY NOT NULL,
+    nonce BYTEA NOT NULL,
+    ciphertext BYTEA NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
@@ -0,0 +1,6 @@
+-- Applied settings-key migrations (see infra/settings_migrations.rs)
+CREATE TABLE IF NOT EXISTS settings_migrations (
+    version BIGINT PRIMARY KEY NOT NULL,
+    description TEXT NOT NULL,
+    applied_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
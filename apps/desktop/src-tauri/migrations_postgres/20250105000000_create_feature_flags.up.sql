@@ -0,0 +1,20 @@
+-- Feature Flags (local rollout rules)
+CREATE TABLE IF NOT EXISTS feature_flags (
+    key TEXT PRIMARY KEY NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    default_enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    -- 0-100, NULL = no percentage rollout
+    rollout_percentage BIGINT,
+    -- RFC 3339, NULL = never expires
+    expires_at TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Per-user overrides
+CREATE TABLE IF NOT EXISTS feature_flag_overrides (
+    flag_key TEXT NOT NULL REFERENCES feature_flags(key) ON DELETE CASCADE,
+    user_id TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL,
+    PRIMARY KEY (flag_key, user_id)
+);
@@ -0,0 +1,58 @@
+//! Archive command handlers - handles exporting and importing all data as one archive.
+
+use std::path::Path;
+use std::sync::Arc;
+use async_trait::async_trait;
+use tracing::info;
+use crate::domain::archive::{ArchiveManifest, ExportArchiveCmd, IArchiveStore, ImportArchiveCmd};
+use crate::domain::backups::IBackupStore;
+use crate::domain::cqrs::CommandHandler;
+use crate::domain::events::{DomainEvent, IEventPublisher};
+use crate::error::AppError;
+
+/// Handles archive-related commands (write operations).
+pub struct ArchiveCommandHandler {
+    store: Arc<dyn IArchiveStore>,
+    /// Takes a backup before an import; `None` when backups are unavailable
+    backups: Option<Arc<dyn IBackupStore>>,
+    publisher: Arc<dyn IEventPublisher>,
+}
+
+impl ArchiveCommandHandler {
+    pub fn new(
+        store: Arc<dyn IArchiveStore>,
+        backups: Option<Arc<dyn IBackupStore>>,
+        publisher: Arc<dyn IEventPublisher>,
+    ) -> Self {
+        Self { store, backups, publisher }
+    }
+}
+
+#[async_trait]
+impl CommandHandler<ExportArchiveCmd, ArchiveManifest> for ArchiveCommandHandler {
+    async fn handle(&self, cmd: ExportArchiveCmd) -> Result<ArchiveManifest, AppError> {
+        self.store.export(&cmd.path).await
+    }
+}
+
+#[async_trait]
+impl CommandHandler<ImportArchiveCmd, ArchiveManifest> for ArchiveCommandHandler {
+    async fn handle(&self, cmd: ImportArchiveCmd) -> Result<ArchiveManifest, AppError> {
+        // Keep the current state in case the import was a mistake
+        if let Some(backups) = &self.backups {
+            let backup = backups.create().await?;
+            info!("Backup {} taken before importing {}", backup.name, cmd.path);
+        }
+
+        let manifest = self.store.import(&cmd.path).await?;
+
+        // Everything cached from the old data is stale now
+        let name = Path::new(&cmd.path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or(cmd.path);
+        self.publisher.publish(DomainEvent::ArchiveImported { archive: name });
+
+        Ok(manifest)
+    }
+}
@@ -0,0 +1,152 @@
+//! Autostart service - keeps the OS login item in sync with the
+//! `launch_on_startup` setting.
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use tracing::{info, warn};
+use crate::application::ConfigQueryHandler;
+use crate::domain::autostart::{AutostartStatus, GetAutostartStatusQuery, IAutostartManager, LAUNCH_ON_STARTUP_KEY};
+use crate::domain::config::GetConfigQuery;
+use crate::domain::cqrs::QueryHandler;
+use crate::domain::events::{DomainEvent, IEventSubscriber};
+use crate::error::AppError;
+
+/// Installs or removes the login item whenever the setting changes.
+/// Cheap to clone, so event handling can run on a background task.
+#[derive(Clone)]
+pub struct AutostartService {
+    manager: Arc<dyn IAutostartManager>,
+    config: ConfigQueryHandler,
+}
+
+impl AutostartService {
+    pub fn new(manager: Arc<dyn IAutostartManager>, config: ConfigQueryHandler) -> Self {
+        Self { manager, config }
+    }
+
+    /// Apply the effective setting to the OS. Called at startup and on every change.
+    pub async fn sync(&self) -> Result<(), AppError> {
+        let configured = self.configured().await?;
+        if self.manager.is_enabled()? == configured {
+            return Ok(());
+        }
+
+        if configured {
+            self.manager.enable()?;
+        } else {
+            self.manager.disable()?;
+        }
+        info!("Launch on startup {}", if configured { "enabled" } else { "disabled" });
+        Ok(())
+    }
+
+    async fn configured(&self) -> Result<bool, AppError> {
+        let value = self
+            .config
+            .handle(GetConfigQuery { key: LAUNCH_ON_STARTUP_KEY.to_string() })
+            .await?;
+        Ok(matches!(value.as_deref(), Some("true") | Some("1")))
+    }
+}
+
+#[async_trait]
+impl QueryHandler<GetAutostartStatusQuery, AutostartStatus> for AutostartService {
+    async fn handle(&self, _query: GetAutostartStatusQuery) -> Result<AutostartStatus, AppError> {
+        Ok(AutostartStatus {
+            enabled: self.manager.is_enabled()?,
+            configured: self.configured().await?,
+            location: self.manager.location(),
+        })
+    }
+}
+
+impl IEventSubscriber for AutostartService {
+    fn on_event(&self, event: &DomainEvent) {
+        let affected = match event {
+            DomainEvent::ConfigChanged { key, .. } | DomainEvent::ConfigDeleted { key } => {
+                key == LAUNCH_ON_STARTUP_KEY
+            }
+            DomainEvent::ConfigBatchChanged { values } => values.contains_key(LAUNCH_ON_STARTUP_KEY),
+            DomainEvent::ConfigReset
+            | DomainEvent::DatabaseRestored { .. }
+            | DomainEvent::ArchiveImported { .. }
+            | DomainEvent::MigrationsRolledBack { .. } => true,
+            _ => false,
+        };
+        if !affected {
+            return;
+        }
+
+        // Resolving the effective value is async (defaults may apply after a delete)
+        let service = self.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = service.sync().await {
+                warn!("Failed to apply launch on startup setting: {:?}", e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+    use crate::domain::config::{IConfigRepository, StaticConfig};
+    use crate::infra::memory::{InMemoryAutostartManager, InMemoryConfigRepository};
+
+    fn service() -> (AutostartService, Arc<InMemoryAutostartManager>, Arc<InMemoryConfigRepository>) {
+        let manager = Arc::new(InMemoryAutostartManager::new());
+        let repo = Arc::new(InMemoryConfigRepository::new());
+        let config = ConfigQueryHandler::new(repo.clone(), Arc::new(StaticConfig::default()));
+        (AutostartService::new(manager.clone(), config), manager, repo)
+    }
+
+    /// Store a value the way a config command would: write, then publish
+    async fn set(service: &AutostartService, repo: &InMemoryConfigRepository, value: &str) -> DomainEvent {
+        repo.set(LAUNCH_ON_STARTUP_KEY, value).await.unwrap();
+        let event = DomainEvent::ConfigChanged { key: LAUNCH_ON_STARTUP_KEY.to_string(), value: value.to_string() };
+        service.config.cache().on_event(&event);
+        event
+    }
+
+    #[tokio::test]
+    async fn sync_installs_and_removes_the_login_item() {
+        let (service, manager, repo) = service();
+        service.sync().await.unwrap();
+        assert!(!manager.is_enabled().unwrap());
+
+        set(&service, &repo, "true").await;
+        service.sync().await.unwrap();
+        assert!(manager.is_enabled().unwrap());
+
+        set(&service, &repo, "false").await;
+        service.sync().await.unwrap();
+        assert!(!manager.is_enabled().unwrap());
+    }
+
+    #[tokio::test]
+    async fn status_reports_the_setting_and_the_os_state() {
+        let (service, manager, repo) = service();
+        set(&service, &repo, "1").await;
+
+        let status = service.handle(GetAutostartStatusQuery).await.unwrap();
+        assert!(status.configured && !status.enabled);
+
+        manager.enable().unwrap();
+        let status = service.handle(GetAutostartStatusQuery).await.unwrap();
+        assert!(status.configured && status.enabled);
+    }
+
+    #[tokio::test]
+    async fn setting_changes_sync_in_the_background() {
+        let (service, manager, repo) = service();
+        let event = set(&service, &repo, "true").await;
+        service.on_event(&event);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !manager.is_enabled().unwrap() {
+            assert!(Instant::now() < deadline, "login item was not installed");
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Backup command handlers - handles creating, rotating and restoring database backups.
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use tracing::{info, warn};
+use crate::application::ConfigQueryHandler;
+use crate::domain::backups::{
+    BackupInfo, CreateBackupCmd, IBackupStore, RestoreBackupCmd, BACKUP_INTERVAL_HOURS_KEY,
+    BACKUP_RETENTION_KEY, DEFAULT_BACKUP_INTERVAL_HOURS, DEFAULT_BACKUP_RETENTION,
+};
+use crate::domain::config::GetConfigQuery;
+use crate::domain::cqrs::{CommandHandler, QueryHandler};
+use crate::domain::events::{DomainEvent, IEventPublisher};
+use crate::error::AppError;
+
+/// Handles backup-related commands (write operations).
+pub struct BackupCommandHandler {
+    store: Arc<dyn IBackupStore>,
+    publisher: Arc<dyn IEventPublisher>,
+    config: ConfigQueryHandler,
+}
+
+impl BackupCommandHandler {
+    pub fn new(
+        store: Arc<dyn IBackupStore>,
+        publisher: Arc<dyn IEventPublisher>,
+        config: ConfigQueryHandler,
+    ) -> Self {
+        Self { store, publisher, config }
+    }
+
+    /// Create a scheduled backup if the newest one is older than the configured interval.
+    pub async fn backup_if_due(&self) -> Result<Option<BackupInfo>, AppError> {
+        let interval_hours = self.setting(BACKUP_INTERVAL_HOURS_KEY, DEFAULT_BACKUP_INTERVAL_HOURS).await?;
+        if interval_hours == 0 {
+            return Ok(None);
+        }
+
+        let newest = self.store.list().await?.into_iter().next();
+        let due = match newest.and_then(|b| OffsetDateTime::parse(&b.created_at, &Rfc3339).ok()) {
+            Some(created_at) => {
+                OffsetDateTime::now_utc() - created_at >= time::Duration::hours(interval_hours as i64)
+            }
+            None => true,
+        };
+        if !due {
+            return Ok(None);
+        }
+
+        info!("Scheduled database backup is due");
+        self.handle(CreateBackupCmd).await.map(Some)
+    }
+
+    /// Delete backups beyond the configured retention, oldest first.
+    /// Snapshots taken before a restore do not count and are kept.
+    async fn rotate(&self) -> Result<(), AppError> {
+        let retention = self.setting(BACKUP_RETENTION_KEY, DEFAULT_BACKUP_RETENTION as u64).await? as usize;
+        let scheduled = self.store.list().await?.into_iter().filter(|backup| !backup.pre_restore);
+        for backup in scheduled.skip(retention.max(1)) {
+            match self.store.delete(&backup.name).await {
+                Ok(()) => info!("Deleted old backup {}", backup.name),
+                Err(e) => warn!("Failed to delete old backup {}: {:?}", backup.name, e),
+            }
+        }
+        Ok(())
+    }
+
+    async fn setting(&self, key: &str, default: u64) -> Result<u64, AppError> {
+        let value = self.config.handle(GetConfigQuery { key: key.to_string() }).await?;
+        Ok(value.and_then(|v| v.trim().parse().ok()).unwrap_or(default))
+    }
+}
+
+#[async_trait]
+impl CommandHandler<CreateBackupCmd, BackupInfo> for BackupCommandHandler {
+    async fn handle(&self, _cmd: CreateBackupCmd) -> Result<BackupInfo, AppError> {
+        let backup = self.store.create().await?;
+        self.rotate().await?;
+        Ok(backup)
+    }
+}
+
+#[async_trait]
+impl CommandHandler<RestoreBackupCmd, ()> for BackupCommandHandler {
+    async fn handle(&self, cmd: RestoreBackupCmd) -> Result<(), AppError> {
+        self.store.restore(&cmd.name).await?;
+
+        // Everything cached from the old database is stale now
+        self.publisher.publish(DomainEvent::DatabaseRestored { backup: cmd.name });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::backups::RestoreBackupCmd;
+    use crate::domain::config::{IConfigRepository, StaticConfig};
+    use crate::infra::backup_store::SqliteBackupStore;
+    use crate::infra::db::{Database, DbOptions};
+    use crate::infra::event_publisher::RecordingEventPublisher;
+    use crate::infra::memory::InMemoryConfigRepository;
+
+    #[tokio::test]
+    async fn rotation_keeps_the_newest_and_pre_restore_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(Database::open(dir.path().join("app.db"), DbOptions::default()).await.unwrap());
+        let store = Arc::new(SqliteBackupStore::new(db, dir.path().join("backups")));
+        let config = Arc::new(InMemoryConfigRepository::new());
+        config.set(BACKUP_RETENTION_KEY, "2").await.unwrap();
+        let handler = BackupCommandHandler::new(
+            store.clone(),
+            Arc::new(RecordingEventPublisher::new()),
+            ConfigQueryHandler::new(config, Arc::new(StaticConfig::default())),
+        );
+
+        let first = handler.handle(CreateBackupCmd).await.unwrap();
+        handler.handle(RestoreBackupCmd { name: first.name.clone() }).await.unwrap();
+        let mut created = Vec::new();
+        for _ in 0..3 {
+            created.push(handler.handle(CreateBackupCmd).await.unwrap().name);
+        }
+
+        let backups = store.list().await.unwrap();
+        let scheduled: Vec<&str> = backups.iter().filter(|b| !b.pre_restore).map(|b| b.name.as_str()).collect();
+        assert_eq!(scheduled, [created[2].as_str(), created[1].as_str()]);
+        assert_eq!(backups.iter().filter(|b| b.pre_restore).count(), 1);
+    }
+}
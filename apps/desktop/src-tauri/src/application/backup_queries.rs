@@ -0,0 +1,25 @@
+//! Backup query handlers - handles all read operations for database backups.
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use crate::domain::backups::{BackupInfo, IBackupStore, ListBackupsQuery};
+use crate::domain::cqrs::QueryHandler;
+use crate::error::AppError;
+
+/// Handles backup-related queries (read operations).
+pub struct BackupQueryHandler {
+    store: Arc<dyn IBackupStore>,
+}
+
+impl BackupQueryHandler {
+    pub fn new(store: Arc<dyn IBackupStore>) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl QueryHandler<ListBackupsQuery, Vec<BackupInfo>> for BackupQueryHandler {
+    async fn handle(&self, _query: ListBackupsQuery) -> Result<Vec<BackupInfo>, AppError> {
+        self.store.list().await
+    }
+}
@@ -0,0 +1,344 @@
+//! Config cache - read-through in-memory cache in front of `IConfigRepository`.
+//!
+//! The cache is warmed with every setting at startup and invalidated per key
+//! whenever a `ConfigChanged` event is published, so reads normally never touch SQLite.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use crate::domain::config::{ConfigCacheStats, IConfigRepository};
+use crate::domain::events::{DomainEvent, IEventSubscriber};
+use crate::error::AppError;
+
+pub struct ConfigCache {
+    repo: Arc<dyn IConfigRepository>,
+    /// Cached lookups. `None` records a key known to be absent (negative caching).
+    entries: RwLock<HashMap<String, Option<String>>>,
+    /// True while `entries` mirrors the whole table, so `get_all` can be served from memory.
+    complete: AtomicBool,
+    /// Bumped on every invalidation so in-flight repository reads don't store stale values.
+    generation: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl ConfigCache {
+    pub fn new(repo: Arc<dyn IConfigRepository>) -> Self {
+        Self {
+            repo,
+            entries: RwLock::new(HashMap::new()),
+            complete: AtomicBool::new(false),
+            generation: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Load every setting into memory. Called once at startup.
+    pub async fn warm(&self) -> Result<usize, AppError> {
+        let generation = self.generation.load(Ordering::Acquire);
+        let all = self.repo.get_all().await?;
+        let count = all.len();
+        self.fill(all, generation);
+        Ok(count)
+    }
+
+    /// Read-through lookup of a single key.
+    pub async fn get(&self, key: &str) -> Result<Option<String>, AppError> {
+        if let Some(value) = self.lookup(key) {
+            return Ok(value);
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let generation = self.generation.load(Ordering::Acquire);
+        let value = self.repo.get(key).await?;
+
+        let mut entries = self.write();
+        if self.generation.load(Ordering::Acquire) == generation {
+            entries.insert(key.to_string(), value.clone());
+        }
+        Ok(value)
+    }
+
+    /// Read-through lookup of the whole settings table.
+    pub async fn get_all(&self) -> Result<HashMap<String, String>, AppError> {
+        if self.complete.load(Ordering::Acquire) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(self.snapshot());
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let generation = self.generation.load(Ordering::Acquire);
+        let all = self.repo.get_all().await?;
+        self.fill(all.clone(), generation);
+        Ok(all)
+    }
+
+    /// Synchronous, cache-only read for Rust callers (e.g. services checking a flag).
+    /// Returns `None` if the key is absent or not cached yet.
+    #[allow(dead_code)]
+    pub fn get_cached(&self, key: &str) -> Option<String> {
+        self.lookup(key).flatten()
+    }
+
+    /// Drop a single key from the cache; the next read goes to the repository.
+    pub fn invalidate(&self, key: &str) {
+        let mut entries = self.write();
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        entries.remove(key);
+        self.complete.store(false, Ordering::Release);
+    }
+
+    /// Drop every cached entry.
+    pub fn invalidate_all(&self) {
+        let mut entries = self.write();
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        entries.clear();
+        self.complete.store(false, Ordering::Release);
+    }
+
+    pub fn stats(&self) -> ConfigCacheStats {
+        ConfigCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.read().len(),
+            warm: self.complete.load(Ordering::Acquire),
+        }
+    }
+
+    /// Returns `Some(value)` on a cache hit (where `value` may itself be `None`
+    /// for a known-absent key), or `None` on a miss.
+    fn lookup(&self, key: &str) -> Option<Option<String>> {
+        let entries = self.read();
+        let value = match entries.get(key) {
+            Some(value) => Some(value.clone()),
+            // A complete cache knows every key, so absence is authoritative
+            None if self.complete.load(Ordering::Acquire) => Some(None),
+            None => None,
+        };
+        drop(entries);
+
+        if value.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        }
+        value
+    }
+
+    fn fill(&self, all: HashMap<String, String>, generation: u64) {
+        let mut entries = self.write();
+        if self.generation.load(Ordering::Acquire) != generation {
+            return;
+        }
+        entries.clear();
+        entries.extend(all.into_iter().map(|(k, v)| (k, Some(v))));
+        self.complete.store(true, Ordering::Release);
+    }
+
+    fn snapshot(&self) -> HashMap<String, String> {
+        self.read()
+            .iter()
+            .filter_map(|(k, v)| v.as_ref().map(|v| (k.clone(), v.clone())))
+            .collect()
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, HashMap<String, Option<String>>> {
+        self.entries.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<String, Option<String>>> {
+        self.entries.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl IEventSubscriber for ConfigCache {
+    fn on_event(&self, event: &DomainEvent) {
+        match event {
+            DomainEvent::ConfigChanged { key, .. } | DomainEvent::ConfigDeleted { key } => {
+                self.invalidate(key)
+            }
+            DomainEvent::ConfigBatchChanged { values } => {
+                values.keys().for_each(|key| self.invalidate(key))
+            }
+            DomainEvent::ConfigReset
+            | DomainEvent::DatabaseRestored { .. }
+            | DomainEvent::ArchiveImported { .. }
+            | DomainEvent::MigrationsRolledBack { .. } => self.invalidate_all(),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::{Mutex, Weak};
+    use async_trait::async_trait;
+
+    /// Counts repository reads; `during_get` runs while a `get` is in flight.
+    #[derive(Default)]
+    struct CountingRepo {
+        values: Mutex<HashMap<String, String>>,
+        gets: AtomicUsize,
+        get_alls: AtomicUsize,
+        during_get: Mutex<Option<Box<dyn FnOnce() + Send>>>,
+    }
+
+    impl CountingRepo {
+        fn with(values: &[(&str, &str)]) -> Arc<Self> {
+            let repo = Self::default();
+            repo.put_all(values);
+            Arc::new(repo)
+        }
+
+        fn put_all(&self, values: &[(&str, &str)]) {
+            let mut stored = self.values.lock().unwrap();
+            for (k, v) in values {
+                stored.insert(k.to_string(), v.to_string());
+            }
+        }
+
+        fn gets(&self) -> usize {
+            self.gets.load(Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait]
+    impl IConfigRepository for CountingRepo {
+        async fn get(&self, key: &str) -> Result<Option<String>, AppError> {
+            self.gets.fetch_add(1, Ordering::SeqCst);
+            let value = self.values.lock().unwrap().get(key).cloned();
+            if let Some(hook) = self.during_get.lock().unwrap().take() {
+                hook();
+            }
+            Ok(value)
+        }
+
+        async fn set(&self, key: &str, value: &str) -> Result<(), AppError> {
+            self.put_all(&[(key, value)]);
+            Ok(())
+        }
+
+        async fn get_all(&self) -> Result<HashMap<String, String>, AppError> {
+            self.get_alls.fetch_add(1, Ordering::SeqCst);
+            Ok(self.values.lock().unwrap().clone())
+        }
+
+        async fn set_many(&self, values: &HashMap<String, String>) -> Result<(), AppError> {
+            self.values.lock().unwrap().extend(values.clone());
+            Ok(())
+        }
+
+        async fn delete(&self, key: &str) -> Result<(), AppError> {
+            self.values.lock().unwrap().remove(key);
+            Ok(())
+        }
+
+        async fn clear(&self, keep: &[&str]) -> Result<u64, AppError> {
+            let mut values = self.values.lock().unwrap();
+            let count = values.len();
+            values.retain(|key, _| keep.contains(&key.as_str()));
+            Ok((count - values.len()) as u64)
+        }
+    }
+
+    #[tokio::test]
+    async fn get_reads_through_once() {
+        let repo = CountingRepo::with(&[("theme", "dark")]);
+        let cache = ConfigCache::new(repo.clone());
+
+        assert_eq!(cache.get("theme").await.unwrap().as_deref(), Some("dark"));
+        assert_eq!(cache.get("theme").await.unwrap().as_deref(), Some("dark"));
+        assert_eq!(repo.gets(), 1);
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries, stats.warm), (1, 1, 1, false));
+    }
+
+    #[tokio::test]
+    async fn absent_keys_are_cached() {
+        let repo = CountingRepo::with(&[]);
+        let cache = ConfigCache::new(repo.clone());
+
+        assert_eq!(cache.get("missing").await.unwrap(), None);
+        assert_eq!(cache.get("missing").await.unwrap(), None);
+        assert_eq!(repo.gets(), 1);
+        assert_eq!(cache.get_cached("missing"), None);
+    }
+
+    #[tokio::test]
+    async fn warm_cache_answers_everything() {
+        let repo = CountingRepo::with(&[("theme", "dark"), ("lang", "en")]);
+        let cache = ConfigCache::new(repo.clone());
+
+        assert_eq!(cache.warm().await.unwrap(), 2);
+        assert_eq!(cache.get("lang").await.unwrap().as_deref(), Some("en"));
+        // Complete, so absence is known without asking the repository
+        assert_eq!(cache.get("unknown").await.unwrap(), None);
+        assert_eq!(cache.get_all().await.unwrap().len(), 2);
+        assert_eq!(repo.gets(), 0);
+        assert_eq!(repo.get_alls.load(Ordering::SeqCst), 1);
+        assert!(cache.stats().warm);
+    }
+
+    #[tokio::test]
+    async fn change_event_invalidates_key() {
+        let repo = CountingRepo::with(&[("theme", "dark"), ("lang", "en")]);
+        let cache = ConfigCache::new(repo.clone());
+        cache.warm().await.unwrap();
+
+        repo.put_all(&[("theme", "light")]);
+        cache.on_event(&DomainEvent::ConfigChanged { key: "theme".into(), value: "light".into() });
+
+        assert!(!cache.stats().warm);
+        assert_eq!(cache.get_cached("theme"), None);
+        assert_eq!(cache.get_cached("lang").as_deref(), Some("en"));
+        assert_eq!(cache.get("theme").await.unwrap().as_deref(), Some("light"));
+        assert_eq!(repo.gets(), 1);
+    }
+
+    #[tokio::test]
+    async fn reset_event_invalidates_everything() {
+        let repo = CountingRepo::with(&[("theme", "dark")]);
+        let cache = ConfigCache::new(repo.clone());
+        cache.warm().await.unwrap();
+
+        cache.on_event(&DomainEvent::ConfigReset);
+
+        let stats = cache.stats();
+        assert_eq!((stats.entries, stats.warm), (0, false));
+    }
+
+    #[tokio::test]
+    async fn migration_rollback_invalidates_everything() {
+        let repo = CountingRepo::with(&[("theme", "dark")]);
+        let cache = ConfigCache::new(repo.clone());
+        cache.warm().await.unwrap();
+
+        cache.on_event(&DomainEvent::MigrationsRolledBack { version: 0 });
+
+        let stats = cache.stats();
+        assert_eq!((stats.entries, stats.warm), (0, false));
+    }
+
+    #[tokio::test]
+    async fn read_racing_an_invalidation_is_not_stored() {
+        let repo = CountingRepo::with(&[("theme", "dark")]);
+        let cache = Arc::new(ConfigCache::new(repo.clone()));
+
+        // The value changes while the first read is in flight
+        let weak: Weak<ConfigCache> = Arc::downgrade(&cache);
+        let changed = repo.clone();
+        *repo.during_get.lock().unwrap() = Some(Box::new(move || {
+            changed.put_all(&[("theme", "light")]);
+            if let Some(cache) = weak.upgrade() {
+                cache.invalidate("theme");
+            }
+        }));
+
+        assert_eq!(cache.get("theme").await.unwrap().as_deref(), Some("dark"));
+        assert_eq!(cache.get_cached("theme"), None);
+        assert_eq!(cache.get("theme").await.unwrap().as_deref(), Some("light"));
+        assert_eq!(repo.gets(), 2);
+    }
+}
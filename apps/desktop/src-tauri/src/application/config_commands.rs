@@ -3,25 +3,47 @@
 use std::sync::Arc;
 use async_trait::async_trait;
 use crate::domain::cqrs::CommandHandler;
-use crate::domain::config::{IConfigRepository, SetConfigCmd};
+use crate::domain::config::{
+    DeleteConfigCmd, IConfigRepository, ResetConfigCmd, SetConfigCmd, SetManyConfigCmd, StaticConfig,
+    INTERNAL_SETTINGS,
+};
 use crate::domain::events::{DomainEvent, IEventPublisher};
 use crate::error::AppError;
 
 /// Handles config-related commands (write operations).
+/// Cheap to clone, so other services can write settings through it.
+#[derive(Clone)]
 pub struct ConfigCommandHandler {
     repo: Arc<dyn IConfigRepository>,
     publisher: Arc<dyn IEventPublisher>,
+    layers: Arc<StaticConfig>,
 }
 
 impl ConfigCommandHandler {
-    pub fn new(repo: Arc<dyn IConfigRepository>, publisher: Arc<dyn IEventPublisher>) -> Self {
-        Self { repo, publisher }
+    pub fn new(
+        repo: Arc<dyn IConfigRepository>,
+        publisher: Arc<dyn IEventPublisher>,
+        layers: Arc<StaticConfig>,
+    ) -> Self {
+        Self { repo, publisher, layers }
+    }
+
+    /// Keys locked by the system config file cannot be written from the UI.
+    fn ensure_unlocked(&self, key: &str) -> Result<(), AppError> {
+        if self.layers.is_locked(key) {
+            return Err(AppError::Domain(format!(
+                "Setting '{}' is locked by the system configuration",
+                key
+            )));
+        }
+        Ok(())
     }
 }
 
 #[async_trait]
 impl CommandHandler<SetConfigCmd, ()> for ConfigCommandHandler {
     async fn handle(&self, cmd: SetConfigCmd) -> Result<(), AppError> {
+        self.ensure_unlocked(&cmd.key)?;
         self.repo.set(&cmd.key, &cmd.value).await?;
 
         // Publish domain event
@@ -33,3 +55,164 @@ impl CommandHandler<SetConfigCmd, ()> for ConfigCommandHandler {
         Ok(())
     }
 }
+
+#[async_trait]
+impl CommandHandler<SetManyConfigCmd, ()> for ConfigCommandHandler {
+    async fn handle(&self, cmd: SetManyConfigCmd) -> Result<(), AppError> {
+        if cmd.values.is_empty() {
+            return Ok(());
+        }
+        for key in cmd.values.keys() {
+            self.ensure_unlocked(key)?;
+        }
+
+        self.repo.set_many(&cmd.values).await?;
+
+        // One event for the whole batch
+        self.publisher.publish(DomainEvent::ConfigBatchChanged { values: cmd.values });
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CommandHandler<DeleteConfigCmd, ()> for ConfigCommandHandler {
+    async fn handle(&self, cmd: DeleteConfigCmd) -> Result<(), AppError> {
+        self.repo.delete(&cmd.key).await?;
+
+        self.publisher.publish(DomainEvent::ConfigDeleted { key: cmd.key });
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CommandHandler<ResetConfigCmd, u64> for ConfigCommandHandler {
+    async fn handle(&self, _cmd: ResetConfigCmd) -> Result<u64, AppError> {
+        let removed = self.repo.clear(&INTERNAL_SETTINGS).await?;
+
+        self.publisher.publish(DomainEvent::ConfigReset);
+
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+    use crate::application::config_cache::ConfigCache;
+    use crate::domain::maintenance::MAINTENANCE_LAST_RUN_KEY;
+    use crate::infra::event_publisher::RecordingEventPublisher;
+    use crate::infra::memory::InMemoryConfigRepository;
+
+    fn handler() -> (ConfigCommandHandler, Arc<InMemoryConfigRepository>, Arc<RecordingEventPublisher>) {
+        let repo = Arc::new(InMemoryConfigRepository::new());
+        let publisher = Arc::new(RecordingEventPublisher::new());
+        let handler = ConfigCommandHandler::new(repo.clone(), publisher.clone(), Arc::new(StaticConfig::default()));
+        (handler, repo, publisher)
+    }
+
+    #[tokio::test]
+    async fn set_publishes_the_change() {
+        let (handler, repo, publisher) = handler();
+        handler
+            .handle(SetConfigCmd { key: "theme_mode".to_string(), value: "dark".to_string() })
+            .await
+            .unwrap();
+
+        assert_eq!(repo.get("theme_mode").await.unwrap().as_deref(), Some("dark"));
+        assert!(matches!(
+            publisher.take().as_slice(),
+            [DomainEvent::ConfigChanged { key, value }] if key == "theme_mode" && value == "dark"
+        ));
+    }
+
+    #[tokio::test]
+    async fn set_many_publishes_one_batch() {
+        let (handler, repo, publisher) = handler();
+        let values = HashMap::from([
+            ("theme_mode".to_string(), "dark".to_string()),
+            ("language".to_string(), "zh".to_string()),
+        ]);
+        handler.handle(SetManyConfigCmd { values: values.clone() }).await.unwrap();
+
+        assert_eq!(repo.get("language").await.unwrap().as_deref(), Some("zh"));
+        assert!(matches!(
+            publisher.take().as_slice(),
+            [DomainEvent::ConfigBatchChanged { values: published }] if *published == values
+        ));
+
+        // An empty batch is a no-op
+        handler.handle(SetManyConfigCmd { values: HashMap::new() }).await.unwrap();
+        assert!(publisher.events().is_empty());
+    }
+
+    #[tokio::test]
+    async fn delete_publishes_the_key() {
+        let (handler, repo, publisher) = handler();
+        repo.set("theme_mode", "dark").await.unwrap();
+
+        handler.handle(DeleteConfigCmd { key: "theme_mode".to_string() }).await.unwrap();
+
+        assert_eq!(repo.get("theme_mode").await.unwrap(), None);
+        assert!(matches!(
+            publisher.take().as_slice(),
+            [DomainEvent::ConfigDeleted { key }] if key == "theme_mode"
+        ));
+    }
+
+    #[tokio::test]
+    async fn locked_keys_are_refused_without_an_event() {
+        let repo = Arc::new(InMemoryConfigRepository::new());
+        let publisher = Arc::new(RecordingEventPublisher::new());
+        let mut layers = StaticConfig::default();
+        layers.lock("theme_mode");
+        let handler = ConfigCommandHandler::new(repo.clone(), publisher.clone(), Arc::new(layers));
+
+        let single = handler
+            .handle(SetConfigCmd { key: "theme_mode".to_string(), value: "dark".to_string() })
+            .await;
+        assert!(matches!(single, Err(AppError::Domain(_))));
+
+        // One locked key rejects the whole batch
+        let values = HashMap::from([
+            ("theme_mode".to_string(), "dark".to_string()),
+            ("language".to_string(), "zh".to_string()),
+        ]);
+        assert!(handler.handle(SetManyConfigCmd { values }).await.is_err());
+
+        assert_eq!(repo.get("language").await.unwrap(), None);
+        assert!(publisher.events().is_empty());
+    }
+
+    #[tokio::test]
+    async fn subscribers_see_writes_before_they_return() {
+        let (handler, repo, publisher) = handler();
+        let cache = Arc::new(ConfigCache::new(repo.clone()));
+        publisher.subscribe(cache.clone());
+
+        repo.set("theme_mode", "light").await.unwrap();
+        assert_eq!(cache.get("theme_mode").await.unwrap().as_deref(), Some("light"));
+
+        handler
+            .handle(SetConfigCmd { key: "theme_mode".to_string(), value: "dark".to_string() })
+            .await
+            .unwrap();
+        assert_eq!(cache.get_cached("theme_mode"), None);
+        assert_eq!(cache.get("theme_mode").await.unwrap().as_deref(), Some("dark"));
+    }
+
+    #[tokio::test]
+    async fn reset_keeps_internal_settings() {
+        let (handler, repo, publisher) = handler();
+        repo.set("theme_mode", "dark").await.unwrap();
+        repo.set(MAINTENANCE_LAST_RUN_KEY, "2025-01-01T00:00:00Z").await.unwrap();
+
+        assert_eq!(handler.handle(ResetConfigCmd).await.unwrap(), 1);
+
+        assert_eq!(repo.get("theme_mode").await.unwrap(), None);
+        assert!(repo.get(MAINTENANCE_LAST_RUN_KEY).await.unwrap().is_some());
+        assert!(matches!(publisher.events().as_slice(), [DomainEvent::ConfigReset]));
+    }
+}
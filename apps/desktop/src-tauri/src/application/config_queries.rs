@@ -3,31 +3,102 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 use async_trait::async_trait;
+use crate::application::config_cache::ConfigCache;
 use crate::domain::cqrs::QueryHandler;
-use crate::domain::config::{GetAllConfigQuery, GetConfigQuery, IConfigRepository};
+use crate::domain::config::{
+    ConfigCacheStats, ConfigLayer, GetAllConfigQuery, GetConfigCacheStatsQuery, GetConfigQuery,
+    GetResolvedConfigQuery, IConfigRepository, ResolvedSetting, StaticConfig, INTERNAL_SETTINGS,
+};
 use crate::error::AppError;
 
 /// Handles config-related queries (read operations).
+/// Database reads are served from an in-memory `ConfigCache`, then layered
+/// on top of the static config (defaults, files, env, CLI). Locked keys
+/// always resolve from the static config.
+/// Cheap to clone: clones share the same cache.
+#[derive(Clone)]
 pub struct ConfigQueryHandler {
-    repo: Arc<dyn IConfigRepository>,
+    cache: Arc<ConfigCache>,
+    layers: Arc<StaticConfig>,
 }
 
 impl ConfigQueryHandler {
-    pub fn new(repo: Arc<dyn IConfigRepository>) -> Self {
-        Self { repo }
+    pub fn new(repo: Arc<dyn IConfigRepository>, layers: Arc<StaticConfig>) -> Self {
+        Self {
+            cache: Arc::new(ConfigCache::new(repo)),
+            layers,
+        }
+    }
+
+    /// The underlying cache, e.g. to subscribe it to domain events
+    /// or to read settings synchronously from Rust code.
+    pub fn cache(&self) -> Arc<ConfigCache> {
+        self.cache.clone()
+    }
+
+    async fn resolve_all(&self) -> Result<Vec<ResolvedSetting>, AppError> {
+        let mut resolved: HashMap<String, ResolvedSetting> = self
+            .layers
+            .iter()
+            .map(|(key, value, layer)| {
+                let setting = ResolvedSetting {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                    layer,
+                    locked: self.layers.is_locked(key),
+                };
+                (key.to_string(), setting)
+            })
+            .collect();
+
+        for (key, value) in self.cache.get_all().await? {
+            if self.layers.is_locked(&key) || INTERNAL_SETTINGS.contains(&key.as_str()) {
+                continue;
+            }
+            resolved.insert(key.clone(), ResolvedSetting {
+                key,
+                value,
+                layer: ConfigLayer::Database,
+                locked: false,
+            });
+        }
+
+        let mut settings: Vec<ResolvedSetting> = resolved.into_values().collect();
+        settings.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(settings)
     }
 }
 
 #[async_trait]
 impl QueryHandler<GetConfigQuery, Option<String>> for ConfigQueryHandler {
     async fn handle(&self, query: GetConfigQuery) -> Result<Option<String>, AppError> {
-        self.repo.get(&query.key).await
+        let fallback = self.layers.get(&query.key).map(|(value, _)| value.to_string());
+        if self.layers.is_locked(&query.key) {
+            return Ok(fallback);
+        }
+
+        Ok(self.cache.get(&query.key).await?.or(fallback))
     }
 }
 
 #[async_trait]
 impl QueryHandler<GetAllConfigQuery, HashMap<String, String>> for ConfigQueryHandler {
     async fn handle(&self, _query: GetAllConfigQuery) -> Result<HashMap<String, String>, AppError> {
-        self.repo.get_all().await
+        let settings = self.resolve_all().await?;
+        Ok(settings.into_iter().map(|s| (s.key, s.value)).collect())
+    }
+}
+
+#[async_trait]
+impl QueryHandler<GetResolvedConfigQuery, Vec<ResolvedSetting>> for ConfigQueryHandler {
+    async fn handle(&self, _query: GetResolvedConfigQuery) -> Result<Vec<ResolvedSetting>, AppError> {
+        self.resolve_all().await
+    }
+}
+
+#[async_trait]
+impl QueryHandler<GetConfigCacheStatsQuery, ConfigCacheStats> for ConfigQueryHandler {
+    async fn handle(&self, _query: GetConfigCacheStatsQuery) -> Result<ConfigCacheStats, AppError> {
+        Ok(self.cache.stats())
     }
 }
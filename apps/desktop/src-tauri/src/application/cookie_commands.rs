@@ -0,0 +1,31 @@
+//! Cookie command handlers - handles clearing the HTTP client's cookie jar.
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use tracing::info;
+use crate::domain::cqrs::CommandHandler;
+use crate::domain::http::{ClearCookiesCmd, ICookieJar};
+use crate::error::AppError;
+
+/// Handles cookie commands (write operations).
+pub struct CookieCommandHandler {
+    jar: Arc<dyn ICookieJar>,
+}
+
+impl CookieCommandHandler {
+    pub fn new(jar: Arc<dyn ICookieJar>) -> Self {
+        Self { jar }
+    }
+}
+
+#[async_trait]
+impl CommandHandler<ClearCookiesCmd, u64> for CookieCommandHandler {
+    async fn handle(&self, cmd: ClearCookiesCmd) -> Result<u64, AppError> {
+        let removed = self.jar.clear(cmd.domain.as_deref())?;
+        match &cmd.domain {
+            Some(domain) => info!("Cleared {} cookies of {}", removed, domain),
+            None => info!("Cleared {} cookies", removed),
+        }
+        Ok(removed)
+    }
+}
@@ -0,0 +1,25 @@
+//! Cookie query handlers - handles inspecting the HTTP client's cookie jar.
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use crate::domain::cqrs::QueryHandler;
+use crate::domain::http::{CookieInfo, ICookieJar, ListCookiesQuery};
+use crate::error::AppError;
+
+/// Handles cookie queries (read operations).
+pub struct CookieQueryHandler {
+    jar: Arc<dyn ICookieJar>,
+}
+
+impl CookieQueryHandler {
+    pub fn new(jar: Arc<dyn ICookieJar>) -> Self {
+        Self { jar }
+    }
+}
+
+#[async_trait]
+impl QueryHandler<ListCookiesQuery, Vec<CookieInfo>> for CookieQueryHandler {
+    async fn handle(&self, query: ListCookiesQuery) -> Result<Vec<CookieInfo>, AppError> {
+        Ok(self.jar.list(query.domain.as_deref()))
+    }
+}
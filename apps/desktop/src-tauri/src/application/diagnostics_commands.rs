@@ -0,0 +1,26 @@
+//! Diagnostics command handlers - handles resetting the query statistics.
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use crate::domain::cqrs::CommandHandler;
+use crate::domain::diagnostics::{IQueryStats, ResetQueryStatsCmd};
+use crate::error::AppError;
+
+/// Handles query statistics commands (write operations).
+pub struct QueryStatsCommandHandler {
+    stats: Arc<dyn IQueryStats>,
+}
+
+impl QueryStatsCommandHandler {
+    pub fn new(stats: Arc<dyn IQueryStats>) -> Self {
+        Self { stats }
+    }
+}
+
+#[async_trait]
+impl CommandHandler<ResetQueryStatsCmd> for QueryStatsCommandHandler {
+    async fn handle(&self, _cmd: ResetQueryStatsCmd) -> Result<(), AppError> {
+        self.stats.reset();
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+//! Diagnostics query handlers - handles inspecting the database.
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use crate::domain::cqrs::QueryHandler;
+use crate::domain::diagnostics::{
+    CheckDbHealthQuery, DbHealthReport, GetQueryStatsQuery, IDbDiagnostics, IQueryStats, QueryStat,
+};
+use crate::error::AppError;
+
+/// Handles diagnostics queries (read operations).
+pub struct DiagnosticsQueryHandler {
+    diagnostics: Arc<dyn IDbDiagnostics>,
+}
+
+impl DiagnosticsQueryHandler {
+    pub fn new(diagnostics: Arc<dyn IDbDiagnostics>) -> Self {
+        Self { diagnostics }
+    }
+}
+
+#[async_trait]
+impl QueryHandler<CheckDbHealthQuery, DbHealthReport> for DiagnosticsQueryHandler {
+    async fn handle(&self, _query: CheckDbHealthQuery) -> Result<DbHealthReport, AppError> {
+        self.diagnostics.collect().await
+    }
+}
+
+/// Handles query statistics queries (read operations).
+pub struct QueryStatsQueryHandler {
+    stats: Arc<dyn IQueryStats>,
+}
+
+impl QueryStatsQueryHandler {
+    pub fn new(stats: Arc<dyn IQueryStats>) -> Self {
+        Self { stats }
+    }
+}
+
+#[async_trait]
+impl QueryHandler<GetQueryStatsQuery, Vec<QueryStat>> for QueryStatsQueryHandler {
+    async fn handle(&self, _query: GetQueryStatsQuery) -> Result<Vec<QueryStat>, AppError> {
+        Ok(self.stats.snapshot())
+    }
+}
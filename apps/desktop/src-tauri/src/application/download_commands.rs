@@ -0,0 +1,96 @@
+//! Download command handlers - handles streaming downloads, progress events and cancellation.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+use uuid::Uuid;
+use crate::domain::cqrs::CommandHandler;
+use crate::domain::downloads::{
+    CancelDownloadCmd, DownloadResult, IFileDownloader, StartDownloadCmd, TransferProgress,
+};
+use crate::domain::events::{DomainEvent, IEventPublisher};
+use crate::error::AppError;
+
+/// Minimum time between progress events of one download
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Handles download-related commands (write operations).
+pub struct DownloadCommandHandler {
+    downloader: Arc<dyn IFileDownloader>,
+    publisher: Arc<dyn IEventPublisher>,
+    /// Running downloads by id
+    active: Mutex<HashMap<String, CancellationToken>>,
+}
+
+impl DownloadCommandHandler {
+    pub fn new(downloader: Arc<dyn IFileDownloader>, publisher: Arc<dyn IEventPublisher>) -> Self {
+        Self {
+            downloader,
+            publisher,
+            active: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl CommandHandler<StartDownloadCmd, DownloadResult> for DownloadCommandHandler {
+    async fn handle(&self, cmd: StartDownloadCmd) -> Result<DownloadResult, AppError> {
+        let id = cmd.id.unwrap_or_else(|| Uuid::new_v4().to_string());
+        let cancel = CancellationToken::new();
+        {
+            let mut active = self.active.lock().unwrap_or_else(|e| e.into_inner());
+            if active.contains_key(&id) {
+                return Err(AppError::Domain(format!("Download {} is already running", id)));
+            }
+            active.insert(id.clone(), cancel.clone());
+        }
+
+        info!("Downloading {} to {} (id {})", cmd.url, cmd.path, id);
+        let last_event: Mutex<Option<Instant>> = Mutex::new(None);
+        let progress = |p: &TransferProgress| {
+            let mut last = last_event.lock().unwrap_or_else(|e| e.into_inner());
+            let due = last.is_none_or(|at| at.elapsed() >= PROGRESS_INTERVAL) || Some(p.bytes) == p.total;
+            if due {
+                *last = Some(Instant::now());
+                self.publisher.publish(DomainEvent::DownloadProgress { id: id.clone(), bytes: p.bytes, total: p.total });
+            }
+        };
+
+        let result = self
+            .downloader
+            .download(&cmd.url, &cmd.path, cmd.sha256.as_deref(), cmd.overwrite, &progress, cancel)
+            .await;
+        self.active.lock().unwrap_or_else(|e| e.into_inner()).remove(&id);
+
+        let file = result.inspect_err(|e| warn!("Download {} failed: {:?}", id, e))?;
+        // Always end with a complete event, even if the last chunk was throttled or the size was unknown
+        self.publisher.publish(DomainEvent::DownloadProgress {
+            id: id.clone(),
+            bytes: file.bytes,
+            total: Some(file.bytes),
+        });
+
+        info!("Download {} finished ({} bytes)", id, file.bytes);
+        Ok(DownloadResult {
+            id,
+            path: cmd.path,
+            bytes: file.bytes,
+            sha256: file.sha256,
+        })
+    }
+}
+
+#[async_trait]
+impl CommandHandler<CancelDownloadCmd, ()> for DownloadCommandHandler {
+    async fn handle(&self, cmd: CancelDownloadCmd) -> Result<(), AppError> {
+        let active = self.active.lock().unwrap_or_else(|e| e.into_inner());
+        let token = active
+            .get(&cmd.id)
+            .ok_or_else(|| AppError::NotFound(format!("Download {} not found", cmd.id)))?;
+        token.cancel();
+        Ok(())
+    }
+}
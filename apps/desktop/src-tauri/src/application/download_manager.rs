@@ -0,0 +1,576 @@
+//! Download manager - a persistent queue of resumable downloads.
+//!
+//! Downloads live in the `downloads` table and run in the background, at most
+//! `download_concurrency` at a time. Data is written to `<path>.part`; whatever
+//! is there survives a pause, a failure or a restart, and the rest is fetched
+//! with a Range request when the download runs again.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+use uuid::Uuid;
+use crate::application::ConfigQueryHandler;
+use crate::domain::config::GetConfigQuery;
+use crate::domain::cqrs::{CommandHandler, QueryHandler};
+use crate::domain::downloads::{
+    CancelManagedDownloadCmd, Download, DownloadState, EnqueueDownloadCmd, IDownloadRepository,
+    IFileDownloader, PauseDownloadCmd, ResumeDownloadCmd, TransferProgress,
+    DEFAULT_DOWNLOAD_CONCURRENCY, DOWNLOAD_CONCURRENCY_KEY,
+};
+use crate::domain::events::{DomainEvent, IEventPublisher};
+use crate::error::AppError;
+
+/// Minimum time between progress events of one download
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+/// How often the position of a running download is written to the database
+const SAVE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Handles download manager commands and runs the queue.
+#[derive(Clone)]
+pub struct DownloadManager {
+    repo: Arc<dyn IDownloadRepository>,
+    downloader: Arc<dyn IFileDownloader>,
+    publisher: Arc<dyn IEventPublisher>,
+    config: ConfigQueryHandler,
+    /// Running downloads by id
+    running: Arc<Mutex<HashMap<String, CancellationToken>>>,
+    /// Serializes state changes with scheduling, so a paused download is never started
+    schedule_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl DownloadManager {
+    pub fn new(
+        repo: Arc<dyn IDownloadRepository>,
+        downloader: Arc<dyn IFileDownloader>,
+        publisher: Arc<dyn IEventPublisher>,
+        config: ConfigQueryHandler,
+    ) -> Self {
+        Self {
+            repo,
+            downloader,
+            publisher,
+            config,
+            running: Arc::new(Mutex::new(HashMap::new())),
+            schedule_lock: Arc::new(tokio::sync::Mutex::new(())),
+        }
+    }
+
+    /// Queue the downloads interrupted by the last shutdown and start the queue.
+    pub async fn start(&self) -> Result<(), AppError> {
+        let requeued = self.repo.requeue_interrupted().await?;
+        if requeued > 0 {
+            info!("Resuming {} interrupted downloads", requeued);
+        }
+        self.schedule().await
+    }
+
+    /// Start queued downloads, oldest first, up to the concurrency limit.
+    async fn schedule(&self) -> Result<(), AppError> {
+        let _guard = self.schedule_lock.lock().await;
+        let limit = self.concurrency().await?;
+
+        for download in self.repo.list_queued().await? {
+            let cancel = {
+                let mut running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+                if running.len() >= limit {
+                    break;
+                }
+                let token = CancellationToken::new();
+                running.insert(download.id.clone(), token.clone());
+                token
+            };
+
+            if let Err(e) = self.set_state(&download.id, DownloadState::Running, None).await {
+                self.running.lock().unwrap_or_else(|e| e.into_inner()).remove(&download.id);
+                return Err(e);
+            }
+            self.spawn(download, cancel);
+        }
+        Ok(())
+    }
+
+    fn spawn(&self, download: Download, cancel: CancellationToken) {
+        let manager = self.clone();
+        // Boxed: a finished task schedules the next downloads, which spawns tasks again
+        let task: Pin<Box<dyn Future<Output = ()> + Send>> = Box::pin(async move {
+            let id = download.id.clone();
+            manager.run(download, cancel).await;
+            manager.running.lock().unwrap_or_else(|e| e.into_inner()).remove(&id);
+            if let Err(e) = manager.schedule().await {
+                warn!("Failed to start queued downloads: {:?}", e);
+            }
+        });
+        tauri::async_runtime::spawn(task);
+    }
+
+    async fn run(&self, download: Download, cancel: CancellationToken) {
+        let id = download.id.as_str();
+
+        // The target may have been replaced since the download was queued
+        let target = match self.downloader.check_target(&download.path, download.overwrite) {
+            Ok(target) => target.to_string_lossy().into_owned(),
+            Err(e) => {
+                warn!("Download {} refused: {:?}", id, e);
+                let _guard = self.schedule_lock.lock().await;
+                if cancel.is_cancelled() {
+                    self.stopped(id, &format!("{}.part", download.path)).await;
+                } else if let Err(e) = self.set_state(id, DownloadState::Failed, Some(&e.to_string())).await {
+                    warn!("Failed to mark download {} as failed: {:?}", id, e);
+                }
+                return;
+            }
+        };
+        let partial = format!("{}.part", target);
+
+        // The partial file is the source of truth; the stored position may lag behind it
+        let offset = tokio::fs::metadata(&partial).await.map(|m| m.len()).unwrap_or(0);
+        info!("Starting download {} at byte {}", id, offset);
+
+        let from = if offset > 0 {
+            TransferProgress {
+                bytes: offset,
+                total: download.total_bytes.map(|total| total as u64),
+                etag: download.etag.clone(),
+                last_modified: download.last_modified.clone(),
+            }
+        } else {
+            TransferProgress::default()
+        };
+        let latest = Mutex::new(from.clone());
+        let last_event: Mutex<Option<Instant>> = Mutex::new(None);
+        let progress = |p: &TransferProgress| {
+            *latest.lock().unwrap_or_else(|e| e.into_inner()) = p.clone();
+            let mut last = last_event.lock().unwrap_or_else(|e| e.into_inner());
+            if last.is_none_or(|at| at.elapsed() >= PROGRESS_INTERVAL) || Some(p.bytes) == p.total {
+                *last = Some(Instant::now());
+                self.publisher.publish(DomainEvent::DownloadProgress { id: id.to_string(), bytes: p.bytes, total: p.total });
+            }
+        };
+
+        let transfer = self.downloader.transfer(&download.url, &partial, &from, &progress, cancel.clone());
+        tokio::pin!(transfer);
+        let mut save = tokio::time::interval(SAVE_INTERVAL);
+        let result = loop {
+            tokio::select! {
+                result = &mut transfer => break result,
+                _ = save.tick() => self.save_progress(id, &latest).await,
+            }
+        };
+        self.save_progress(id, &latest).await;
+
+        // Pause and cancel record their state and stop the transfer under the schedule
+        // lock, so a transfer that completed meanwhile must not override that state
+        let _guard = self.schedule_lock.lock().await;
+        if cancel.is_cancelled() {
+            self.stopped(id, &partial).await;
+            return;
+        }
+
+        let outcome = match result {
+            Ok(_) => self.downloader.finish(&partial, &target, download.sha256.as_deref(), download.overwrite).await,
+            Err(e) => Err(e),
+        };
+        match outcome {
+            Ok(file) => {
+                info!("Download {} finished ({} bytes)", id, file.bytes);
+                self.publisher.publish(DomainEvent::DownloadProgress {
+                    id: id.to_string(),
+                    bytes: file.bytes,
+                    total: Some(file.bytes),
+                });
+                if let Err(e) = self.set_state(id, DownloadState::Completed, None).await {
+                    warn!("Failed to mark download {} as completed: {:?}", id, e);
+                }
+            }
+            Err(e) => {
+                warn!("Download {} failed: {:?}", id, e);
+                if let Err(e) = self.set_state(id, DownloadState::Failed, Some(&e.to_string())).await {
+                    warn!("Failed to mark download {} as failed: {:?}", id, e);
+                }
+            }
+        }
+    }
+
+    /// A run was paused or cancelled; the command already recorded the new state.
+    async fn stopped(&self, id: &str, partial: &str) {
+        if let Ok(Some(current)) = self.repo.find(id).await {
+            if current.state == DownloadState::Cancelled {
+                let _ = tokio::fs::remove_file(partial).await;
+            }
+        }
+    }
+
+    async fn save_progress(&self, id: &str, latest: &Mutex<TransferProgress>) {
+        let progress = latest.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        if let Err(e) = self.repo.save_progress(id, &progress).await {
+            warn!("Failed to save progress of download {}: {:?}", id, e);
+        }
+    }
+
+    async fn set_state(&self, id: &str, state: DownloadState, error: Option<&str>) -> Result<(), AppError> {
+        self.repo.set_state(id, state, error).await?;
+        self.publisher.publish(DomainEvent::DownloadStateChanged { id: id.to_string(), state });
+        Ok(())
+    }
+
+    async fn find(&self, id: &str) -> Result<Download, AppError> {
+        self.repo
+            .find(id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Download {} not found", id)))
+    }
+
+    /// Stop the transfer if the download is running. Returns whether it was.
+    fn stop(&self, id: &str) -> bool {
+        match self.running.lock().unwrap_or_else(|e| e.into_inner()).get(id) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    async fn concurrency(&self) -> Result<usize, AppError> {
+        let value = self.config.handle(GetConfigQuery { key: DOWNLOAD_CONCURRENCY_KEY.to_string() }).await?;
+        let limit = value
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(DEFAULT_DOWNLOAD_CONCURRENCY);
+        Ok(limit.max(1))
+    }
+}
+
+#[async_trait]
+impl CommandHandler<EnqueueDownloadCmd, Download> for DownloadManager {
+    async fn handle(&self, cmd: EnqueueDownloadCmd) -> Result<Download, AppError> {
+        let target = self.downloader.check_target(&cmd.path, cmd.overwrite)?;
+        let download = self
+            .repo
+            .insert(&Download {
+                id: Uuid::new_v4().to_string(),
+                url: cmd.url,
+                path: target.to_string_lossy().into_owned(),
+                state: DownloadState::Queued,
+                bytes_done: 0,
+                total_bytes: None,
+                etag: None,
+                last_modified: None,
+                sha256: cmd.sha256,
+                overwrite: cmd.overwrite,
+                error: None,
+                created_at: String::new(),
+                updated_at: String::new(),
+            })
+            .await?;
+        info!("Queued download {} of {}", download.id, download.url);
+        self.publisher.publish(DomainEvent::DownloadStateChanged { id: download.id.clone(), state: download.state });
+
+        self.schedule().await?;
+        Ok(download)
+    }
+}
+
+#[async_trait]
+impl CommandHandler<PauseDownloadCmd, ()> for DownloadManager {
+    async fn handle(&self, cmd: PauseDownloadCmd) -> Result<(), AppError> {
+        let _guard = self.schedule_lock.lock().await;
+        let download = self.find(&cmd.id).await?;
+        if !matches!(download.state, DownloadState::Queued | DownloadState::Running) {
+            return Err(AppError::Domain(format!("Download {} is {:?} and cannot be paused", cmd.id, download.state)));
+        }
+
+        self.set_state(&cmd.id, DownloadState::Paused, None).await?;
+        self.stop(&cmd.id);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CommandHandler<ResumeDownloadCmd, ()> for DownloadManager {
+    async fn handle(&self, cmd: ResumeDownloadCmd) -> Result<(), AppError> {
+        {
+            let _guard = self.schedule_lock.lock().await;
+            let download = self.find(&cmd.id).await?;
+            if !matches!(download.state, DownloadState::Paused | DownloadState::Failed) {
+                return Err(AppError::Domain(format!("Download {} is {:?} and cannot be resumed", cmd.id, download.state)));
+            }
+            self.set_state(&cmd.id, DownloadState::Queued, None).await?;
+        }
+        self.schedule().await
+    }
+}
+
+#[async_trait]
+impl CommandHandler<CancelManagedDownloadCmd, ()> for DownloadManager {
+    async fn handle(&self, cmd: CancelManagedDownloadCmd) -> Result<(), AppError> {
+        let _guard = self.schedule_lock.lock().await;
+        let download = self.find(&cmd.id).await?;
+        if matches!(download.state, DownloadState::Completed | DownloadState::Cancelled) {
+            return Err(AppError::Domain(format!("Download {} is {:?} and cannot be cancelled", cmd.id, download.state)));
+        }
+
+        self.set_state(&cmd.id, DownloadState::Cancelled, None).await?;
+        // A running download removes its partial file once the transfer has stopped
+        if !self.stop(&cmd.id) {
+            let _ = tokio::fs::remove_file(format!("{}.part", download.path)).await;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use tokio::sync::Semaphore;
+    use crate::domain::config::{IConfigRepository, StaticConfig};
+    use crate::domain::downloads::{DownloadedFile, ProgressFn};
+    use crate::infra::event_publisher::RecordingEventPublisher;
+    use crate::infra::memory::{InMemoryConfigRepository, InMemoryDownloadRepository};
+
+    /// Transfers block until `release` lets them finish, or until cancelled unless
+    /// `completes_when_cancelled` is set; paths under `/refused` fail the target check
+    struct FakeDownloader {
+        finished: Semaphore,
+        completes_when_cancelled: AtomicBool,
+        started: Mutex<Vec<String>>,
+        partials: Mutex<Vec<String>>,
+    }
+
+    impl FakeDownloader {
+        fn new() -> Self {
+            Self {
+                finished: Semaphore::new(0),
+                completes_when_cancelled: AtomicBool::new(false),
+                started: Mutex::new(Vec::new()),
+                partials: Mutex::new(Vec::new()),
+            }
+        }
+
+        fn release(&self, transfers: usize) {
+            self.finished.add_permits(transfers);
+        }
+
+        fn started(&self) -> Vec<String> {
+            self.started.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl IFileDownloader for FakeDownloader {
+        async fn download(
+            &self,
+            _url: &str,
+            _path: &str,
+            _expected_sha256: Option<&str>,
+            _overwrite: bool,
+            _progress: &ProgressFn<'_>,
+            _cancel: CancellationToken,
+        ) -> Result<DownloadedFile, AppError> {
+            unimplemented!("not used by the download manager")
+        }
+
+        fn check_target(&self, path: &str, _overwrite: bool) -> Result<PathBuf, AppError> {
+            match path.strip_prefix("/refused") {
+                Some(_) => Err(AppError::ScopeViolation(path.to_string())),
+                // Stands in for resolving a symlinked directory
+                None if path.starts_with("/resolved") => Ok(PathBuf::from(path)),
+                None => Ok(PathBuf::from("/resolved").join(path.trim_start_matches('/'))),
+            }
+        }
+
+        async fn transfer(
+            &self,
+            url: &str,
+            partial: &str,
+            from: &TransferProgress,
+            progress: &ProgressFn<'_>,
+            cancel: CancellationToken,
+        ) -> Result<TransferProgress, AppError> {
+            self.started.lock().unwrap().push(url.to_string());
+            self.partials.lock().unwrap().push(partial.to_string());
+            tokio::select! {
+                _ = cancel.cancelled(), if !self.completes_when_cancelled.load(Ordering::SeqCst) => {
+                    Err(AppError::Unknown("Download cancelled".to_string()))
+                }
+                permit = self.finished.acquire() => {
+                    permit.unwrap().forget();
+                    let done = TransferProgress { bytes: 10, total: Some(10), ..from.clone() };
+                    progress(&done);
+                    Ok(done)
+                }
+            }
+        }
+
+        async fn finish(
+            &self,
+            _partial: &str,
+            _path: &str,
+            _expected_sha256: Option<&str>,
+            _overwrite: bool,
+        ) -> Result<DownloadedFile, AppError> {
+            Ok(DownloadedFile { bytes: 10, sha256: String::new() })
+        }
+    }
+
+    async fn manager(concurrency: usize) -> (DownloadManager, Arc<InMemoryDownloadRepository>, Arc<FakeDownloader>) {
+        let repo = Arc::new(InMemoryDownloadRepository::new());
+        let downloader = Arc::new(FakeDownloader::new());
+        let config = Arc::new(InMemoryConfigRepository::new());
+        config.set(DOWNLOAD_CONCURRENCY_KEY, &concurrency.to_string()).await.unwrap();
+        let manager = DownloadManager::new(
+            repo.clone(),
+            downloader.clone(),
+            Arc::new(RecordingEventPublisher::new()),
+            ConfigQueryHandler::new(config, Arc::new(StaticConfig::default())),
+        );
+        (manager, repo, downloader)
+    }
+
+    async fn enqueue(manager: &DownloadManager, name: &str) -> String {
+        let cmd = EnqueueDownloadCmd {
+            url: format!("https://example.com/{}", name),
+            path: format!("/downloads/{}", name),
+            sha256: None,
+            overwrite: false,
+        };
+        manager.handle(cmd).await.unwrap().id
+    }
+
+    async fn state(repo: &InMemoryDownloadRepository, id: &str) -> DownloadState {
+        repo.find(id).await.unwrap().unwrap().state
+    }
+
+    /// Wait for the background tasks to move `id` into `expected`
+    async fn wait_for(repo: &InMemoryDownloadRepository, id: &str, expected: DownloadState) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while state(repo, id).await != expected {
+            assert!(Instant::now() < deadline, "download {} stayed {:?}, expected {:?}", id, state(repo, id).await, expected);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn queued_downloads_run_up_to_the_concurrency_limit() {
+        let (manager, repo, downloader) = manager(2).await;
+        let first = enqueue(&manager, "a").await;
+        let second = enqueue(&manager, "b").await;
+        let third = enqueue(&manager, "c").await;
+
+        assert_eq!(state(&repo, &first).await, DownloadState::Running);
+        assert_eq!(state(&repo, &second).await, DownloadState::Running);
+        assert_eq!(state(&repo, &third).await, DownloadState::Queued);
+
+        // A finished download makes room for the next one
+        downloader.release(1);
+        wait_for(&repo, &third, DownloadState::Running).await;
+        downloader.release(2);
+        for id in [&first, &second, &third] {
+            wait_for(&repo, id, DownloadState::Completed).await;
+        }
+        assert_eq!(downloader.started().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn downloads_use_the_resolved_target() {
+        let (manager, repo, downloader) = manager(1).await;
+        let id = enqueue(&manager, "a").await;
+        downloader.release(1);
+        wait_for(&repo, &id, DownloadState::Completed).await;
+
+        assert_eq!(repo.find(&id).await.unwrap().unwrap().path, "/resolved/downloads/a");
+        assert_eq!(*downloader.partials.lock().unwrap(), ["/resolved/downloads/a.part"]);
+    }
+
+    #[tokio::test]
+    async fn refused_targets_are_not_queued() {
+        let (manager, repo, _) = manager(1).await;
+        let cmd = EnqueueDownloadCmd {
+            url: "https://example.com/a".to_string(),
+            path: "/refused/a".to_string(),
+            sha256: None,
+            overwrite: false,
+        };
+        assert!(matches!(manager.handle(cmd).await, Err(AppError::ScopeViolation(_))));
+        assert!(repo.list().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn pause_stops_the_transfer_and_resume_queues_it_again() {
+        let (manager, repo, downloader) = manager(1).await;
+        let id = enqueue(&manager, "a").await;
+        // A transfer that completes right as it is paused must not override the pause
+        downloader.completes_when_cancelled.store(true, Ordering::SeqCst);
+        manager.handle(PauseDownloadCmd { id: id.clone() }).await.unwrap();
+        downloader.release(1);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(state(&repo, &id).await, DownloadState::Paused);
+
+        downloader.completes_when_cancelled.store(false, Ordering::SeqCst);
+        manager.handle(ResumeDownloadCmd { id: id.clone() }).await.unwrap();
+        downloader.release(1);
+        wait_for(&repo, &id, DownloadState::Completed).await;
+        assert_eq!(downloader.started().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn paused_downloads_make_room_for_queued_ones() {
+        let (manager, repo, _) = manager(1).await;
+        let first = enqueue(&manager, "a").await;
+        let second = enqueue(&manager, "b").await;
+        manager.handle(PauseDownloadCmd { id: first.clone() }).await.unwrap();
+        wait_for(&repo, &second, DownloadState::Running).await;
+        assert_eq!(state(&repo, &first).await, DownloadState::Paused);
+    }
+
+    #[tokio::test]
+    async fn cancel_stops_queued_and_running_downloads() {
+        let (manager, repo, downloader) = manager(1).await;
+        let running = enqueue(&manager, "a").await;
+        let queued = enqueue(&manager, "b").await;
+
+        manager.handle(CancelManagedDownloadCmd { id: queued.clone() }).await.unwrap();
+        manager.handle(CancelManagedDownloadCmd { id: running.clone() }).await.unwrap();
+        downloader.release(2);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(state(&repo, &running).await, DownloadState::Cancelled);
+        assert_eq!(state(&repo, &queued).await, DownloadState::Cancelled);
+        assert_eq!(downloader.started().len(), 1);
+        let err = manager.handle(ResumeDownloadCmd { id: running }).await.unwrap_err();
+        assert!(matches!(err, AppError::Domain(_)));
+    }
+
+    #[tokio::test]
+    async fn start_requeues_downloads_interrupted_by_a_restart() {
+        let (manager, repo, downloader) = manager(1).await;
+        // Left running by the previous session
+        let interrupted = Download {
+            id: "interrupted".to_string(),
+            url: "https://example.com/a".to_string(),
+            path: "/downloads/a".to_string(),
+            state: DownloadState::Running,
+            bytes_done: 5,
+            total_bytes: Some(10),
+            etag: None,
+            last_modified: None,
+            sha256: None,
+            overwrite: false,
+            error: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+        repo.insert(&interrupted).await.unwrap();
+
+        manager.start().await.unwrap();
+        assert_eq!(state(&repo, "interrupted").await, DownloadState::Running);
+        downloader.release(1);
+        wait_for(&repo, "interrupted", DownloadState::Completed).await;
+    }
+}
@@ -0,0 +1,25 @@
+//! Download query handlers - handles all read operations for managed downloads.
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use crate::domain::cqrs::QueryHandler;
+use crate::domain::downloads::{Download, IDownloadRepository, ListDownloadsQuery};
+use crate::error::AppError;
+
+/// Handles download-related queries (read operations).
+pub struct DownloadQueryHandler {
+    repo: Arc<dyn IDownloadRepository>,
+}
+
+impl DownloadQueryHandler {
+    pub fn new(repo: Arc<dyn IDownloadRepository>) -> Self {
+        Self { repo }
+    }
+}
+
+#[async_trait]
+impl QueryHandler<ListDownloadsQuery, Vec<Download>> for DownloadQueryHandler {
+    async fn handle(&self, _query: ListDownloadsQuery) -> Result<Vec<Download>, AppError> {
+        self.repo.list().await
+    }
+}
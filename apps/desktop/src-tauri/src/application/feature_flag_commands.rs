@@ -0,0 +1,56 @@
+//! Feature flag command handlers - handles all write operations for feature flags.
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use crate::application::feature_flag_evaluator::FeatureFlagEvaluator;
+use crate::domain::cqrs::CommandHandler;
+use crate::domain::feature_flags::{
+    DeleteFeatureFlagCmd, FeatureFlag, IFeatureFlagRepository, SetFlagOverrideCmd, UpsertFeatureFlagCmd,
+};
+use crate::error::AppError;
+
+/// Handles feature-flag-related commands (write operations).
+/// Every write re-evaluates observed flags so changes are published.
+pub struct FeatureFlagCommandHandler {
+    repo: Arc<dyn IFeatureFlagRepository>,
+    evaluator: Arc<FeatureFlagEvaluator>,
+}
+
+impl FeatureFlagCommandHandler {
+    pub fn new(repo: Arc<dyn IFeatureFlagRepository>, evaluator: Arc<FeatureFlagEvaluator>) -> Self {
+        Self { repo, evaluator }
+    }
+}
+
+#[async_trait]
+impl CommandHandler<UpsertFeatureFlagCmd, FeatureFlag> for FeatureFlagCommandHandler {
+    async fn handle(&self, cmd: UpsertFeatureFlagCmd) -> Result<FeatureFlag, AppError> {
+        cmd.validate()?;
+        let flag = self.repo.upsert(&cmd).await?;
+        self.evaluator.refresh().await?;
+        Ok(flag)
+    }
+}
+
+#[async_trait]
+impl CommandHandler<DeleteFeatureFlagCmd, ()> for FeatureFlagCommandHandler {
+    async fn handle(&self, cmd: DeleteFeatureFlagCmd) -> Result<(), AppError> {
+        self.repo.delete(&cmd.key).await?;
+        self.evaluator.refresh().await
+    }
+}
+
+#[async_trait]
+impl CommandHandler<SetFlagOverrideCmd, ()> for FeatureFlagCommandHandler {
+    async fn handle(&self, cmd: SetFlagOverrideCmd) -> Result<(), AppError> {
+        if self.repo.find(&cmd.key).await?.is_none() {
+            return Err(AppError::NotFound(format!("Feature flag {} not found", cmd.key)));
+        }
+
+        match cmd.enabled {
+            Some(enabled) => self.repo.set_override(&cmd.key, &cmd.user_id, enabled).await?,
+            None => self.repo.clear_override(&cmd.key, &cmd.user_id).await?,
+        }
+        self.evaluator.refresh().await
+    }
+}
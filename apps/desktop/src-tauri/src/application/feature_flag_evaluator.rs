@@ -0,0 +1,221 @@
+//! Feature flag evaluator - computes effective flag values and publishes
+//! `FeatureFlagChanged` when a value observed by the app changes.
+//!
+//! The most recently evaluated (flag, user) pairs are remembered. Writes and a
+//! periodic tick (for expiry dates) call `refresh`, which re-evaluates those pairs.
+//! Both values come from the frontend, so the number of pairs is capped and
+//! pairs of deleted flags are dropped.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use crate::domain::events::{DomainEvent, IEventPublisher};
+use crate::domain::feature_flags::{FeatureFlag, FlagSource, IFeatureFlagRepository};
+use crate::error::AppError;
+
+/// Pairs beyond this evict the least recently evaluated one
+const MAX_OBSERVED: usize = 1000;
+
+type FlagContext = (String, Option<String>);
+
+#[derive(Default)]
+struct Observed {
+    /// Last value and when the app last asked for it (see `clock`)
+    values: HashMap<FlagContext, (bool, u64)>,
+    /// Bumped by every evaluation the app asks for; `refresh` does not count as use
+    clock: u64,
+}
+
+pub struct FeatureFlagEvaluator {
+    repo: Arc<dyn IFeatureFlagRepository>,
+    publisher: Arc<dyn IEventPublisher>,
+    observed: Mutex<Observed>,
+}
+
+impl FeatureFlagEvaluator {
+    pub fn new(repo: Arc<dyn IFeatureFlagRepository>, publisher: Arc<dyn IEventPublisher>) -> Self {
+        Self {
+            repo,
+            publisher,
+            observed: Mutex::new(Observed::default()),
+        }
+    }
+
+    /// Effective value of a single flag. Unknown flags are disabled.
+    pub async fn evaluate(&self, key: &str, user_id: Option<&str>) -> Result<bool, AppError> {
+        self.evaluate_observed(key, user_id, true).await
+    }
+
+    async fn evaluate_observed(&self, key: &str, user_id: Option<&str>, used: bool) -> Result<bool, AppError> {
+        let Some(flag) = self.repo.find(key).await? else {
+            self.forget(key, user_id);
+            return Ok(false);
+        };
+
+        let user_override = match user_id {
+            Some(user_id) => self.repo.get_override(key, user_id).await?,
+            None => None,
+        };
+
+        let (enabled, _) = flag.evaluate(user_id, user_override);
+        self.record(key, user_id, enabled, used);
+        Ok(enabled)
+    }
+
+    /// Effective values of every flag.
+    pub async fn evaluate_all(&self, user_id: Option<&str>) -> Result<Vec<(FeatureFlag, bool, FlagSource)>, AppError> {
+        let overrides = match user_id {
+            Some(user_id) => self.repo.list_overrides(user_id).await?,
+            None => HashMap::new(),
+        };
+
+        let flags = self.repo.list().await?;
+        let evaluated = flags
+            .into_iter()
+            .map(|flag| {
+                let (enabled, source) = flag.evaluate(user_id, overrides.get(&flag.key).copied());
+                self.record(&flag.key, user_id, enabled, true);
+                (flag, enabled, source)
+            })
+            .collect();
+        Ok(evaluated)
+    }
+
+    /// Re-evaluate every observed (flag, user) pair, publishing changes.
+    pub async fn refresh(&self) -> Result<(), AppError> {
+        let contexts: Vec<FlagContext> = self.lock().values.keys().cloned().collect();
+        for (key, user_id) in contexts {
+            self.evaluate_observed(&key, user_id.as_deref(), false).await?;
+        }
+        Ok(())
+    }
+
+    /// Number of (flag, user) pairs `refresh` re-evaluates
+    #[cfg(test)]
+    fn observed_count(&self) -> usize {
+        self.lock().values.len()
+    }
+
+    fn record(&self, key: &str, user_id: Option<&str>, enabled: bool, used: bool) {
+        let context = (key.to_string(), user_id.map(str::to_string));
+        let previous = {
+            let mut observed = self.lock();
+            if used {
+                observed.clock += 1;
+            }
+            let clock = observed.clock;
+            let previous = observed.values.get(&context).copied();
+            // A refresh keeps the pair's age, so pairs the app stopped asking for age out
+            let last_used = match previous {
+                Some((_, last_used)) if !used => last_used,
+                _ => clock,
+            };
+            observed.values.insert(context, (enabled, last_used));
+
+            if previous.is_none() && observed.values.len() > MAX_OBSERVED {
+                let oldest = observed
+                    .values
+                    .iter()
+                    .min_by_key(|(_, (_, last_used))| *last_used)
+                    .map(|(context, _)| context.clone());
+                if let Some(oldest) = oldest {
+                    observed.values.remove(&oldest);
+                }
+            }
+            previous.map(|(enabled, _)| enabled)
+        };
+
+        if previous.is_some_and(|previous| previous != enabled) {
+            self.publish(key, user_id, enabled);
+        }
+    }
+
+    /// Stop observing a pair whose flag is gone. It reads as disabled from now on.
+    fn forget(&self, key: &str, user_id: Option<&str>) {
+        let context = (key.to_string(), user_id.map(str::to_string));
+        let previous = self.lock().values.remove(&context);
+        if previous.is_some_and(|(enabled, _)| enabled) {
+            self.publish(key, user_id, false);
+        }
+    }
+
+    fn publish(&self, key: &str, user_id: Option<&str>, enabled: bool) {
+        self.publisher.publish(DomainEvent::FeatureFlagChanged {
+            key: key.to_string(),
+            user_id: user_id.map(str::to_string),
+            enabled,
+        });
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Observed> {
+        self.observed.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::feature_flags::UpsertFeatureFlagCmd;
+    use crate::infra::event_publisher::RecordingEventPublisher;
+    use crate::infra::memory::InMemoryFeatureFlagRepository;
+
+    async fn evaluator() -> (FeatureFlagEvaluator, Arc<InMemoryFeatureFlagRepository>, Arc<RecordingEventPublisher>) {
+        let repo = Arc::new(InMemoryFeatureFlagRepository::new());
+        repo.upsert(&UpsertFeatureFlagCmd {
+            key: "new_ui".to_string(),
+            description: String::new(),
+            default_enabled: true,
+            rollout_percentage: None,
+            expires_at: None,
+        })
+        .await
+        .unwrap();
+        let publisher = Arc::new(RecordingEventPublisher::new());
+        (FeatureFlagEvaluator::new(repo.clone(), publisher.clone()), repo, publisher)
+    }
+
+    fn observes(evaluator: &FeatureFlagEvaluator, user_id: &str) -> bool {
+        evaluator.lock().values.contains_key(&("new_ui".to_string(), Some(user_id.to_string())))
+    }
+
+    #[tokio::test]
+    async fn observed_pairs_are_capped_least_recently_used_first() {
+        let (evaluator, _, _) = evaluator().await;
+        for i in 0..MAX_OBSERVED {
+            evaluator.evaluate("new_ui", Some(&format!("user-{}", i))).await.unwrap();
+        }
+        // Asked again, so user-0 is now the most recent
+        evaluator.evaluate("new_ui", Some("user-0")).await.unwrap();
+        // A refresh is not a use: user-1 stays the oldest
+        evaluator.refresh().await.unwrap();
+
+        evaluator.evaluate("new_ui", Some("one-more")).await.unwrap();
+
+        assert_eq!(evaluator.observed_count(), MAX_OBSERVED);
+        assert!(observes(&evaluator, "user-0"));
+        assert!(!observes(&evaluator, "user-1"));
+        assert!(observes(&evaluator, "one-more"));
+    }
+
+    #[tokio::test]
+    async fn deleted_flag_is_forgotten_and_reported_disabled() {
+        let (evaluator, repo, publisher) = evaluator().await;
+        assert!(evaluator.evaluate("new_ui", Some("alice")).await.unwrap());
+
+        repo.delete("new_ui").await.unwrap();
+        evaluator.refresh().await.unwrap();
+
+        assert_eq!(evaluator.observed_count(), 0);
+        assert!(matches!(
+            publisher.events().as_slice(),
+            [DomainEvent::FeatureFlagChanged { enabled: false, .. }]
+        ));
+    }
+
+    #[tokio::test]
+    async fn unknown_flags_are_not_observed() {
+        let (evaluator, _, publisher) = evaluator().await;
+        assert!(!evaluator.evaluate("missing", None).await.unwrap());
+        assert_eq!(evaluator.observed_count(), 0);
+        assert!(publisher.events().is_empty());
+    }
+}
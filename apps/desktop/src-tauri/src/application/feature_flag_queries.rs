@@ -0,0 +1,37 @@
+//! Feature flag query handlers - handles all read operations for feature flags.
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use crate::application::feature_flag_evaluator::FeatureFlagEvaluator;
+use crate::domain::cqrs::QueryHandler;
+use crate::domain::feature_flags::{FeatureFlagState, IsFlagEnabledQuery, ListFlagsQuery};
+use crate::error::AppError;
+
+/// Handles feature-flag-related queries (read operations).
+pub struct FeatureFlagQueryHandler {
+    evaluator: Arc<FeatureFlagEvaluator>,
+}
+
+impl FeatureFlagQueryHandler {
+    pub fn new(evaluator: Arc<FeatureFlagEvaluator>) -> Self {
+        Self { evaluator }
+    }
+}
+
+#[async_trait]
+impl QueryHandler<IsFlagEnabledQuery, bool> for FeatureFlagQueryHandler {
+    async fn handle(&self, query: IsFlagEnabledQuery) -> Result<bool, AppError> {
+        self.evaluator.evaluate(&query.key, query.user_id.as_deref()).await
+    }
+}
+
+#[async_trait]
+impl QueryHandler<ListFlagsQuery, Vec<FeatureFlagState>> for FeatureFlagQueryHandler {
+    async fn handle(&self, query: ListFlagsQuery) -> Result<Vec<FeatureFlagState>, AppError> {
+        let evaluated = self.evaluator.evaluate_all(query.user_id.as_deref()).await?;
+        Ok(evaluated
+            .into_iter()
+            .map(|(flag, enabled, source)| FeatureFlagState { flag, enabled, source })
+            .collect())
+    }
+}
@@ -0,0 +1,28 @@
+//! HTTP cache command handlers - handles clearing the response cache.
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use tracing::info;
+use crate::domain::cqrs::CommandHandler;
+use crate::domain::http::{ClearHttpCacheCmd, IHttpCacheRepository};
+use crate::error::AppError;
+
+/// Handles HTTP cache commands (write operations).
+pub struct HttpCacheCommandHandler {
+    repo: Arc<dyn IHttpCacheRepository>,
+}
+
+impl HttpCacheCommandHandler {
+    pub fn new(repo: Arc<dyn IHttpCacheRepository>) -> Self {
+        Self { repo }
+    }
+}
+
+#[async_trait]
+impl CommandHandler<ClearHttpCacheCmd, u64> for HttpCacheCommandHandler {
+    async fn handle(&self, _cmd: ClearHttpCacheCmd) -> Result<u64, AppError> {
+        let removed = self.repo.clear().await?;
+        info!("Cleared {} cached HTTP responses", removed);
+        Ok(removed)
+    }
+}
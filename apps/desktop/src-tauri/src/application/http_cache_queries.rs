@@ -0,0 +1,25 @@
+//! HTTP cache query handlers - handles inspecting the response cache.
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use crate::domain::cqrs::QueryHandler;
+use crate::domain::http::{HttpCacheEntry, IHttpCacheRepository, ListHttpCacheQuery};
+use crate::error::AppError;
+
+/// Handles HTTP cache queries (read operations).
+pub struct HttpCacheQueryHandler {
+    repo: Arc<dyn IHttpCacheRepository>,
+}
+
+impl HttpCacheQueryHandler {
+    pub fn new(repo: Arc<dyn IHttpCacheRepository>) -> Self {
+        Self { repo }
+    }
+}
+
+#[async_trait]
+impl QueryHandler<ListHttpCacheQuery, Vec<HttpCacheEntry>> for HttpCacheQueryHandler {
+    async fn handle(&self, _query: ListHttpCacheQuery) -> Result<Vec<HttpCacheEntry>, AppError> {
+        self.repo.list().await
+    }
+}
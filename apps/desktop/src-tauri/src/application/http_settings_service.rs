@@ -0,0 +1,190 @@
+//! HTTP settings service - keeps the shared HTTP client in sync with the
+//! `http_*` settings.
+//!
+//! The proxy and CA bundle decide which hosts requests reach and whom they
+//! trust, so like the HTTP scope they are read only from config files, env vars
+//! and the CLI; values written with `set_config` are ignored.
+
+use std::str::FromStr;
+use std::sync::Arc;
+use async_trait::async_trait;
+use tracing::warn;
+use crate::application::ConfigQueryHandler;
+use crate::domain::config::{GetConfigQuery, StaticConfig};
+use crate::domain::cqrs::QueryHandler;
+use crate::domain::events::{DomainEvent, IEventSubscriber};
+use crate::domain::http::{
+    CircuitBreakerStatus, HttpSettings, IHttpClient, ListCircuitBreakersQuery,
+    HTTP_CACHE_ENABLED_KEY, HTTP_CACHE_MAX_BYTES_KEY, HTTP_CA_BUNDLE_PATH_KEY, HTTP_CIRCUIT_FAILURE_THRESHOLD_KEY, HTTP_CIRCUIT_OPEN_SECS_KEY,
+    HTTP_MAX_RETRIES_KEY, HTTP_NO_PROXY_KEY, HTTP_PROXY_URL_KEY, HTTP_RETRY_BASE_DELAY_MS_KEY,
+    HTTP_RETRY_MAX_DELAY_MS_KEY, HTTP_RETRY_NON_IDEMPOTENT_KEY, HTTP_SETTINGS_PREFIX,
+    HTTP_TIMEOUT_SECS_KEY, HTTP_USER_AGENT_KEY,
+};
+use crate::error::AppError;
+
+/// Applies the settings to the client whenever they change.
+/// Cheap to clone, so event handling can run on a background task.
+#[derive(Clone)]
+pub struct HttpSettingsService {
+    client: Arc<dyn IHttpClient>,
+    config: ConfigQueryHandler,
+    layers: Arc<StaticConfig>,
+}
+
+impl HttpSettingsService {
+    pub fn new(client: Arc<dyn IHttpClient>, config: ConfigQueryHandler, layers: Arc<StaticConfig>) -> Self {
+        Self { client, config, layers }
+    }
+
+    /// Apply the effective settings to the client. Called at startup and on every change.
+    pub async fn sync(&self) -> Result<(), AppError> {
+        let mut settings = HttpSettings::default();
+        let client = &mut settings.client;
+        client.timeout_secs = self.setting(HTTP_TIMEOUT_SECS_KEY, client.timeout_secs).await?;
+        client.user_agent = self.setting(HTTP_USER_AGENT_KEY, client.user_agent.clone()).await?;
+        client.proxy_url = self.static_optional(HTTP_PROXY_URL_KEY);
+        client.no_proxy = self.static_optional(HTTP_NO_PROXY_KEY);
+        client.ca_bundle_path = self.static_optional(HTTP_CA_BUNDLE_PATH_KEY);
+        settings.cache_enabled = self.setting(HTTP_CACHE_ENABLED_KEY, settings.cache_enabled).await?;
+        settings.cache_max_bytes = self.setting(HTTP_CACHE_MAX_BYTES_KEY, settings.cache_max_bytes).await?;
+
+        let retry = &mut settings.retry;
+        retry.max_retries = self.setting(HTTP_MAX_RETRIES_KEY, retry.max_retries).await?;
+        retry.base_delay_ms = self.setting(HTTP_RETRY_BASE_DELAY_MS_KEY, retry.base_delay_ms).await?;
+        retry.max_delay_ms = self.setting(HTTP_RETRY_MAX_DELAY_MS_KEY, retry.max_delay_ms).await?;
+        retry.retry_non_idempotent = self
+            .setting(HTTP_RETRY_NON_IDEMPOTENT_KEY, retry.retry_non_idempotent)
+            .await?;
+
+        let breaker = &mut settings.circuit_breaker;
+        breaker.failure_threshold = self
+            .setting(HTTP_CIRCUIT_FAILURE_THRESHOLD_KEY, breaker.failure_threshold)
+            .await?;
+        breaker.open_secs = self.setting(HTTP_CIRCUIT_OPEN_SECS_KEY, breaker.open_secs).await?;
+
+        self.client.apply_settings(settings)
+    }
+
+    /// The setting parsed as `T`, or `default` if it is missing or invalid.
+    async fn setting<T: FromStr>(&self, key: &str, default: T) -> Result<T, AppError> {
+        let Some(value) = self.optional(key).await? else { return Ok(default) };
+        Ok(value.parse().unwrap_or_else(|_| {
+            warn!("Ignoring invalid value {:?} of setting {}", value, key);
+            default
+        }))
+    }
+
+    /// The setting, or `None` if it is missing or blank.
+    async fn optional(&self, key: &str) -> Result<Option<String>, AppError> {
+        let value = self.config.handle(GetConfigQuery { key: key.to_string() }).await?;
+        Ok(value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty()))
+    }
+
+    /// Like `optional`, but ignoring the database.
+    fn static_optional(&self, key: &str) -> Option<String> {
+        let (value, _) = self.layers.get(key)?;
+        Some(value.trim().to_string()).filter(|v| !v.is_empty())
+    }
+}
+
+#[async_trait]
+impl QueryHandler<ListCircuitBreakersQuery, Vec<CircuitBreakerStatus>> for HttpSettingsService {
+    async fn handle(&self, _query: ListCircuitBreakersQuery) -> Result<Vec<CircuitBreakerStatus>, AppError> {
+        Ok(self.client.circuit_breakers())
+    }
+}
+
+impl IEventSubscriber for HttpSettingsService {
+    fn on_event(&self, event: &DomainEvent) {
+        let affected = match event {
+            DomainEvent::ConfigChanged { key, .. } | DomainEvent::ConfigDeleted { key } => {
+                key.starts_with(HTTP_SETTINGS_PREFIX)
+            }
+            DomainEvent::ConfigBatchChanged { values } => {
+                values.keys().any(|key| key.starts_with(HTTP_SETTINGS_PREFIX))
+            }
+            DomainEvent::ConfigReset
+            | DomainEvent::DatabaseRestored { .. }
+            | DomainEvent::ArchiveImported { .. }
+            | DomainEvent::MigrationsRolledBack { .. } => true,
+            _ => false,
+        };
+        if !affected {
+            return;
+        }
+
+        // Resolving the effective values is async (defaults may apply after a delete)
+        let service = self.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = service.sync().await {
+                warn!("Failed to apply HTTP settings: {:?}", e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use super::*;
+    use crate::domain::config::{ConfigLayer, IConfigRepository};
+    use crate::infra::memory::InMemoryConfigRepository;
+
+    /// Keeps the last settings applied
+    #[derive(Default)]
+    struct SettingsRecorder(Mutex<Option<HttpSettings>>);
+
+    impl IHttpClient for SettingsRecorder {
+        fn apply_settings(&self, settings: HttpSettings) -> Result<(), AppError> {
+            *self.0.lock().unwrap() = Some(settings);
+            Ok(())
+        }
+
+        fn circuit_breakers(&self) -> Vec<CircuitBreakerStatus> {
+            Vec::new()
+        }
+    }
+
+    async fn synced(layers: StaticConfig) -> HttpSettings {
+        let repo = Arc::new(InMemoryConfigRepository::new());
+        for key in [HTTP_PROXY_URL_KEY, HTTP_NO_PROXY_KEY, HTTP_CA_BUNDLE_PATH_KEY] {
+            repo.set(key, "from-database").await.unwrap();
+        }
+        repo.set(HTTP_USER_AGENT_KEY, "agent/1.0").await.unwrap();
+
+        let layers = Arc::new(layers);
+        let client = Arc::new(SettingsRecorder::default());
+        let service = HttpSettingsService::new(client.clone(), ConfigQueryHandler::new(repo, layers.clone()), layers);
+        service.sync().await.unwrap();
+        let settings = client.0.lock().unwrap().take();
+        settings.unwrap()
+    }
+
+    #[tokio::test]
+    async fn proxy_and_ca_ignore_the_database() {
+        let settings = synced(StaticConfig::default()).await;
+        assert_eq!(settings.client.proxy_url, None);
+        assert_eq!(settings.client.no_proxy, None);
+        assert_eq!(settings.client.ca_bundle_path, None);
+        // Other settings still come from the database
+        assert_eq!(settings.client.user_agent, "agent/1.0");
+    }
+
+    #[tokio::test]
+    async fn proxy_and_ca_come_from_static_config() {
+        let mut layers = StaticConfig::default();
+        layers.apply(
+            ConfigLayer::Env,
+            HashMap::from([
+                (HTTP_PROXY_URL_KEY.to_string(), "http://proxy.corp:8080".to_string()),
+                (HTTP_NO_PROXY_KEY.to_string(), " ".to_string()),
+                (HTTP_CA_BUNDLE_PATH_KEY.to_string(), "/etc/ssl/corp.pem".to_string()),
+            ]),
+        );
+        let settings = synced(layers).await;
+        assert_eq!(settings.client.proxy_url.as_deref(), Some("http://proxy.corp:8080"));
+        assert_eq!(settings.client.no_proxy, None);
+        assert_eq!(settings.client.ca_bundle_path.as_deref(), Some("/etc/ssl/corp.pem"));
+    }
+}
@@ -0,0 +1,181 @@
+//! Maintenance command handlers - routine optimization and manual compaction of the database.
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use tracing::info;
+use crate::application::ConfigQueryHandler;
+use crate::domain::config::{GetConfigQuery, IConfigRepository};
+use crate::domain::cqrs::{CommandHandler, QueryHandler};
+use crate::domain::maintenance::{
+    CompactDatabaseCmd, IDatabaseMaintenance, MaintenanceReport, RunMaintenanceCmd,
+    DEFAULT_MAINTENANCE_INTERVAL_HOURS, MAINTENANCE_INTERVAL_HOURS_KEY, MAINTENANCE_LAST_RUN_KEY,
+};
+use crate::error::AppError;
+
+/// Handles maintenance commands and records the last run in `system_settings`.
+pub struct MaintenanceCommandHandler {
+    maintenance: Arc<dyn IDatabaseMaintenance>,
+    /// Holds the last run, an internal setting (see `INTERNAL_SETTINGS`)
+    settings: Arc<dyn IConfigRepository>,
+    config_queries: ConfigQueryHandler,
+}
+
+impl MaintenanceCommandHandler {
+    pub fn new(
+        maintenance: Arc<dyn IDatabaseMaintenance>,
+        settings: Arc<dyn IConfigRepository>,
+        config_queries: ConfigQueryHandler,
+    ) -> Self {
+        Self { maintenance, settings, config_queries }
+    }
+
+    /// Run routine maintenance if the interval has elapsed and the database is idle.
+    pub async fn run_if_due(&self) -> Result<Option<MaintenanceReport>, AppError> {
+        let interval_hours = self
+            .config_queries
+            .handle(GetConfigQuery { key: MAINTENANCE_INTERVAL_HOURS_KEY.to_string() })
+            .await?
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(DEFAULT_MAINTENANCE_INTERVAL_HOURS);
+        if interval_hours == 0 || !self.maintenance.is_idle() {
+            return Ok(None);
+        }
+
+        let last_run = self
+            .settings
+            .get(MAINTENANCE_LAST_RUN_KEY)
+            .await?
+            .and_then(|v| OffsetDateTime::parse(&v, &Rfc3339).ok());
+        let due = last_run.is_none_or(|last_run| {
+            OffsetDateTime::now_utc() - last_run >= time::Duration::hours(interval_hours as i64)
+        });
+        if !due {
+            return Ok(None);
+        }
+
+        info!("Scheduled database maintenance is due");
+        self.handle(RunMaintenanceCmd).await.map(Some)
+    }
+
+    async fn record(&self, report: &MaintenanceReport) -> Result<(), AppError> {
+        self.settings.set(MAINTENANCE_LAST_RUN_KEY, &report.started_at).await
+    }
+}
+
+#[async_trait]
+impl CommandHandler<RunMaintenanceCmd, MaintenanceReport> for MaintenanceCommandHandler {
+    async fn handle(&self, _cmd: RunMaintenanceCmd) -> Result<MaintenanceReport, AppError> {
+        let report = self.maintenance.optimize().await?;
+        self.record(&report).await?;
+        Ok(report)
+    }
+}
+
+#[async_trait]
+impl CommandHandler<CompactDatabaseCmd, MaintenanceReport> for MaintenanceCommandHandler {
+    async fn handle(&self, _cmd: CompactDatabaseCmd) -> Result<MaintenanceReport, AppError> {
+        let report = self.maintenance.compact().await?;
+        self.record(&report).await?;
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use crate::domain::config::{ConfigLayer, GetAllConfigQuery, StaticConfig};
+    use crate::infra::memory::InMemoryConfigRepository;
+
+    #[derive(Default)]
+    struct FakeMaintenance {
+        runs: AtomicUsize,
+        busy: AtomicBool,
+    }
+
+    #[async_trait]
+    impl IDatabaseMaintenance for FakeMaintenance {
+        async fn optimize(&self) -> Result<MaintenanceReport, AppError> {
+            self.runs.fetch_add(1, Ordering::SeqCst);
+            Ok(MaintenanceReport {
+                started_at: OffsetDateTime::now_utc().format(&Rfc3339).unwrap(),
+                duration_ms: 0,
+                full_vacuum: false,
+                bytes_before: 0,
+                bytes_after: 0,
+                bytes_reclaimed: 0,
+            })
+        }
+
+        async fn compact(&self) -> Result<MaintenanceReport, AppError> {
+            self.optimize().await
+        }
+
+        fn is_idle(&self) -> bool {
+            !self.busy.load(Ordering::SeqCst)
+        }
+    }
+
+    fn handler(layers: StaticConfig) -> (MaintenanceCommandHandler, Arc<FakeMaintenance>, Arc<InMemoryConfigRepository>) {
+        let maintenance = Arc::new(FakeMaintenance::default());
+        let settings = Arc::new(InMemoryConfigRepository::new());
+        let queries = ConfigQueryHandler::new(settings.clone(), Arc::new(layers));
+        (MaintenanceCommandHandler::new(maintenance.clone(), settings.clone(), queries), maintenance, settings)
+    }
+
+    fn hours_ago(hours: i64) -> String {
+        (OffsetDateTime::now_utc() - time::Duration::hours(hours)).format(&Rfc3339).unwrap()
+    }
+
+    #[tokio::test]
+    async fn runs_when_never_run_and_records_the_time() {
+        let (handler, maintenance, settings) = handler(StaticConfig::default());
+
+        assert!(handler.run_if_due().await.unwrap().is_some());
+        assert!(settings.get(MAINTENANCE_LAST_RUN_KEY).await.unwrap().is_some());
+        // Bookkeeping stays out of the settings the UI sees
+        let all = handler.config_queries.handle(GetAllConfigQuery).await.unwrap();
+        assert!(!all.contains_key(MAINTENANCE_LAST_RUN_KEY));
+        // Just ran: not due again
+        assert!(handler.run_if_due().await.unwrap().is_none());
+        assert_eq!(maintenance.runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn runs_once_the_interval_has_elapsed() {
+        let (handler, maintenance, settings) = handler(StaticConfig::default());
+        settings.set(MAINTENANCE_INTERVAL_HOURS_KEY, "6").await.unwrap();
+
+        settings.set(MAINTENANCE_LAST_RUN_KEY, &hours_ago(5)).await.unwrap();
+        assert!(handler.run_if_due().await.unwrap().is_none());
+        settings.set(MAINTENANCE_LAST_RUN_KEY, &hours_ago(7)).await.unwrap();
+        assert!(handler.run_if_due().await.unwrap().is_some());
+        assert_eq!(maintenance.runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn skips_when_disabled_or_busy() {
+        let (handler, maintenance, settings) = handler(StaticConfig::default());
+        settings.set(MAINTENANCE_INTERVAL_HOURS_KEY, "0").await.unwrap();
+        assert!(handler.run_if_due().await.unwrap().is_none());
+
+        settings.set(MAINTENANCE_INTERVAL_HOURS_KEY, "24").await.unwrap();
+        maintenance.busy.store(true, Ordering::SeqCst);
+        assert!(handler.run_if_due().await.unwrap().is_none());
+        assert_eq!(maintenance.runs.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn last_run_is_recorded_even_if_locked() {
+        let mut layers = StaticConfig::default();
+        layers.apply(ConfigLayer::SystemFile, [(MAINTENANCE_LAST_RUN_KEY.to_string(), hours_ago(48))].into());
+        layers.lock(MAINTENANCE_LAST_RUN_KEY);
+        let (handler, _, settings) = handler(layers);
+
+        assert!(handler.run_if_due().await.unwrap().is_some());
+        assert!(settings.get(MAINTENANCE_LAST_RUN_KEY).await.unwrap().is_some());
+        assert!(handler.run_if_due().await.unwrap().is_none());
+    }
+}
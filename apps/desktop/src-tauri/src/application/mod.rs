@@ -1,11 +1,54 @@
 // CQRS Handlers
+pub mod archive_commands;
+pub mod autostart_service;
+pub mod backup_commands;
+pub mod backup_queries;
+pub mod config_cache;
 pub mod config_commands;
 pub mod config_queries;
+pub mod cookie_commands;
+pub mod cookie_queries;
+pub mod diagnostics_commands;
+pub mod diagnostics_queries;
+pub mod download_commands;
+pub mod download_manager;
+pub mod download_queries;
+pub mod feature_flag_commands;
+pub mod feature_flag_evaluator;
+pub mod feature_flag_queries;
+pub mod http_cache_commands;
+pub mod http_cache_queries;
+pub mod http_settings_service;
+pub mod maintenance_commands;
+pub mod recovery_service;
+pub mod secret_commands;
+pub mod secret_queries;
 pub mod user_commands;
 pub mod user_queries;
 
 // Re-exports for convenience
+pub use archive_commands::ArchiveCommandHandler;
+pub use autostart_service::AutostartService;
+pub use backup_commands::BackupCommandHandler;
+pub use backup_queries::BackupQueryHandler;
 pub use config_commands::ConfigCommandHandler;
 pub use config_queries::ConfigQueryHandler;
+pub use cookie_commands::CookieCommandHandler;
+pub use cookie_queries::CookieQueryHandler;
+pub use diagnostics_commands::QueryStatsCommandHandler;
+pub use diagnostics_queries::{DiagnosticsQueryHandler, QueryStatsQueryHandler};
+pub use download_commands::DownloadCommandHandler;
+pub use download_manager::DownloadManager;
+pub use download_queries::DownloadQueryHandler;
+pub use feature_flag_commands::FeatureFlagCommandHandler;
+pub use feature_flag_evaluator::FeatureFlagEvaluator;
+pub use feature_flag_queries::FeatureFlagQueryHandler;
+pub use http_cache_commands::HttpCacheCommandHandler;
+pub use http_cache_queries::HttpCacheQueryHandler;
+pub use http_settings_service::HttpSettingsService;
+pub use maintenance_commands::MaintenanceCommandHandler;
+pub use recovery_service::RecoveryService;
+pub use secret_commands::SecretCommandHandler;
+pub use secret_queries::SecretQueryHandler;
 pub use user_commands::UserCommandHandler;
 pub use user_queries::UserQueryHandler;
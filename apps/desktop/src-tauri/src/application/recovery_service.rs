@@ -0,0 +1,142 @@
+//! Recovery service - the commands available while the database cannot be opened.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use tracing::warn;
+use crate::domain::cqrs::CommandHandler;
+use crate::domain::recovery::{
+    ExportSalvageCmd, IDatabaseRecovery, RecoverFromBackupCmd, RecoveryStatus,
+    RetryDatabaseCmd, SalvageReport, StartFreshDatabaseCmd,
+};
+use crate::error::AppError;
+
+/// Tracks the last open error and guards against acting on a database that is already open.
+pub struct RecoveryService {
+    recovery: Arc<dyn IDatabaseRecovery>,
+    last_error: Mutex<String>,
+    recovered: AtomicBool,
+}
+
+impl RecoveryService {
+    pub fn new(recovery: Arc<dyn IDatabaseRecovery>, error: String) -> Self {
+        Self {
+            recovery,
+            last_error: Mutex::new(error),
+            recovered: AtomicBool::new(false),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.recovered.load(Ordering::SeqCst)
+    }
+
+    pub fn status(&self) -> Result<RecoveryStatus, AppError> {
+        Ok(RecoveryStatus {
+            active: self.is_active(),
+            error: self.last_error.lock().unwrap_or_else(|e| e.into_inner()).clone(),
+            db_path: self.recovery.db_path(),
+            backups: self.recovery.list_backups()?,
+        })
+    }
+
+    fn ensure_active(&self) -> Result<(), AppError> {
+        if self.is_active() {
+            Ok(())
+        } else {
+            Err(AppError::Domain("Database is already open".to_string()))
+        }
+    }
+
+    async fn reopen(&self) -> Result<(), AppError> {
+        match self.recovery.reopen().await {
+            Ok(()) => {
+                self.recovered.store(true, Ordering::SeqCst);
+                Ok(())
+            }
+            Err(e) => {
+                warn!("Database still cannot be opened: {:?}", e);
+                *self.last_error.lock().unwrap_or_else(|e| e.into_inner()) = e.to_string();
+                Err(e)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl CommandHandler<RetryDatabaseCmd, ()> for RecoveryService {
+    async fn handle(&self, _cmd: RetryDatabaseCmd) -> Result<(), AppError> {
+        self.ensure_active()?;
+        self.reopen().await
+    }
+}
+
+#[async_trait]
+impl CommandHandler<RecoverFromBackupCmd, ()> for RecoveryService {
+    async fn handle(&self, cmd: RecoverFromBackupCmd) -> Result<(), AppError> {
+        self.ensure_active()?;
+        self.recovery.restore_backup(&cmd.name).await?;
+        self.reopen().await
+    }
+}
+
+#[async_trait]
+impl CommandHandler<StartFreshDatabaseCmd, String> for RecoveryService {
+    async fn handle(&self, _cmd: StartFreshDatabaseCmd) -> Result<String, AppError> {
+        self.ensure_active()?;
+        let moved_to = self.recovery.move_aside().await?;
+        self.reopen().await?;
+        Ok(moved_to)
+    }
+}
+
+#[async_trait]
+impl CommandHandler<ExportSalvageCmd, SalvageReport> for RecoveryService {
+    async fn handle(&self, _cmd: ExportSalvageCmd) -> Result<SalvageReport, AppError> {
+        self.ensure_active()?;
+        self.recovery.export_salvage().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::recovery::ExportSalvageCmd;
+    use crate::infra::db::DbOptions;
+    use crate::infra::db_recovery::SqliteRecovery;
+
+    /// A recovery service for a database file that is not a database
+    fn broken(dir: &std::path::Path) -> (RecoveryService, tokio::sync::oneshot::Receiver<crate::infra::db::Database>) {
+        let db_path = dir.join("app.db");
+        std::fs::write(&db_path, vec![0xab; 4096]).unwrap();
+        let (recovery, opened) = SqliteRecovery::new(db_path, dir.join("backups"), DbOptions::default());
+        (RecoveryService::new(Arc::new(recovery), "startup error".to_string()), opened)
+    }
+
+    #[tokio::test]
+    async fn failed_retry_records_the_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let (service, _opened) = broken(dir.path());
+
+        assert!(service.handle(RetryDatabaseCmd).await.is_err());
+        let status = service.status().unwrap();
+        assert!(status.active);
+        assert_ne!(status.error, "startup error");
+    }
+
+    #[tokio::test]
+    async fn start_fresh_moves_the_broken_file_aside_and_opens() {
+        let dir = tempfile::tempdir().unwrap();
+        let (service, opened) = broken(dir.path());
+
+        let moved_to = service.handle(StartFreshDatabaseCmd).await.unwrap();
+        assert_eq!(std::fs::read(dir.path().join(&moved_to)).unwrap(), vec![0xab; 4096]);
+        assert!(!service.is_active());
+        let db = opened.await.unwrap();
+        sqlx::query("SELECT 1").execute(&*db.pool().await).await.unwrap();
+
+        // Recovery commands refuse to touch an open database
+        let err = service.handle(ExportSalvageCmd).await.unwrap_err();
+        assert!(matches!(err, AppError::Domain(_)));
+    }
+}
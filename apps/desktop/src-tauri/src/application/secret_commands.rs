@@ -0,0 +1,35 @@
+//! Secret command handlers - handles all write operations for secret settings.
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use crate::domain::cqrs::CommandHandler;
+use crate::domain::secrets::{ClearSecretCmd, ISecretRepository, SetSecretCmd};
+use crate::error::AppError;
+
+/// Handles secret-related commands (write operations).
+pub struct SecretCommandHandler {
+    repo: Arc<dyn ISecretRepository>,
+}
+
+impl SecretCommandHandler {
+    pub fn new(repo: Arc<dyn ISecretRepository>) -> Self {
+        Self { repo }
+    }
+}
+
+#[async_trait]
+impl CommandHandler<SetSecretCmd, ()> for SecretCommandHandler {
+    async fn handle(&self, cmd: SetSecretCmd) -> Result<(), AppError> {
+        if cmd.key.trim().is_empty() {
+            return Err(AppError::Domain("Secret key must not be empty".to_string()));
+        }
+        self.repo.set(&cmd.key, &cmd.value).await
+    }
+}
+
+#[async_trait]
+impl CommandHandler<ClearSecretCmd, ()> for SecretCommandHandler {
+    async fn handle(&self, cmd: ClearSecretCmd) -> Result<(), AppError> {
+        self.repo.delete(&cmd.key).await
+    }
+}
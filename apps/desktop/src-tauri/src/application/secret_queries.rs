@@ -0,0 +1,112 @@
+//! Secret query handlers - handles all read operations for secret settings.
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use crate::domain::cqrs::QueryHandler;
+use crate::domain::config::StaticConfig;
+use crate::domain::secrets::{
+    host_allowed, GetSecretQuery, ISecretRepository, ListSecretsQuery, SecretInfo, SECRET_HOSTS_PREFIX,
+};
+use crate::error::AppError;
+
+/// Handles secret-related queries (read operations).
+///
+/// `GetSecretQuery` returns decrypted values and is meant for backend code only
+/// (e.g. injecting auth headers); no Tauri command exposes it.
+pub struct SecretQueryHandler {
+    repo: Arc<dyn ISecretRepository>,
+    /// Holds the host bindings; the database layer cannot override them
+    layers: Arc<StaticConfig>,
+}
+
+impl SecretQueryHandler {
+    pub fn new(repo: Arc<dyn ISecretRepository>, layers: Arc<StaticConfig>) -> Self {
+        Self { repo, layers }
+    }
+}
+
+#[async_trait]
+impl QueryHandler<ListSecretsQuery, Vec<SecretInfo>> for SecretQueryHandler {
+    async fn handle(&self, _query: ListSecretsQuery) -> Result<Vec<SecretInfo>, AppError> {
+        self.repo.list().await
+    }
+}
+
+#[async_trait]
+impl QueryHandler<GetSecretQuery, Option<String>> for SecretQueryHandler {
+    async fn handle(&self, query: GetSecretQuery) -> Result<Option<String>, AppError> {
+        let key = format!("{}{}", SECRET_HOSTS_PREFIX, query.key);
+        let Some((patterns, _)) = self.layers.get(&key) else {
+            return Err(AppError::ScopeViolation(format!(
+                "Secret {} is not bound to any host (set {})",
+                query.key, key
+            )));
+        };
+        if !query.scheme.eq_ignore_ascii_case("https") {
+            return Err(AppError::ScopeViolation(format!(
+                "Secret {} is only sent over https",
+                query.key
+            )));
+        }
+        if !host_allowed(patterns, &query.host) {
+            return Err(AppError::ScopeViolation(format!(
+                "Secret {} may not be sent to {}",
+                query.key, query.host
+            )));
+        }
+        self.repo.get(&query.key).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::domain::config::ConfigLayer;
+    use crate::infra::memory::InMemorySecretRepository;
+
+    async fn handler(bindings: &[(&str, &str)]) -> SecretQueryHandler {
+        let repo = Arc::new(InMemorySecretRepository::new());
+        repo.set("github_token", "s3cret").await.unwrap();
+        let mut layers = StaticConfig::default();
+        layers.apply(
+            ConfigLayer::Env,
+            bindings.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<HashMap<_, _>>(),
+        );
+        SecretQueryHandler::new(repo, Arc::new(layers))
+    }
+
+    fn query(host: &str) -> GetSecretQuery {
+        GetSecretQuery { key: "github_token".to_string(), scheme: "https".to_string(), host: host.to_string() }
+    }
+
+    #[tokio::test]
+    async fn bound_host_gets_the_secret() {
+        let handler = handler(&[("secret_hosts_github_token", "api.github.com")]).await;
+        assert_eq!(handler.handle(query("api.github.com")).await.unwrap().as_deref(), Some("s3cret"));
+    }
+
+    #[tokio::test]
+    async fn other_host_is_refused() {
+        let handler = handler(&[("secret_hosts_github_token", "api.github.com")]).await;
+        let err = handler.handle(query("attacker.example")).await.unwrap_err();
+        assert!(matches!(err, AppError::ScopeViolation(_)));
+    }
+
+    #[tokio::test]
+    async fn plain_http_is_refused_for_a_bound_host() {
+        let handler = handler(&[("secret_hosts_github_token", "api.github.com")]).await;
+        let err = handler
+            .handle(GetSecretQuery { scheme: "http".to_string(), ..query("api.github.com") })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::ScopeViolation(_)));
+    }
+
+    #[tokio::test]
+    async fn unbound_secret_is_refused() {
+        let handler = handler(&[]).await;
+        let err = handler.handle(query("api.github.com")).await.unwrap_err();
+        assert!(matches!(err, AppError::ScopeViolation(_)));
+    }
+}
@@ -0,0 +1,59 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use crate::error::AppError;
+use crate::domain::cqrs::Command;
+
+/// Version of the archive layout written by this build. Older archives are
+/// upgraded on import; newer ones are rejected.
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+/// `manifest.json` at the root of a data archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub format_version: u32,
+    /// Version of the app that wrote the archive
+    pub app_version: String,
+    /// Highest schema migration applied to the exported database
+    pub schema_version: i64,
+    /// RFC 3339 creation time
+    pub created_at: String,
+    pub tables: Vec<ArchivedTable>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedTable {
+    pub name: String,
+    /// Path of the table's JSON file inside the archive
+    pub file: String,
+    pub rows: usize,
+    /// Hex-encoded SHA-256 of the table file
+    pub sha256: String,
+}
+
+// ============ Commands ============
+
+/// Command to write every table into an archive at `path`
+#[derive(Debug, Deserialize)]
+pub struct ExportArchiveCmd {
+    pub path: String,
+}
+
+impl Command for ExportArchiveCmd {}
+
+/// Command to replace all data with the contents of the archive at `path`
+#[derive(Debug, Deserialize)]
+pub struct ImportArchiveCmd {
+    pub path: String,
+}
+
+impl Command for ImportArchiveCmd {}
+
+// ============ Store ============
+
+#[async_trait]
+pub trait IArchiveStore: Send + Sync {
+    /// Dump every table into a new archive file.
+    async fn export(&self, path: &str) -> Result<ArchiveManifest, AppError>;
+    /// Validate the archive and replace the data of every archived table in one transaction.
+    async fn import(&self, path: &str) -> Result<ArchiveManifest, AppError>;
+}
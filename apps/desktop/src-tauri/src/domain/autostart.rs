@@ -0,0 +1,35 @@
+use serde::Serialize;
+use crate::error::AppError;
+use crate::domain::cqrs::Query;
+
+/// Setting key backing the "Launch on startup" toggle ("true" / "false").
+pub const LAUNCH_ON_STARTUP_KEY: &str = "launch_on_startup";
+
+/// Autostart state as seen by the OS versus the stored setting
+#[derive(Debug, Clone, Serialize)]
+pub struct AutostartStatus {
+    /// Whether the OS will actually launch the app at login
+    pub enabled: bool,
+    /// Whether the `launch_on_startup` setting asks for it
+    pub configured: bool,
+    /// Platform-specific location of the autostart entry, if any
+    pub location: Option<String>,
+}
+
+// ============ Queries ============
+
+/// Query the actual OS autostart state
+#[derive(Debug)]
+pub struct GetAutostartStatusQuery;
+
+impl Query for GetAutostartStatusQuery {}
+
+// ============ OS Integration ============
+
+/// Installs or removes the platform's login item for the app.
+pub trait IAutostartManager: Send + Sync {
+    fn is_enabled(&self) -> Result<bool, AppError>;
+    fn enable(&self) -> Result<(), AppError>;
+    fn disable(&self) -> Result<(), AppError>;
+    fn location(&self) -> Option<String>;
+}
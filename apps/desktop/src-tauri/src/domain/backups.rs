@@ -0,0 +1,61 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use crate::error::AppError;
+use crate::domain::cqrs::{Command, Query};
+
+/// Setting: hours between scheduled backups ("0" disables scheduled backups).
+pub const BACKUP_INTERVAL_HOURS_KEY: &str = "backup_interval_hours";
+/// Setting: number of backups to keep; older ones are deleted.
+pub const BACKUP_RETENTION_KEY: &str = "backup_retention";
+
+pub const DEFAULT_BACKUP_INTERVAL_HOURS: u64 = 24;
+pub const DEFAULT_BACKUP_RETENTION: usize = 7;
+
+/// A database backup file
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupInfo {
+    /// File name, used to reference the backup in commands
+    pub name: String,
+    pub size_bytes: u64,
+    /// RFC 3339 creation time
+    pub created_at: String,
+    /// Taken automatically before a restore; rotation never deletes it
+    pub pre_restore: bool,
+}
+
+// ============ Commands ============
+
+/// Command to create a backup now
+#[derive(Debug)]
+pub struct CreateBackupCmd;
+
+impl Command for CreateBackupCmd {}
+
+/// Command to replace the live database with a backup
+#[derive(Debug, Deserialize)]
+pub struct RestoreBackupCmd {
+    pub name: String,
+}
+
+impl Command for RestoreBackupCmd {}
+
+// ============ Queries ============
+
+/// Query to list backups, newest first
+#[derive(Debug)]
+pub struct ListBackupsQuery;
+
+impl Query for ListBackupsQuery {}
+
+// ============ Store ============
+
+#[async_trait]
+pub trait IBackupStore: Send + Sync {
+    /// Snapshot the live database into a new backup file.
+    async fn create(&self) -> Result<BackupInfo, AppError>;
+    /// Backups, newest first.
+    async fn list(&self) -> Result<Vec<BackupInfo>, AppError>;
+    async fn delete(&self, name: &str) -> Result<(), AppError>;
+    /// Close the live database, swap in the backup and reopen (running migrations).
+    async fn restore(&self, name: &str) -> Result<(), AppError>;
+}
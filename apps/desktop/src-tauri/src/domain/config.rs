@@ -1,7 +1,14 @@
+use std::collections::{HashMap, HashSet};
 use async_trait::async_trait;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use crate::error::AppError;
 use crate::domain::cqrs::{Command, Query};
+use crate::domain::maintenance::MAINTENANCE_LAST_RUN_KEY;
+
+/// Settings the app writes for its own bookkeeping, straight through the
+/// repository: no events, no locks, and hidden from the settings queries.
+/// A reset keeps them, so it does not make scheduled work look overdue.
+pub const INTERNAL_SETTINGS: [&str; 1] = [MAINTENANCE_LAST_RUN_KEY];
 
 pub struct SystemSetting {
     pub key: String,
@@ -19,6 +26,29 @@ pub struct SetConfigCmd {
 
 impl Command for SetConfigCmd {}
 
+/// Command to set several configuration values atomically
+#[derive(Debug, Deserialize)]
+pub struct SetManyConfigCmd {
+    pub values: HashMap<String, String>,
+}
+
+impl Command for SetManyConfigCmd {}
+
+/// Command to delete a stored value, falling back to the lower config layers
+#[derive(Debug, Deserialize)]
+pub struct DeleteConfigCmd {
+    pub key: String,
+}
+
+impl Command for DeleteConfigCmd {}
+
+/// Command to reset every setting to its default (clears all stored values
+/// except `INTERNAL_SETTINGS`)
+#[derive(Debug)]
+pub struct ResetConfigCmd;
+
+impl Command for ResetConfigCmd {}
+
 // ============ Queries ============
 
 /// Query to get a single config value by key
@@ -35,11 +65,146 @@ pub struct GetAllConfigQuery;
 
 impl Query for GetAllConfigQuery {}
 
+/// Query to get the config cache hit/miss statistics
+#[derive(Debug)]
+pub struct GetConfigCacheStatsQuery;
+
+impl Query for GetConfigCacheStatsQuery {}
+
+/// Hit/miss counters of the in-memory config cache
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    /// Whether the cache currently mirrors the whole settings table
+    pub warm: bool,
+}
+
+/// Query to get every effective setting together with the layer it came from
+#[derive(Debug)]
+pub struct GetResolvedConfigQuery;
+
+impl Query for GetResolvedConfigQuery {}
+
+// ============ Layers ============
+
+/// Configuration layers, lowest precedence first.
+/// A value from a later layer overrides the same key from an earlier one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigLayer {
+    /// Defaults bundled into the binary
+    Default,
+    /// System-wide config file managed by an administrator
+    SystemFile,
+    /// Per-user config file in the app config dir
+    UserFile,
+    /// `APP_*` environment variables
+    Env,
+    /// `--config key=value` command line arguments
+    Cli,
+    /// Values stored in `system_settings` (written from the UI)
+    Database,
+}
+
+/// An effective setting value and its origin
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedSetting {
+    pub key: String,
+    pub value: String,
+    pub layer: ConfigLayer,
+    /// Locked keys cannot be changed from the UI
+    pub locked: bool,
+}
+
+/// Settings merged from every layer below the database.
+/// Built once at startup; the database layer is applied on top at query time.
+#[derive(Debug, Default)]
+pub struct StaticConfig {
+    values: HashMap<String, (String, ConfigLayer)>,
+    locked: HashSet<String>,
+}
+
+impl StaticConfig {
+    /// Merge a layer on top of the current values. Locked keys keep their
+    /// value from the system file (or below) whatever the layers above say.
+    pub fn apply(&mut self, layer: ConfigLayer, values: HashMap<String, String>) {
+        for (key, value) in values {
+            if layer > ConfigLayer::SystemFile && self.locked.contains(&key) {
+                continue;
+            }
+            self.values.insert(key, (value, layer));
+        }
+    }
+
+    /// Lock a key so no layer above the system file (the user file, env, CLI,
+    /// and the database and thus the UI) can override it.
+    pub fn lock(&mut self, key: impl Into<String>) {
+        self.locked.insert(key.into());
+    }
+
+    pub fn get(&self, key: &str) -> Option<(&str, ConfigLayer)> {
+        self.values.get(key).map(|(value, layer)| (value.as_str(), *layer))
+    }
+
+    pub fn is_locked(&self, key: &str) -> bool {
+        self.locked.contains(key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str, ConfigLayer)> {
+        self.values
+            .iter()
+            .map(|(key, (value, layer))| (key.as_str(), value.as_str(), *layer))
+    }
+}
+
 // ============ Repository ============
 
 #[async_trait]
 pub trait IConfigRepository: Send + Sync {
     async fn get(&self, key: &str) -> Result<Option<String>, AppError>;
     async fn set(&self, key: &str, value: &str) -> Result<(), AppError>;
-    async fn get_all(&self) -> Result<std::collections::HashMap<String, String>, AppError>;
+    async fn get_all(&self) -> Result<HashMap<String, String>, AppError>;
+    /// Upsert all values in a single transaction.
+    async fn set_many(&self, values: &HashMap<String, String>) -> Result<(), AppError>;
+    async fn delete(&self, key: &str) -> Result<(), AppError>;
+    /// Remove every stored value except the `keep` keys. Returns the number of deleted rows.
+    async fn clear(&self, keep: &[&str]) -> Result<u64, AppError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(values: &[(&str, &str)]) -> HashMap<String, String> {
+        values.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn later_layers_override_earlier_ones() {
+        let mut config = StaticConfig::default();
+        config.apply(ConfigLayer::Default, layer(&[("theme_mode", "light"), ("language", "en")]));
+        config.apply(ConfigLayer::UserFile, layer(&[("theme_mode", "dark")]));
+        config.apply(ConfigLayer::Cli, layer(&[("theme_mode", "system")]));
+
+        assert_eq!(config.get("theme_mode"), Some(("system", ConfigLayer::Cli)));
+        assert_eq!(config.get("language"), Some(("en", ConfigLayer::Default)));
+    }
+
+    #[test]
+    fn locked_keys_keep_the_system_file_value() {
+        let mut config = StaticConfig::default();
+        config.apply(ConfigLayer::Default, layer(&[("theme_mode", "light"), ("language", "en")]));
+        config.lock("theme_mode");
+        config.lock("language");
+        config.apply(ConfigLayer::SystemFile, layer(&[("theme_mode", "dark")]));
+        for above in [ConfigLayer::UserFile, ConfigLayer::Env, ConfigLayer::Cli] {
+            config.apply(above, layer(&[("theme_mode", "system"), ("language", "de")]));
+        }
+
+        assert_eq!(config.get("theme_mode"), Some(("dark", ConfigLayer::SystemFile)));
+        // Locked without a system value: the default stays
+        assert_eq!(config.get("language"), Some(("en", ConfigLayer::Default)));
+    }
 }
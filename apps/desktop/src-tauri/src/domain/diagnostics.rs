@@ -0,0 +1,168 @@
+//! Database diagnostics: pool options and the health report behind the Advanced settings page.
+
+use async_trait::async_trait;
+use serde::Serialize;
+use crate::domain::cqrs::{Command, Query};
+use crate::error::AppError;
+
+/// Connection pragmas and pool tuning.
+///
+/// Overridable through the config layers below the database (config files,
+/// `APP_DB_*` env vars, CLI), since they are needed before the database opens:
+/// `db_journal_mode`, `db_synchronous`, `db_busy_timeout_ms`, `db_foreign_keys`,
+/// `db_auto_vacuum`, `db_max_connections`, `db_acquire_timeout_ms`, `db_slow_query_ms`.
+#[derive(Debug, Clone, Serialize)]
+pub struct DbOptions {
+    pub journal_mode: String,
+    pub synchronous: String,
+    pub busy_timeout_ms: u64,
+    pub foreign_keys: bool,
+    pub auto_vacuum: String,
+    pub max_connections: u32,
+    pub acquire_timeout_ms: u64,
+    /// Queries at least this slow are logged at WARN
+    pub slow_query_ms: u64,
+}
+
+impl Default for DbOptions {
+    fn default() -> Self {
+        Self {
+            // WAL lets readers proceed while a writer is active
+            journal_mode: "WAL".to_string(),
+            // NORMAL is durable in WAL mode except on power loss, and much faster than FULL
+            synchronous: "NORMAL".to_string(),
+            // Wait for locks instead of failing with "database is locked"
+            busy_timeout_ms: 5_000,
+            foreign_keys: true,
+            // Lets maintenance reclaim free pages without a full VACUUM.
+            // Existing databases switch over on their next full VACUUM (compact).
+            auto_vacuum: "INCREMENTAL".to_string(),
+            max_connections: 5,
+            acquire_timeout_ms: 30_000,
+            slow_query_ms: 200,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DbHealthReport {
+    /// True when `PRAGMA integrity_check` reports "ok"
+    pub healthy: bool,
+    /// Raw `PRAGMA integrity_check` output ("ok" or a list of problems)
+    pub integrity_check: Vec<String>,
+    pub file_path: String,
+    pub file_size_bytes: u64,
+    pub wal_size_bytes: u64,
+    pub page_size: i64,
+    pub page_count: i64,
+    pub freelist_count: i64,
+    /// Pragmas as reported by a live connection
+    pub pragmas: PragmaReport,
+    /// Options the pool was configured with
+    pub options: DbOptions,
+    pub migrations: Vec<MigrationInfo>,
+    /// Applied settings key migration versions
+    pub settings_migrations: Vec<i64>,
+    pub tables: Vec<TableStats>,
+    pub pool: PoolStats,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PragmaReport {
+    pub journal_mode: String,
+    pub synchronous: i64,
+    pub busy_timeout_ms: i64,
+    pub foreign_keys: bool,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct MigrationInfo {
+    pub version: i64,
+    pub description: String,
+    pub installed_on: String,
+    pub success: bool,
+    /// Hex-encoded SHA-384 checksum recorded by sqlx
+    #[sqlx(try_from = "Vec<u8>")]
+    pub checksum: HexChecksum,
+}
+
+/// Checksum bytes serialized as a hex string
+#[derive(Debug)]
+pub struct HexChecksum(String);
+
+impl From<Vec<u8>> for HexChecksum {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+    }
+}
+
+impl Serialize for HexChecksum {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TableStats {
+    pub name: String,
+    pub rows: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PoolStats {
+    pub size: u32,
+    pub idle: usize,
+    pub max_connections: u32,
+}
+
+/// Aggregated timings of one statement since startup (or the last reset)
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryStat {
+    pub statement: String,
+    pub calls: u64,
+    pub errors: u64,
+    /// Calls slower than the threshold
+    pub slow: u64,
+    /// Rows returned or affected, summed over all calls
+    pub rows: u64,
+    pub total_ms: f64,
+    pub mean_ms: f64,
+    pub max_ms: f64,
+}
+
+// ============ Queries ============
+
+/// Query to collect a full health report of the live database
+#[derive(Debug)]
+pub struct CheckDbHealthQuery;
+
+impl Query for CheckDbHealthQuery {}
+
+/// Query for per-statement timings, slowest in total first
+#[derive(Debug)]
+pub struct GetQueryStatsQuery;
+
+impl Query for GetQueryStatsQuery {}
+
+// ============ Commands ============
+
+/// Command to start the query statistics over
+#[derive(Debug)]
+pub struct ResetQueryStatsCmd;
+
+impl Command for ResetQueryStatsCmd {}
+
+// ============ Diagnostics ============
+
+#[async_trait]
+pub trait IDbDiagnostics: Send + Sync {
+    /// Integrity check, pragmas, migrations, table sizes and pool usage.
+    async fn collect(&self) -> Result<DbHealthReport, AppError>;
+}
+
+/// Timings of the statements the repositories ran.
+pub trait IQueryStats: Send + Sync {
+    /// Statistics per statement, slowest in total first.
+    fn snapshot(&self) -> Vec<QueryStat>;
+    fn reset(&self);
+}
@@ -0,0 +1,213 @@
+use std::path::PathBuf;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+use crate::error::AppError;
+use crate::domain::cqrs::{Command, Query};
+
+/// Setting: how many queued downloads may run at the same time.
+pub const DOWNLOAD_CONCURRENCY_KEY: &str = "download_concurrency";
+
+pub const DEFAULT_DOWNLOAD_CONCURRENCY: usize = 3;
+
+/// A finished download
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadResult {
+    pub id: String,
+    pub path: String,
+    pub bytes: u64,
+    /// Hex-encoded SHA-256 of the downloaded file
+    pub sha256: String,
+}
+
+/// A download tracked by the download manager (`downloads` table)
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Download {
+    pub id: String,
+    pub url: String,
+    /// Destination; data is written to `<path>.part` until the download completes
+    pub path: String,
+    pub state: DownloadState,
+    pub bytes_done: i64,
+    /// `None` while the size is unknown
+    pub total_bytes: Option<i64>,
+    /// Validator sent as `If-Range` when resuming, so a changed file starts over
+    pub etag: Option<String>,
+    /// Sent as `If-Range` instead when the server gave no ETag
+    pub last_modified: Option<String>,
+    /// Expected hex-encoded SHA-256
+    pub sha256: Option<String>,
+    /// Replace an existing file at `path` when the download completes
+    pub overwrite: bool,
+    /// Why the last attempt failed
+    pub error: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum DownloadState {
+    Queued,
+    Running,
+    Paused,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+// ============ Commands ============
+
+/// Command to download `url` to `path`, publishing `DownloadProgress` events.
+/// Completes when the file has been written (or the download failed or was cancelled).
+#[derive(Debug, Deserialize)]
+pub struct StartDownloadCmd {
+    /// Chosen by the caller so it can cancel before the download finishes; generated if missing
+    pub id: Option<String>,
+    pub url: String,
+    pub path: String,
+    /// Expected hex-encoded SHA-256; the file is discarded on mismatch
+    pub sha256: Option<String>,
+    /// Replace an existing file at `path` instead of failing
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+impl Command for StartDownloadCmd {}
+
+/// Command to cancel a download started with `StartDownloadCmd`
+#[derive(Debug, Deserialize)]
+pub struct CancelDownloadCmd {
+    pub id: String,
+}
+
+impl Command for CancelDownloadCmd {}
+
+/// Command to add a download to the manager's queue. `path` follows the rules of
+/// `StartDownloadCmd`, checked when queued and again whenever the download runs.
+#[derive(Debug, Deserialize)]
+pub struct EnqueueDownloadCmd {
+    pub url: String,
+    pub path: String,
+    pub sha256: Option<String>,
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+impl Command for EnqueueDownloadCmd {}
+
+/// Command to stop a queued or running download, keeping what was downloaded so far
+#[derive(Debug, Deserialize)]
+pub struct PauseDownloadCmd {
+    pub id: String,
+}
+
+impl Command for PauseDownloadCmd {}
+
+/// Command to queue a paused or failed download again; it continues where it stopped
+#[derive(Debug, Deserialize)]
+pub struct ResumeDownloadCmd {
+    pub id: String,
+}
+
+impl Command for ResumeDownloadCmd {}
+
+/// Command to cancel a managed download and delete its partial file
+#[derive(Debug, Deserialize)]
+pub struct CancelManagedDownloadCmd {
+    pub id: String,
+}
+
+impl Command for CancelManagedDownloadCmd {}
+
+// ============ Queries ============
+
+/// Query to list managed downloads, newest first
+#[derive(Debug)]
+pub struct ListDownloadsQuery;
+
+impl Query for ListDownloadsQuery {}
+
+// ============ Repository ============
+
+#[async_trait]
+pub trait IDownloadRepository: Send + Sync {
+    async fn insert(&self, download: &Download) -> Result<Download, AppError>;
+    /// Newest first
+    async fn list(&self) -> Result<Vec<Download>, AppError>;
+    async fn find(&self, id: &str) -> Result<Option<Download>, AppError>;
+    /// Queued downloads, oldest first
+    async fn list_queued(&self) -> Result<Vec<Download>, AppError>;
+    async fn set_state(&self, id: &str, state: DownloadState, error: Option<&str>) -> Result<(), AppError>;
+    async fn save_progress(&self, id: &str, progress: &TransferProgress) -> Result<(), AppError>;
+    /// Queue downloads left running by a previous session. Returns how many were requeued.
+    async fn requeue_interrupted(&self) -> Result<u64, AppError>;
+}
+
+// ============ Downloader ============
+
+#[derive(Debug, Clone)]
+pub struct DownloadedFile {
+    pub bytes: u64,
+    /// Hex-encoded SHA-256
+    pub sha256: String,
+}
+
+/// Position of a running transfer
+#[derive(Debug, Clone, Default)]
+pub struct TransferProgress {
+    /// Bytes in the partial file so far
+    pub bytes: u64,
+    /// `None` if the server did not send a length
+    pub total: Option<u64>,
+    /// Validators of the resource, sent as `If-Range` when resuming
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+/// Called after every chunk written
+pub type ProgressFn<'a> = dyn Fn(&TransferProgress) + Send + Sync + 'a;
+
+#[async_trait]
+pub trait IFileDownloader: Send + Sync {
+    /// Stream `url` into a temporary file next to `path` and move it into place once
+    /// complete (and the checksum matches). Nothing is left behind on failure.
+    /// `path` must be a place the app may write to; an existing file is only replaced with `overwrite`.
+    async fn download(
+        &self,
+        url: &str,
+        path: &str,
+        expected_sha256: Option<&str>,
+        overwrite: bool,
+        progress: &ProgressFn<'_>,
+        cancel: CancellationToken,
+    ) -> Result<DownloadedFile, AppError>;
+
+    /// Fail unless `path` may be a destination of `download` or `finish`.
+    /// Returns the path with symlinks in its directory resolved.
+    fn check_target(&self, path: &str, overwrite: bool) -> Result<PathBuf, AppError>;
+
+    /// Append `url` to `partial` from `from.bytes` with a Range request, sending
+    /// `from`'s ETag or Last-Modified as `If-Range`. Starts over if there is no
+    /// validator, the server ignores the range, the resource no longer matches,
+    /// or the partial file does not match the resource's known size.
+    async fn transfer(
+        &self,
+        url: &str,
+        partial: &str,
+        from: &TransferProgress,
+        progress: &ProgressFn<'_>,
+        cancel: CancellationToken,
+    ) -> Result<TransferProgress, AppError>;
+
+    /// Verify a completed `partial` file against `expected_sha256` and move it to `path`,
+    /// under the same rules as `download`.
+    async fn finish(
+        &self,
+        partial: &str,
+        path: &str,
+        expected_sha256: Option<&str>,
+        overwrite: bool,
+    ) -> Result<DownloadedFile, AppError>;
+}
@@ -1,12 +1,34 @@
+use std::collections::HashMap;
 use async_trait::async_trait;
 use serde::Serialize;
+use crate::domain::downloads::DownloadState;
+use crate::domain::http::CircuitState;
 
 #[derive(Debug, Serialize, Clone)]
 #[serde(tag = "event", content = "payload")] // { "event": "ConfigChanged", "payload": { ... } }
 pub enum DomainEvent {
     ConfigChanged { key: String, value: String },
+    /// Several settings written atomically by one command
+    ConfigBatchChanged { values: HashMap<String, String> },
+    /// A stored setting was removed and falls back to its default
+    ConfigDeleted { key: String },
+    /// Every stored setting was removed
+    ConfigReset,
+    /// The effective value of a flag changed for a user (`None` = no user context)
+    FeatureFlagChanged { key: String, user_id: Option<String>, enabled: bool },
+    /// The live database was replaced by a backup; cached data is stale
+    DatabaseRestored { backup: String },
+    /// The data of an archive (file name) replaced the stored data; cached data is stale
+    ArchiveImported { archive: String },
+    /// Migrations above `version` were reverted (development builds); cached data is stale
+    MigrationsRolledBack { version: i64 },
+    /// Bytes written so far by a running download (`total` is `None` if the size is unknown)
+    DownloadProgress { id: String, bytes: u64, total: Option<u64> },
+    /// A managed download was queued, started, paused, finished, failed or cancelled
+    DownloadStateChanged { id: String, state: DownloadState },
+    /// The circuit breaker of a host opened, let a trial request through or closed again
+    CircuitBreakerChanged { host: String, state: CircuitState },
     // Future events:
-    // DownloadProgress { id: String, progress: u32 },
     // UserLoggedIn { user_id: String },
 }
 
@@ -15,6 +37,16 @@ impl DomainEvent {
     pub fn name(&self) -> &'static str {
         match self {
             DomainEvent::ConfigChanged { .. } => "config:changed",
+            DomainEvent::ConfigBatchChanged { .. } => "config:batch-changed",
+            DomainEvent::ConfigDeleted { .. } => "config:deleted",
+            DomainEvent::ConfigReset => "config:reset",
+            DomainEvent::FeatureFlagChanged { .. } => "feature-flag:changed",
+            DomainEvent::DatabaseRestored { .. } => "database:restored",
+            DomainEvent::ArchiveImported { .. } => "archive:imported",
+            DomainEvent::MigrationsRolledBack { .. } => "database:migrations-rolled-back",
+            DomainEvent::DownloadProgress { .. } => "download:progress",
+            DomainEvent::DownloadStateChanged { .. } => "download:state-changed",
+            DomainEvent::CircuitBreakerChanged { .. } => "http:circuit-changed",
         }
     }
 }
@@ -23,3 +55,9 @@ impl DomainEvent {
 pub trait IEventPublisher: Send + Sync {
     fn publish(&self, event: DomainEvent);
 }
+
+/// In-process listener for domain events (e.g. cache invalidation).
+/// Subscribers are notified synchronously before the event reaches the frontend.
+pub trait IEventSubscriber: Send + Sync {
+    fn on_event(&self, event: &DomainEvent);
+}
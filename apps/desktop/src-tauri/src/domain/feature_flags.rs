@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use crate::error::AppError;
+use crate::domain::cqrs::{Command, Query};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct FeatureFlag {
+    pub key: String,
+    pub description: String,
+    pub default_enabled: bool,
+    /// Percentage of users (0-100) the flag is enabled for, bucketed by user id.
+    pub rollout_percentage: Option<i64>,
+    /// RFC 3339 timestamp after which the flag falls back to `default_enabled`.
+    pub expires_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Why a flag evaluated to its value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagSource {
+    Default,
+    Override,
+    Rollout,
+    Expired,
+}
+
+/// A flag together with its effective value for a user
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureFlagState {
+    #[serde(flatten)]
+    pub flag: FeatureFlag,
+    pub enabled: bool,
+    pub source: FlagSource,
+}
+
+impl FeatureFlag {
+    /// Evaluate the flag. Precedence: expiry > per-user override > rollout > default.
+    pub fn evaluate(&self, user_id: Option<&str>, user_override: Option<bool>) -> (bool, FlagSource) {
+        if self.is_expired() {
+            return (self.default_enabled, FlagSource::Expired);
+        }
+        if let Some(enabled) = user_override {
+            return (enabled, FlagSource::Override);
+        }
+        if let (Some(percentage), Some(user_id)) = (self.rollout_percentage, user_id) {
+            return (rollout_bucket(&self.key, user_id) < percentage, FlagSource::Rollout);
+        }
+        (self.default_enabled, FlagSource::Default)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .as_deref()
+            .and_then(|s| OffsetDateTime::parse(s, &Rfc3339).ok())
+            .is_some_and(|expires_at| expires_at <= OffsetDateTime::now_utc())
+    }
+}
+
+/// Stable 0-99 bucket for a (flag, user) pair, so a user keeps the same
+/// rollout decision across launches and flags roll out independently (FNV-1a).
+fn rollout_bucket(flag_key: &str, user_id: &str) -> i64 {
+    let mut hash: u32 = 0x811c_9dc5;
+    for byte in flag_key.bytes().chain(std::iter::once(b':')).chain(user_id.bytes()) {
+        hash ^= u32::from(byte);
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+    i64::from(hash % 100)
+}
+
+// ============ Commands ============
+
+/// Command to create or update a feature flag
+#[derive(Debug, Deserialize)]
+pub struct UpsertFeatureFlagCmd {
+    pub key: String,
+    #[serde(default)]
+    pub description: String,
+    pub default_enabled: bool,
+    pub rollout_percentage: Option<i64>,
+    pub expires_at: Option<String>,
+}
+
+impl Command for UpsertFeatureFlagCmd {}
+
+impl UpsertFeatureFlagCmd {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.key.trim().is_empty() {
+            return Err(AppError::Domain("Flag key must not be empty".to_string()));
+        }
+        if let Some(percentage) = self.rollout_percentage {
+            if !(0..=100).contains(&percentage) {
+                return Err(AppError::Domain("Rollout percentage must be between 0 and 100".to_string()));
+            }
+        }
+        if let Some(expires_at) = &self.expires_at {
+            OffsetDateTime::parse(expires_at, &Rfc3339)
+                .map_err(|e| AppError::Domain(format!("Invalid expiry date (expected RFC 3339): {}", e)))?;
+        }
+        Ok(())
+    }
+}
+
+/// Command to delete a feature flag and its overrides
+#[derive(Debug, Deserialize)]
+pub struct DeleteFeatureFlagCmd {
+    pub key: String,
+}
+
+impl Command for DeleteFeatureFlagCmd {}
+
+/// Command to set (or clear, with `enabled: None`) a per-user override
+#[derive(Debug, Deserialize)]
+pub struct SetFlagOverrideCmd {
+    pub key: String,
+    pub user_id: String,
+    pub enabled: Option<bool>,
+}
+
+impl Command for SetFlagOverrideCmd {}
+
+// ============ Queries ============
+
+/// Query whether a flag is enabled, optionally for a specific user.
+/// Unknown flags are disabled.
+#[derive(Debug)]
+pub struct IsFlagEnabledQuery {
+    pub key: String,
+    pub user_id: Option<String>,
+}
+
+impl Query for IsFlagEnabledQuery {}
+
+/// Query to list all flags with their effective value, optionally for a specific user
+#[derive(Debug)]
+pub struct ListFlagsQuery {
+    pub user_id: Option<String>,
+}
+
+impl Query for ListFlagsQuery {}
+
+// ============ Repository ============
+
+#[async_trait]
+pub trait IFeatureFlagRepository: Send + Sync {
+    async fn list(&self) -> Result<Vec<FeatureFlag>, AppError>;
+    async fn find(&self, key: &str) -> Result<Option<FeatureFlag>, AppError>;
+    async fn upsert(&self, cmd: &UpsertFeatureFlagCmd) -> Result<FeatureFlag, AppError>;
+    async fn delete(&self, key: &str) -> Result<(), AppError>;
+    async fn get_override(&self, key: &str, user_id: &str) -> Result<Option<bool>, AppError>;
+    /// All overrides for a user, keyed by flag key.
+    async fn list_overrides(&self, user_id: &str) -> Result<HashMap<String, bool>, AppError>;
+    async fn set_override(&self, key: &str, user_id: &str, enabled: bool) -> Result<(), AppError>;
+    async fn clear_override(&self, key: &str, user_id: &str) -> Result<(), AppError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flag(default_enabled: bool, rollout_percentage: Option<i64>, expires_at: Option<&str>) -> FeatureFlag {
+        FeatureFlag {
+            key: "new_ui".to_string(),
+            description: String::new(),
+            default_enabled,
+            rollout_percentage,
+            expires_at: expires_at.map(str::to_string),
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn rollout_bucket_is_fnv1a_of_flag_and_user() {
+        // FNV-1a("new_ui:alice") = 0x53a45852, FNV-1a("new_ui:bob") = 0x5e42a9ad
+        assert_eq!(rollout_bucket("new_ui", "alice"), 0x53a4_5852 % 100);
+        assert_eq!(rollout_bucket("new_ui", "bob"), 0x5e42_a9ad % 100);
+    }
+
+    #[test]
+    fn rollout_buckets_are_spread_evenly() {
+        let users = 10_000;
+        let below_half = (0..users)
+            .filter(|i| rollout_bucket("new_ui", &format!("user-{}", i)) < 50)
+            .count();
+        assert!((4_500..=5_500).contains(&below_half), "{} of {} users", below_half, users);
+        assert!((0..users).all(|i| (0..100).contains(&rollout_bucket("x", &i.to_string()))));
+    }
+
+    #[test]
+    fn flags_roll_out_independently() {
+        let differs = (0..100)
+            .filter(|i| {
+                let user = format!("user-{}", i);
+                rollout_bucket("flag_a", &user) != rollout_bucket("flag_b", &user)
+            })
+            .count();
+        assert!(differs > 90);
+    }
+
+    #[test]
+    fn evaluation_precedence() {
+        let past = Some("2000-01-01T00:00:00Z");
+        let future = Some("2999-01-01T00:00:00Z");
+
+        // Expiry beats overrides and rollout
+        assert_eq!(flag(false, Some(100), past).evaluate(Some("alice"), Some(true)), (false, FlagSource::Expired));
+        // Overrides beat rollout
+        assert_eq!(flag(false, Some(100), future).evaluate(Some("alice"), Some(false)), (false, FlagSource::Override));
+        // Rollout needs a user
+        assert_eq!(flag(false, Some(100), None).evaluate(Some("alice"), None), (true, FlagSource::Rollout));
+        assert_eq!(flag(false, Some(0), None).evaluate(Some("alice"), None), (false, FlagSource::Rollout));
+        assert_eq!(flag(true, Some(0), None).evaluate(None, None), (true, FlagSource::Default));
+    }
+
+    #[test]
+    fn upsert_validation() {
+        let cmd = |key: &str, rollout: Option<i64>, expires_at: Option<&str>| UpsertFeatureFlagCmd {
+            key: key.to_string(),
+            description: String::new(),
+            default_enabled: false,
+            rollout_percentage: rollout,
+            expires_at: expires_at.map(str::to_string),
+        };
+        assert!(cmd("new_ui", Some(50), Some("2030-01-01T00:00:00Z")).validate().is_ok());
+        assert!(cmd(" ", None, None).validate().is_err());
+        assert!(cmd("new_ui", Some(101), None).validate().is_err());
+        assert!(cmd("new_ui", None, Some("tomorrow")).validate().is_err());
+    }
+}
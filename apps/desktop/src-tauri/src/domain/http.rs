@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use crate::error::AppError;
+use crate::domain::cqrs::{Command, Query};
+
+// Settings used by every request that does not bring its own policy
+pub const HTTP_MAX_RETRIES_KEY: &str = "http_max_retries";
+pub const HTTP_RETRY_BASE_DELAY_MS_KEY: &str = "http_retry_base_delay_ms";
+pub const HTTP_RETRY_MAX_DELAY_MS_KEY: &str = "http_retry_max_delay_ms";
+pub const HTTP_RETRY_NON_IDEMPOTENT_KEY: &str = "http_retry_non_idempotent";
+pub const HTTP_CIRCUIT_FAILURE_THRESHOLD_KEY: &str = "http_circuit_failure_threshold";
+pub const HTTP_CIRCUIT_OPEN_SECS_KEY: &str = "http_circuit_open_secs";
+pub const HTTP_TIMEOUT_SECS_KEY: &str = "http_timeout_secs";
+pub const HTTP_USER_AGENT_KEY: &str = "http_user_agent";
+// Read only from config files, env vars and the CLI, like the HTTP scope
+pub const HTTP_PROXY_URL_KEY: &str = "http_proxy_url";
+pub const HTTP_NO_PROXY_KEY: &str = "http_no_proxy";
+pub const HTTP_CA_BUNDLE_PATH_KEY: &str = "http_ca_bundle_path";
+pub const HTTP_CACHE_ENABLED_KEY: &str = "http_cache_enabled";
+pub const HTTP_CACHE_MAX_BYTES_KEY: &str = "http_cache_max_bytes";
+
+/// Prefix shared by all HTTP client settings
+pub const HTTP_SETTINGS_PREFIX: &str = "http_";
+
+/// Options the client is built with; changing any of them rebuilds it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientOptions {
+    /// Default total timeout of a request, and the longest a download may stall
+    pub timeout_secs: u64,
+    pub user_agent: String,
+    /// Proxy for all requests, e.g. `http://proxy.corp:8080`. Without it the
+    /// `HTTP_PROXY` / `HTTPS_PROXY` environment variables apply.
+    pub proxy_url: Option<String>,
+    /// Comma-separated hosts, domains and CIDR ranges that bypass the proxy
+    pub no_proxy: Option<String>,
+    /// PEM file with root certificates trusted in addition to the built-in ones
+    pub ca_bundle_path: Option<String>,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 30,
+            user_agent: "Tauri-React-Template/0.1.0".to_string(),
+            proxy_url: None,
+            no_proxy: None,
+            ca_bundle_path: None,
+        }
+    }
+}
+
+/// Whether a request follows redirects, e.g. `{ "type": "follow", "max": 5 }`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RedirectPolicy {
+    /// Follow up to `max` redirects; a longer chain fails the request.
+    /// `max` is rounded down to 1, 2, 3, 5, 10 or 20.
+    Follow { max: usize },
+    /// Return redirect responses as they are
+    None,
+}
+
+impl RedirectPolicy {
+    pub const DEFAULT_MAX: usize = 10;
+}
+
+impl Default for RedirectPolicy {
+    fn default() -> Self {
+        RedirectPolicy::Follow { max: Self::DEFAULT_MAX }
+    }
+}
+
+/// How a request uses the response cache (like `fetch`'s `cache` option)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheMode {
+    /// Serve fresh responses from the cache, revalidate stale ones
+    Default,
+    /// Always revalidate a stored response before using it
+    NoCache,
+    /// Neither read nor write the cache
+    NoStore,
+}
+
+/// Where a response came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheStatus {
+    /// The cache was not used (disabled, `no_store` or not a GET request)
+    #[default]
+    Bypass,
+    /// Fetched from the server
+    Miss,
+    /// Served from the cache without contacting the server
+    Hit,
+    /// Served from the cache after the server answered 304 Not Modified
+    Revalidated,
+}
+
+/// When to retry a failed request. Connection errors, timeouts and
+/// `retry_statuses` are retried; anything else is returned as is.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Attempts after the first one; 0 disables retries
+    pub max_retries: u32,
+    /// Backoff before the first retry, doubled for every further one (with jitter)
+    pub base_delay_ms: u64,
+    /// Upper bound of a backoff. A longer `Retry-After` ends the retries.
+    pub max_delay_ms: u64,
+    /// Also retry POST and PATCH, which may repeat their side effects.
+    /// Requests that could not connect are always retried.
+    pub retry_non_idempotent: bool,
+    pub retry_statuses: Vec<u16>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            base_delay_ms: 250,
+            max_delay_ms: 10_000,
+            retry_non_idempotent: false,
+            retry_statuses: vec![408, 429, 500, 502, 503, 504],
+        }
+    }
+}
+
+/// When to stop sending requests to a failing host
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CircuitBreakerPolicy {
+    /// Consecutive failures (connection errors, timeouts, 5xx) that open the circuit; 0 disables it
+    pub failure_threshold: u32,
+    /// How long an open circuit rejects requests before a single trial request is let through
+    pub open_secs: u64,
+}
+
+impl Default for CircuitBreakerPolicy {
+    fn default() -> Self {
+        Self { failure_threshold: 5, open_secs: 30 }
+    }
+}
+
+/// Global HTTP client settings, built from the `http_*` settings
+#[derive(Debug, Clone)]
+pub struct HttpSettings {
+    pub client: ClientOptions,
+    /// Whether requests without a `cache` mode use the response cache
+    pub cache_enabled: bool,
+    /// Total size of the cached bodies; the oldest entries beyond it are dropped
+    pub cache_max_bytes: u64,
+    pub retry: RetryPolicy,
+    pub circuit_breaker: CircuitBreakerPolicy,
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        Self {
+            client: ClientOptions::default(),
+            cache_enabled: false,
+            cache_max_bytes: 50 * 1024 * 1024,
+            retry: RetryPolicy::default(),
+            circuit_breaker: CircuitBreakerPolicy::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Requests pass
+    Closed,
+    /// Requests are rejected without being sent
+    Open,
+    /// A trial request is in flight; its outcome closes or reopens the circuit
+    HalfOpen,
+}
+
+/// Circuit breaker of a host that failed recently
+#[derive(Debug, Clone, Serialize)]
+pub struct CircuitBreakerStatus {
+    /// `host:port`
+    pub host: String,
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    /// Time until an open circuit lets a trial request through
+    pub retry_in_ms: Option<u64>,
+}
+
+/// A response in the cache (`http_cache` table)
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    pub method: String,
+    pub url: String,
+    pub status: u16,
+    /// Header names are lowercase
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+    /// Values of the request headers named by `Vary`; a request with other values misses
+    pub vary: HashMap<String, String>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// Unix seconds; fresh until then
+    pub expires_at: i64,
+}
+
+/// A cached response without its body, for inspecting the cache
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct HttpCacheEntry {
+    pub method: String,
+    pub url: String,
+    pub status: i64,
+    /// Body size in bytes
+    pub size: i64,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// Unix seconds
+    pub expires_at: i64,
+    pub stored_at: String,
+}
+
+/// A cookie in the jar. HttpOnly values stay in the backend.
+#[derive(Debug, Clone, Serialize)]
+pub struct CookieInfo {
+    pub domain: String,
+    /// Sent to `domain` only, not to its subdomains (no `Domain` attribute)
+    pub host_only: bool,
+    pub path: String,
+    pub name: String,
+    /// `None` for HttpOnly cookies
+    pub value: Option<String>,
+    pub secure: bool,
+    pub http_only: bool,
+    /// RFC 3339; `None` for session cookies
+    pub expires: Option<String>,
+}
+
+// ============ Commands ============
+
+/// Command to remove every cached response. Returns how many were removed.
+#[derive(Debug)]
+pub struct ClearHttpCacheCmd;
+
+impl Command for ClearHttpCacheCmd {}
+
+/// Command to remove the cookies of `domain` and its subdomains, or every
+/// cookie if `None`. Returns how many were removed.
+#[derive(Debug)]
+pub struct ClearCookiesCmd {
+    pub domain: Option<String>,
+}
+
+impl Command for ClearCookiesCmd {}
+
+// ============ Queries ============
+
+/// Query the circuit breakers of hosts that failed recently
+#[derive(Debug)]
+pub struct ListCircuitBreakersQuery;
+
+impl Query for ListCircuitBreakersQuery {}
+
+/// Query the cached responses, most recently stored first
+#[derive(Debug)]
+pub struct ListHttpCacheQuery;
+
+impl Query for ListHttpCacheQuery {}
+
+/// Query the cookies of `domain` and its subdomains, or every cookie if `None`
+#[derive(Debug)]
+pub struct ListCookiesQuery {
+    pub domain: Option<String>,
+}
+
+impl Query for ListCookiesQuery {}
+
+// ============ Repository ============
+
+#[async_trait]
+pub trait IHttpCacheRepository: Send + Sync {
+    /// Keyed by `<METHOD> <url>`
+    async fn find(&self, method: &str, url: &str) -> Result<Option<CachedResponse>, AppError>;
+    /// Insert or replace, then drop the oldest entries beyond `max_entries`
+    /// or beyond `max_bytes` of bodies in total
+    async fn store(&self, response: &CachedResponse, max_entries: u32, max_bytes: u64) -> Result<(), AppError>;
+    async fn remove(&self, method: &str, url: &str) -> Result<(), AppError>;
+    async fn list(&self) -> Result<Vec<HttpCacheEntry>, AppError>;
+    /// Returns how many entries were removed
+    async fn clear(&self) -> Result<u64, AppError>;
+}
+
+// ============ Client ============
+
+/// The shared HTTP client, as seen by the services that configure it.
+pub trait IHttpClient: Send + Sync {
+    /// Replace the settings used by requests that do not override them.
+    /// Rebuilds the client if its options changed; fails (keeping the old one) if they are invalid.
+    fn apply_settings(&self, settings: HttpSettings) -> Result<(), AppError>;
+    fn circuit_breakers(&self) -> Vec<CircuitBreakerStatus>;
+}
+
+/// The cookies the client keeps between requests and app launches.
+pub trait ICookieJar: Send + Sync {
+    /// Unexpired cookies of `domain` and its subdomains (all if `None`), sorted by domain, path and name
+    fn list(&self, domain: Option<&str>) -> Vec<CookieInfo>;
+    /// Returns how many cookies were removed
+    fn clear(&self, domain: Option<&str>) -> Result<u64, AppError>;
+}
@@ -0,0 +1,51 @@
+use async_trait::async_trait;
+use serde::Serialize;
+use crate::error::AppError;
+use crate::domain::cqrs::Command;
+
+/// Setting: hours between scheduled maintenance runs ("0" disables).
+pub const MAINTENANCE_INTERVAL_HOURS_KEY: &str = "maintenance_interval_hours";
+/// Setting (written by the app): RFC 3339 time of the last maintenance run.
+pub const MAINTENANCE_LAST_RUN_KEY: &str = "maintenance_last_run";
+
+pub const DEFAULT_MAINTENANCE_INTERVAL_HOURS: u64 = 24;
+
+/// Outcome of a maintenance run
+#[derive(Debug, Clone, Serialize)]
+pub struct MaintenanceReport {
+    /// RFC 3339 start time
+    pub started_at: String,
+    pub duration_ms: u64,
+    /// Whether a full `VACUUM` ran (compact) or only incremental steps
+    pub full_vacuum: bool,
+    /// Database + WAL size before and after
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    pub bytes_reclaimed: u64,
+}
+
+// ============ Commands ============
+
+/// Command to run routine maintenance (optimize, ANALYZE, incremental vacuum)
+#[derive(Debug)]
+pub struct RunMaintenanceCmd;
+
+impl Command for RunMaintenanceCmd {}
+
+/// Command to fully compact the database (VACUUM)
+#[derive(Debug)]
+pub struct CompactDatabaseCmd;
+
+impl Command for CompactDatabaseCmd {}
+
+// ============ Maintenance ============
+
+#[async_trait]
+pub trait IDatabaseMaintenance: Send + Sync {
+    /// `PRAGMA optimize`, `ANALYZE` and `PRAGMA incremental_vacuum`.
+    async fn optimize(&self) -> Result<MaintenanceReport, AppError>;
+    /// Full `VACUUM`, rebuilding the file and reclaiming all free pages.
+    async fn compact(&self) -> Result<MaintenanceReport, AppError>;
+    /// True when no connection is checked out of the pool.
+    fn is_idle(&self) -> bool;
+}
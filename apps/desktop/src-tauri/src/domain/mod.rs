@@ -1,4 +1,14 @@
+pub mod archive;
+pub mod autostart;
+pub mod backups;
 pub mod config;
 pub mod cqrs;
+pub mod diagnostics;
+pub mod downloads;
 pub mod events;
+pub mod feature_flags;
+pub mod http;
+pub mod maintenance;
+pub mod recovery;
+pub mod secrets;
 pub mod users;
\ No newline at end of file
@@ -0,0 +1,79 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use crate::error::AppError;
+use crate::domain::backups::BackupInfo;
+use crate::domain::cqrs::Command;
+
+/// State of recovery mode, entered when the database fails to open at startup
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoveryStatus {
+    /// False once the database has been opened successfully
+    pub active: bool,
+    /// Why the database could not be opened (last failed attempt)
+    pub error: String,
+    pub db_path: String,
+    /// Backups that can be restored, newest first
+    pub backups: Vec<BackupInfo>,
+}
+
+/// Result of salvaging data from a database that cannot be opened normally
+#[derive(Debug, Clone, Serialize)]
+pub struct SalvageReport {
+    /// JSON file the readable rows were written to
+    pub path: String,
+    pub tables: Vec<SalvagedTable>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SalvagedTable {
+    pub name: String,
+    pub rows: usize,
+    /// Set when the table could not be read (fully or partially)
+    pub error: Option<String>,
+}
+
+// ============ Commands ============
+
+/// Command to try opening the database again
+#[derive(Debug)]
+pub struct RetryDatabaseCmd;
+
+impl Command for RetryDatabaseCmd {}
+
+/// Command to replace the broken database with a backup, then open it
+#[derive(Debug, Deserialize)]
+pub struct RecoverFromBackupCmd {
+    pub name: String,
+}
+
+impl Command for RecoverFromBackupCmd {}
+
+/// Command to move the broken database aside and start with a fresh one.
+/// Returns the file name the broken database was moved to.
+#[derive(Debug)]
+pub struct StartFreshDatabaseCmd;
+
+impl Command for StartFreshDatabaseCmd {}
+
+/// Command to export whatever rows can still be read from the broken database
+#[derive(Debug)]
+pub struct ExportSalvageCmd;
+
+impl Command for ExportSalvageCmd {}
+
+// ============ Recovery ============
+
+/// File-level operations on a database that failed to open.
+#[async_trait]
+pub trait IDatabaseRecovery: Send + Sync {
+    fn db_path(&self) -> String;
+    fn list_backups(&self) -> Result<Vec<BackupInfo>, AppError>;
+    /// Open the database and hand it over to the app. Fails if it still cannot be opened.
+    async fn reopen(&self) -> Result<(), AppError>;
+    /// Move the database file (and its WAL/SHM files) aside. Returns the new file name.
+    async fn move_aside(&self) -> Result<String, AppError>;
+    /// Copy a backup over the database file.
+    async fn restore_backup(&self, name: &str) -> Result<(), AppError>;
+    /// Read every readable row into a JSON file next to the database.
+    async fn export_salvage(&self) -> Result<SalvageReport, AppError>;
+}
@@ -0,0 +1,111 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use crate::error::AppError;
+use crate::domain::cqrs::{Command, Query};
+
+/// Static config key (config files, env, CLI) naming the hosts a secret may be
+/// sent to, e.g. `secret_hosts_github_token = "api.github.com"`. A secret without
+/// it is never sent. Not read from the database, so the webview cannot change it.
+pub const SECRET_HOSTS_PREFIX: &str = "secret_hosts_";
+
+/// Metadata of a stored secret. The value itself never leaves the backend.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct SecretInfo {
+    pub key: String,
+    pub updated_at: String,
+}
+
+// ============ Commands ============
+
+/// Command to store (or replace) a secret value
+#[derive(Debug, Deserialize)]
+pub struct SetSecretCmd {
+    pub key: String,
+    pub value: String,
+}
+
+impl Command for SetSecretCmd {}
+
+/// Command to remove a secret
+#[derive(Debug, Deserialize)]
+pub struct ClearSecretCmd {
+    pub key: String,
+}
+
+impl Command for ClearSecretCmd {}
+
+// ============ Queries ============
+
+/// Query to list stored secret keys (without values)
+#[derive(Debug)]
+pub struct ListSecretsQuery;
+
+impl Query for ListSecretsQuery {}
+
+/// Query to read a decrypted secret value to send to `host`. Fails unless
+/// the secret is bound to that host (see `SECRET_HOSTS_PREFIX`) and the
+/// request goes over https.
+/// Backend-only: must never be exposed as a Tauri command.
+#[derive(Debug)]
+pub struct GetSecretQuery {
+    pub key: String,
+    /// URL scheme of the request the secret is sent with
+    pub scheme: String,
+    pub host: String,
+}
+
+impl Query for GetSecretQuery {}
+
+/// Whether `host` matches one of the comma-separated `patterns`: exact host
+/// names, or `*.example.com` for the subdomains of `example.com`.
+pub fn host_allowed(patterns: &str, host: &str) -> bool {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    patterns
+        .split(',')
+        .map(|pattern| pattern.trim().to_ascii_lowercase())
+        .filter(|pattern| !pattern.is_empty())
+        .any(|pattern| match pattern.strip_prefix("*.") {
+            Some(domain) => host.ends_with(&format!(".{}", domain)),
+            None => host == pattern,
+        })
+}
+
+// ============ Repository ============
+
+/// Stores secrets encrypted at rest. Implementations receive and return plaintext;
+/// encryption is an infrastructure concern.
+#[async_trait]
+pub trait ISecretRepository: Send + Sync {
+    async fn get(&self, key: &str) -> Result<Option<String>, AppError>;
+    async fn set(&self, key: &str, value: &str) -> Result<(), AppError>;
+    async fn delete(&self, key: &str) -> Result<(), AppError>;
+    async fn list(&self) -> Result<Vec<SecretInfo>, AppError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_host_matches_only_itself() {
+        assert!(host_allowed("api.github.com", "api.github.com"));
+        assert!(host_allowed("api.github.com", "API.GitHub.com."));
+        assert!(!host_allowed("api.github.com", "github.com"));
+        assert!(!host_allowed("api.github.com", "api.github.com.evil.io"));
+    }
+
+    #[test]
+    fn wildcard_matches_subdomains_only() {
+        assert!(host_allowed("*.example.com", "api.example.com"));
+        assert!(host_allowed("*.example.com", "a.b.example.com"));
+        assert!(!host_allowed("*.example.com", "example.com"));
+        assert!(!host_allowed("*.example.com", "badexample.com"));
+    }
+
+    #[test]
+    fn list_is_comma_separated() {
+        assert!(host_allowed(" a.io , b.io ", "b.io"));
+        assert!(!host_allowed("", "a.io"));
+        assert!(!host_allowed(" , ", "a.io"));
+    }
+}
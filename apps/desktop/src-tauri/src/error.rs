@@ -16,6 +16,13 @@ pub enum AppError {
     #[error("Not found: {0}")]
     NotFound(String),
     
+    #[error("Crypto error: {0}")]
+    Crypto(String),
+
+    /// An HTTP request outside the configured scope (scheme, host, port or private address)
+    #[error("Request outside the allowed scope: {0}")]
+    ScopeViolation(String),
+
     #[error("Tauri error: {0}")]
     Tauri(String),
     
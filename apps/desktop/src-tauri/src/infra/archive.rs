@@ -0,0 +1,491 @@
+//! Full-data archives: every table as JSON in a single zip file.
+//!
+//! Layout: `manifest.json` plus one `tables/<name>.json` per table, holding an
+//! array of row objects (BLOB columns hex-encoded). Secrets are not archived:
+//! they are encrypted with this install's master key and unreadable elsewhere.
+//! Neither are the download queue and the HTTP cache, which are runtime state of
+//! this install and in use while an import runs.
+//!
+//! Import tolerates archives from an older schema: tables and columns missing
+//! from the archive are cleared or take their column defaults. Changes that
+//! need more than that go into `UPGRADES` together with a format version bump.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use async_trait::async_trait;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use sqlx::sqlite::SqliteArguments;
+use sqlx::query::Query;
+use sqlx::{Sqlite, SqliteConnection};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use tracing::info;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::domain::archive::{ArchiveManifest, ArchivedTable, IArchiveStore, ARCHIVE_FORMAT_VERSION};
+use crate::error::AppError;
+use crate::infra::db::Database;
+use crate::infra::db_migrations::MIGRATOR;
+use crate::infra::db_recovery::{quote_ident, read_table};
+
+const MANIFEST_FILE: &str = "manifest.json";
+/// Schema bookkeeping, secrets and runtime state (see module docs) stay out of archives
+const EXCLUDED_TABLES: [&str; 4] = ["_sqlx_migrations", "secret_settings", "downloads", "http_cache"];
+/// Refuse to inflate entries beyond this size
+const MAX_ENTRY_BYTES: u64 = 512 * 1024 * 1024;
+
+pub(crate) type Rows = Vec<Map<String, Value>>;
+
+/// Decoded archive contents, keyed by table name
+pub(crate) struct ArchiveContents {
+    pub(crate) manifest: ArchiveManifest,
+    pub(crate) tables: BTreeMap<String, Rows>,
+}
+
+/// Upgrades an archive from format version `n` to `n + 1`.
+type ArchiveUpgrade = fn(&mut ArchiveContents) -> Result<(), AppError>;
+
+/// `(from_version, upgrade)`, applied in order until the archive reaches `ARCHIVE_FORMAT_VERSION`.
+const UPGRADES: &[(u32, ArchiveUpgrade)] = &[];
+
+pub struct SqliteArchiveStore {
+    db: Arc<Database>,
+}
+
+impl SqliteArchiveStore {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl IArchiveStore for SqliteArchiveStore {
+    async fn export(&self, path: &str) -> Result<ArchiveManifest, AppError> {
+        // A read transaction keeps every table from the same snapshot
+        let pool = self.db.pool().await;
+        let mut tx = pool.begin().await?;
+        let schema_version: Option<i64> =
+            sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success = 1")
+                .fetch_one(&mut *tx)
+                .await?;
+
+        let mut tables = BTreeMap::new();
+        for name in archived_tables(&mut tx).await? {
+            let rows = read_table(&mut tx, &name).await?;
+            tables.insert(name, rows);
+        }
+        tx.rollback().await?;
+
+        write_tables(Path::new(path), schema_version.unwrap_or(0), &tables)
+    }
+
+    async fn import(&self, path: &str) -> Result<ArchiveManifest, AppError> {
+        let contents = read_tables(Path::new(path))?;
+
+        let pool = self.db.pool().await;
+        let mut tx = pool.begin().await?;
+        // Rows are inserted table by table; check references once everything is in place
+        sqlx::query("PRAGMA defer_foreign_keys = ON").execute(&mut *tx).await?;
+
+        let current = archived_tables(&mut tx).await?;
+        if let Some(unknown) = contents.tables.keys().find(|name| !current.contains(name)) {
+            return Err(AppError::Domain(format!("Archive contains unknown table {}", unknown)));
+        }
+
+        for name in &current {
+            sqlx::query(&format!("DELETE FROM {}", quote_ident(name)))
+                .execute(&mut *tx)
+                .await?;
+        }
+        for (name, rows) in &contents.tables {
+            insert_rows(&mut tx, name, rows).await?;
+        }
+        tx.commit().await?;
+
+        info!(
+            "Imported {} tables from {} (format {}, schema {})",
+            contents.tables.len(),
+            path,
+            contents.manifest.format_version,
+            contents.manifest.schema_version
+        );
+        Ok(contents.manifest)
+    }
+}
+
+/// Write `tables` and their manifest into a new archive at `path`.
+pub(crate) fn write_tables(
+    path: &Path,
+    schema_version: i64,
+    tables: &BTreeMap<String, Vec<Value>>,
+) -> Result<ArchiveManifest, AppError> {
+    let mut archived = Vec::new();
+    let mut files = Vec::new();
+    for (name, rows) in tables {
+        let bytes = serde_json::to_vec_pretty(rows).map_err(|e| AppError::Unknown(e.to_string()))?;
+        let file = format!("tables/{}.json", name);
+        archived.push(ArchivedTable {
+            name: name.clone(),
+            file: file.clone(),
+            rows: rows.len(),
+            sha256: sha256_hex(&bytes),
+        });
+        files.push((file, bytes));
+    }
+
+    let manifest = ArchiveManifest {
+        format_version: ARCHIVE_FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        schema_version,
+        created_at: OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .map_err(|e| AppError::Unknown(e.to_string()))?,
+        tables: archived,
+    };
+    let manifest_bytes =
+        serde_json::to_vec_pretty(&manifest).map_err(|e| AppError::Unknown(e.to_string()))?;
+    files.push((MANIFEST_FILE.to_string(), manifest_bytes));
+
+    // Write next to the target and rename, so a failed export never leaves a truncated archive
+    let partial = PathBuf::from(format!("{}.partial", path.to_string_lossy()));
+    if let Err(e) = write_zip(&partial, &files) {
+        let _ = fs::remove_file(&partial);
+        return Err(e);
+    }
+    fs::rename(&partial, path)?;
+
+    info!("Exported {} tables to {:?}", manifest.tables.len(), path);
+    Ok(manifest)
+}
+
+/// Read an archive for import: verified, upgraded to the current format and
+/// checked against the schema versions this build knows.
+pub(crate) fn read_tables(path: &Path) -> Result<ArchiveContents, AppError> {
+    let mut contents = read_archive(path)?;
+    upgrade(&mut contents, UPGRADES)?;
+
+    let latest = MIGRATOR.iter().map(|m| m.version).max().unwrap_or(0);
+    if contents.manifest.schema_version > latest {
+        return Err(AppError::Domain(format!(
+            "Archive was written by a newer version of the app (schema {}, this build supports up to {})",
+            contents.manifest.schema_version, latest
+        )));
+    }
+    Ok(contents)
+}
+
+/// Tables that go into an archive, sorted by name.
+async fn archived_tables(conn: &mut SqliteConnection) -> Result<Vec<String>, AppError> {
+    let names: Vec<String> = sqlx::query_scalar(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(names
+        .into_iter()
+        .filter(|name| !EXCLUDED_TABLES.contains(&name.as_str()))
+        .collect())
+}
+
+async fn insert_rows(conn: &mut SqliteConnection, table: &str, rows: &Rows) -> Result<(), AppError> {
+    let columns: Vec<(String, String)> = sqlx::query_as("SELECT name, type FROM pragma_table_info(?)")
+        .bind(table)
+        .fetch_all(&mut *conn)
+        .await?;
+    let blob_columns: HashSet<&str> = columns
+        .iter()
+        .filter(|(_, ty)| ty.eq_ignore_ascii_case("BLOB"))
+        .map(|(name, _)| name.as_str())
+        .collect();
+
+    for row in rows {
+        if let Some(unknown) = row.keys().find(|key| !columns.iter().any(|(name, _)| name == *key)) {
+            return Err(AppError::Domain(format!("Archive table {} has unknown column {}", table, unknown)));
+        }
+
+        let names: Vec<String> = row.keys().map(|key| quote_ident(key)).collect();
+        let placeholders: Vec<&str> = row
+            .keys()
+            .map(|key| if blob_columns.contains(key.as_str()) { "unhex(?)" } else { "?" })
+            .collect();
+        let sql = format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            quote_ident(table),
+            names.join(", "),
+            placeholders.join(", ")
+        );
+
+        let query = row.values().fold(sqlx::query(&sql), bind_value);
+        query.execute(&mut *conn).await?;
+    }
+    Ok(())
+}
+
+fn bind_value<'q>(
+    query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    value: &Value,
+) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+    match value {
+        Value::Null => query.bind(None::<String>),
+        Value::Bool(b) => query.bind(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => query.bind(i),
+            None => query.bind(n.as_f64()),
+        },
+        Value::String(s) => query.bind(s.clone()),
+        other => query.bind(other.to_string()),
+    }
+}
+
+fn write_zip(path: &Path, files: &[(String, Vec<u8>)]) -> Result<(), AppError> {
+    let mut zip = ZipWriter::new(File::create(path)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, bytes) in files {
+        zip.start_file(name.as_str(), options).map_err(zip_error)?;
+        zip.write_all(bytes)?;
+    }
+    zip.finish().map_err(zip_error)?;
+    Ok(())
+}
+
+/// Read the manifest and every table, verifying checksums and row counts.
+fn read_archive(path: &Path) -> Result<ArchiveContents, AppError> {
+    let mut zip = ZipArchive::new(File::open(path)?).map_err(zip_error)?;
+
+    let manifest: ArchiveManifest = serde_json::from_slice(&read_entry(&mut zip, MANIFEST_FILE)?)
+        .map_err(|e| invalid(format!("unreadable manifest: {}", e)))?;
+    if manifest.format_version > ARCHIVE_FORMAT_VERSION {
+        return Err(AppError::Domain(format!(
+            "Archive format {} is newer than this build supports ({})",
+            manifest.format_version, ARCHIVE_FORMAT_VERSION
+        )));
+    }
+
+    let mut tables = BTreeMap::new();
+    for table in &manifest.tables {
+        let bytes = read_entry(&mut zip, &table.file)?;
+        if sha256_hex(&bytes) != table.sha256.to_ascii_lowercase() {
+            return Err(invalid(format!("checksum mismatch for {}", table.file)));
+        }
+        let rows: Rows = serde_json::from_slice(&bytes)
+            .map_err(|e| invalid(format!("unreadable {}: {}", table.file, e)))?;
+        if rows.len() != table.rows {
+            return Err(invalid(format!(
+                "{} has {} rows, manifest says {}",
+                table.file,
+                rows.len(),
+                table.rows
+            )));
+        }
+        if tables.insert(table.name.clone(), rows).is_some() {
+            return Err(invalid(format!("table {} is listed twice", table.name)));
+        }
+    }
+
+    Ok(ArchiveContents { manifest, tables })
+}
+
+fn read_entry(zip: &mut ZipArchive<File>, name: &str) -> Result<Vec<u8>, AppError> {
+    let entry = zip
+        .by_name(name)
+        .map_err(|_| invalid(format!("missing {}", name)))?;
+    if entry.size() > MAX_ENTRY_BYTES {
+        return Err(invalid(format!("{} is too large", name)));
+    }
+
+    let mut bytes = Vec::with_capacity(entry.size() as usize);
+    entry.take(MAX_ENTRY_BYTES).read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Bring an older archive up to the current format.
+fn upgrade(contents: &mut ArchiveContents, upgrades: &[(u32, ArchiveUpgrade)]) -> Result<(), AppError> {
+    while contents.manifest.format_version < ARCHIVE_FORMAT_VERSION {
+        let from = contents.manifest.format_version;
+        let (_, step) = upgrades
+            .iter()
+            .find(|(version, _)| *version == from)
+            .ok_or_else(|| AppError::Domain(format!("Archive format {} is no longer supported", from)))?;
+        step(contents)?;
+        contents.manifest.format_version = from + 1;
+        info!("Upgraded archive from format {} to {}", from, from + 1);
+    }
+    Ok(())
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+fn invalid(reason: String) -> AppError {
+    AppError::Domain(format!("Invalid archive: {}", reason))
+}
+
+fn zip_error(err: zip::result::ZipError) -> AppError {
+    invalid(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::infra::db::DbOptions;
+
+    fn manifest(format_version: u32, tables: Vec<ArchivedTable>) -> ArchiveManifest {
+        ArchiveManifest {
+            format_version,
+            app_version: "0.0.0".to_string(),
+            schema_version: 1,
+            created_at: "2025-01-01T00:00:00Z".to_string(),
+            tables,
+        }
+    }
+
+    /// Write an archive holding `rows` as the `users` table, described by `table`.
+    fn write_archive(dir: &Path, format_version: u32, rows: &Value, table: impl FnOnce(&[u8]) -> ArchivedTable) -> PathBuf {
+        let bytes = serde_json::to_vec(rows).unwrap();
+        let manifest = manifest(format_version, vec![table(&bytes)]);
+        let path = dir.join("archive.zip");
+        write_zip(
+            &path,
+            &[
+                (MANIFEST_FILE.to_string(), serde_json::to_vec(&manifest).unwrap()),
+                ("tables/users.json".to_string(), bytes),
+            ],
+        )
+        .unwrap();
+        path
+    }
+
+    fn users_table(bytes: &[u8], rows: usize) -> ArchivedTable {
+        ArchivedTable {
+            name: "users".to_string(),
+            file: "tables/users.json".to_string(),
+            rows,
+            sha256: sha256_hex(bytes),
+        }
+    }
+
+    fn rejection(result: Result<ArchiveContents, AppError>) -> String {
+        match result {
+            Err(AppError::Domain(reason)) => reason,
+            Err(other) => panic!("unexpected error {:?}", other),
+            Ok(_) => panic!("archive was accepted"),
+        }
+    }
+
+    #[test]
+    fn valid_archive_is_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let rows = json!([{ "id": "u1", "username": "alice" }]);
+        let path = write_archive(dir.path(), ARCHIVE_FORMAT_VERSION, &rows, |bytes| users_table(bytes, 1));
+
+        let contents = read_archive(&path).unwrap();
+        assert_eq!(contents.tables["users"][0]["username"], "alice");
+    }
+
+    #[test]
+    fn checksum_mismatch_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let rows = json!([{ "id": "u1" }]);
+        let path = write_archive(dir.path(), ARCHIVE_FORMAT_VERSION, &rows, |_| users_table(b"something else", 1));
+
+        assert!(rejection(read_archive(&path)).contains("checksum mismatch"));
+    }
+
+    #[test]
+    fn checksum_is_case_insensitive() {
+        let dir = tempfile::tempdir().unwrap();
+        let rows = json!([{ "id": "u1" }]);
+        let path = write_archive(dir.path(), ARCHIVE_FORMAT_VERSION, &rows, |bytes| ArchivedTable {
+            sha256: sha256_hex(bytes).to_ascii_uppercase(),
+            ..users_table(bytes, 1)
+        });
+
+        assert!(read_archive(&path).is_ok());
+    }
+
+    #[test]
+    fn row_count_mismatch_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let rows = json!([{ "id": "u1" }, { "id": "u2" }]);
+        let path = write_archive(dir.path(), ARCHIVE_FORMAT_VERSION, &rows, |bytes| users_table(bytes, 3));
+
+        assert!(rejection(read_archive(&path)).contains("has 2 rows, manifest says 3"));
+    }
+
+    #[test]
+    fn newer_format_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let rows = json!([]);
+        let path = write_archive(dir.path(), ARCHIVE_FORMAT_VERSION + 1, &rows, |bytes| users_table(bytes, 0));
+
+        assert!(rejection(read_archive(&path)).contains("newer than this build supports"));
+    }
+
+    #[test]
+    fn upgrades_run_in_order_up_to_the_current_format() {
+        fn rename_name_to_username(contents: &mut ArchiveContents) -> Result<(), AppError> {
+            for row in contents.tables.get_mut("users").into_iter().flatten() {
+                if let Some(name) = row.remove("name") {
+                    row.insert("username".to_string(), name);
+                }
+            }
+            Ok(())
+        }
+
+        let mut contents = ArchiveContents {
+            manifest: manifest(ARCHIVE_FORMAT_VERSION - 1, Vec::new()),
+            tables: BTreeMap::from([(
+                "users".to_string(),
+                vec![json!({ "id": "u1", "name": "alice" }).as_object().unwrap().clone()],
+            )]),
+        };
+        upgrade(&mut contents, &[(ARCHIVE_FORMAT_VERSION - 1, rename_name_to_username)]).unwrap();
+
+        assert_eq!(contents.manifest.format_version, ARCHIVE_FORMAT_VERSION);
+        assert_eq!(contents.tables["users"][0]["username"], "alice");
+        assert!(!contents.tables["users"][0].contains_key("name"));
+    }
+
+    #[test]
+    fn missing_upgrade_step_is_rejected() {
+        let mut contents = ArchiveContents {
+            manifest: manifest(ARCHIVE_FORMAT_VERSION - 1, Vec::new()),
+            tables: BTreeMap::new(),
+        };
+        let result = upgrade(&mut contents, &[]);
+        assert!(matches!(result, Err(AppError::Domain(reason)) if reason.contains("no longer supported")));
+    }
+
+    #[tokio::test]
+    async fn import_replaces_data_but_not_the_download_queue() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(Database::open(dir.path().join("app.db"), DbOptions::default()).await.unwrap());
+        let store = SqliteArchiveStore::new(db.clone());
+        let execute = |sql: &'static str| {
+            let db = db.clone();
+            async move { sqlx::query(sql).execute(&*db.pool().await).await.unwrap() }
+        };
+
+        execute("INSERT INTO users (id, username) VALUES ('u1', 'alice')").await;
+        let archive = dir.path().join("export.zip");
+        let manifest = store.export(&archive.to_string_lossy()).await.unwrap();
+        assert!(manifest.tables.iter().all(|t| !EXCLUDED_TABLES.contains(&t.name.as_str())));
+
+        execute("INSERT INTO users (id, username) VALUES ('u2', 'bob')").await;
+        execute("INSERT INTO downloads (id, url, path) VALUES ('d1', 'https://example.com/a', '/tmp/a')").await;
+        store.import(&archive.to_string_lossy()).await.unwrap();
+
+        let pool = db.pool().await;
+        let users: Vec<String> = sqlx::query_scalar("SELECT username FROM users").fetch_all(&*pool).await.unwrap();
+        assert_eq!(users, ["alice"]);
+        let downloads: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM downloads").fetch_one(&*pool).await.unwrap();
+        assert_eq!(downloads, 1);
+    }
+}
@@ -0,0 +1,302 @@
+//! Platform login items for "Launch on startup":
+//! - Linux: XDG autostart `.desktop` entry in `$XDG_CONFIG_HOME/autostart`
+//! - macOS: LaunchAgent plist in `~/Library/LaunchAgents`
+//! - Windows: `HKCU\Software\Microsoft\Windows\CurrentVersion\Run` value
+
+use std::path::PathBuf;
+use tauri::{AppHandle, Runtime};
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+use tauri::Manager;
+
+use crate::domain::autostart::IAutostartManager;
+use crate::error::AppError;
+
+#[allow(dead_code)] // Not every platform uses every field
+pub struct OsAutostartManager {
+    /// App identifier, used as entry file name / registry value name
+    identifier: String,
+    product_name: String,
+    exe: PathBuf,
+    /// Entry file (Linux / macOS)
+    entry_path: Option<PathBuf>,
+}
+
+impl OsAutostartManager {
+    pub fn new<R: Runtime>(app: &AppHandle<R>) -> Result<Self, AppError> {
+        let config = app.config();
+        let identifier = config.identifier.clone();
+        let product_name = config.product_name.clone().unwrap_or_else(|| identifier.clone());
+        let exe = std::env::current_exe()?;
+        let entry_path = entry_path(app, &identifier)?;
+
+        Ok(Self { identifier, product_name, exe, entry_path })
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn entry_path<R: Runtime>(app: &AppHandle<R>, identifier: &str) -> Result<Option<PathBuf>, AppError> {
+    let dir = app.path().config_dir()?.join("autostart");
+    Ok(Some(dir.join(format!("{}.desktop", identifier))))
+}
+
+#[cfg(target_os = "macos")]
+fn entry_path<R: Runtime>(app: &AppHandle<R>, identifier: &str) -> Result<Option<PathBuf>, AppError> {
+    let dir = app.path().home_dir()?.join("Library").join("LaunchAgents");
+    Ok(Some(dir.join(format!("{}.plist", identifier))))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn entry_path<R: Runtime>(_app: &AppHandle<R>, _identifier: &str) -> Result<Option<PathBuf>, AppError> {
+    Ok(None)
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+impl OsAutostartManager {
+    fn entry_path(&self) -> Result<&PathBuf, AppError> {
+        self.entry_path
+            .as_ref()
+            .ok_or_else(|| AppError::Io("Autostart entry path is unavailable".to_string()))
+    }
+
+    fn write_entry(&self, content: String) -> Result<(), AppError> {
+        let path = self.entry_path()?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl IAutostartManager for OsAutostartManager {
+    fn is_enabled(&self) -> Result<bool, AppError> {
+        let path = self.entry_path()?;
+        if !path.exists() {
+            return Ok(false);
+        }
+        // Desktop environments honor these keys to disable an existing entry
+        let content = std::fs::read_to_string(path)?;
+        let disabled = content.lines().any(|line| {
+            let line = line.trim();
+            line == "Hidden=true" || line == "X-GNOME-Autostart-enabled=false"
+        });
+        Ok(!disabled)
+    }
+
+    fn enable(&self) -> Result<(), AppError> {
+        self.write_entry(format!(
+            "[Desktop Entry]\nType=Application\nName={}\nExec={}\nTerminal=false\nX-GNOME-Autostart-enabled=true\n",
+            desktop_string(&self.product_name),
+            desktop_exec(&self.exe.to_string_lossy())
+        ))
+    }
+
+    fn disable(&self) -> Result<(), AppError> {
+        remove_if_exists(self.entry_path()?)
+    }
+
+    fn location(&self) -> Option<String> {
+        self.entry_path.as_ref().map(|p| p.display().to_string())
+    }
+}
+
+/// `Exec` value running `program` without arguments: quoted, with `"`, `` ` ``, `$`
+/// and `\` escaped inside the quotes and `%` doubled so it is not a field code.
+/// The quoting rule applies after the string escapes, hence the second pass.
+#[cfg(target_os = "linux")]
+fn desktop_exec(program: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in program.chars() {
+        match c {
+            '"' | '`' | '$' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '%' => quoted.push_str("%%"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    desktop_string(&quoted)
+}
+
+/// Escape a value of type string (or localestring) in a `.desktop` file.
+#[cfg(target_os = "linux")]
+fn desktop_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(target_os = "macos")]
+impl IAutostartManager for OsAutostartManager {
+    fn is_enabled(&self) -> Result<bool, AppError> {
+        Ok(self.entry_path()?.exists())
+    }
+
+    fn enable(&self) -> Result<(), AppError> {
+        self.write_entry(format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+</dict>
+</plist>
+"#,
+            self.identifier,
+            self.exe.display()
+        ))
+    }
+
+    fn disable(&self) -> Result<(), AppError> {
+        remove_if_exists(self.entry_path()?)
+    }
+
+    fn location(&self) -> Option<String> {
+        self.entry_path.as_ref().map(|p| p.display().to_string())
+    }
+}
+
+#[cfg(target_os = "windows")]
+const RUN_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+
+/// Process creation flag that keeps `reg.exe` from flashing a console window
+#[cfg(target_os = "windows")]
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+#[cfg(target_os = "windows")]
+impl IAutostartManager for OsAutostartManager {
+    fn is_enabled(&self) -> Result<bool, AppError> {
+        let status = reg(&["query", RUN_KEY, "/v", &self.identifier])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()?;
+        Ok(status.success())
+    }
+
+    fn enable(&self) -> Result<(), AppError> {
+        let exe = format!("\"{}\"", self.exe.display());
+        run_reg(&["add", RUN_KEY, "/v", &self.identifier, "/t", "REG_SZ", "/d", &exe, "/f"])
+    }
+
+    fn disable(&self) -> Result<(), AppError> {
+        if !self.is_enabled()? {
+            return Ok(());
+        }
+        run_reg(&["delete", RUN_KEY, "/v", &self.identifier, "/f"])
+    }
+
+    fn location(&self) -> Option<String> {
+        Some(format!(r"{}\{} ({})", RUN_KEY, self.identifier, self.product_name))
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn run_reg(args: &[&str]) -> Result<(), AppError> {
+    let status = reg(args)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()?;
+    if !status.success() {
+        return Err(AppError::Io(format!("reg {} failed (status: {:?})", args[0], status.code())));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn reg(args: &[&str]) -> std::process::Command {
+    use std::os::windows::process::CommandExt;
+
+    let mut command = std::process::Command::new("reg");
+    command.args(args).creation_flags(CREATE_NO_WINDOW);
+    command
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+impl IAutostartManager for OsAutostartManager {
+    fn is_enabled(&self) -> Result<bool, AppError> {
+        Ok(false)
+    }
+
+    fn enable(&self) -> Result<(), AppError> {
+        Err(AppError::Unknown("Launch on startup is not supported on this platform".to_string()))
+    }
+
+    fn disable(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    fn location(&self) -> Option<String> {
+        None
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn remove_if_exists(path: &std::path::Path) -> Result<(), AppError> {
+    match std::fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_paths_are_only_quoted() {
+        assert_eq!(desktop_exec("/opt/My App/app"), r#""/opt/My App/app""#);
+    }
+
+    #[test]
+    fn exec_escapes_shell_characters_and_field_codes() {
+        assert_eq!(desktop_exec(r#"/opt/a"b`c$d"#), r#""/opt/a\\"b\\`c\\$d""#);
+        assert_eq!(desktop_exec("/opt/100%/app"), r#""/opt/100%%/app""#);
+        // Quoting turns `\` into `\\`, the string escapes double both
+        assert_eq!(desktop_exec(r"/opt/a\b"), r#""/opt/a\\\\b""#);
+    }
+
+    #[test]
+    fn names_cannot_add_lines() {
+        assert_eq!(desktop_string("My App\nExec=evil"), r"My App\nExec=evil");
+        assert_eq!(desktop_string(r"C:\x"), r"C:\\x");
+    }
+
+    #[test]
+    fn enable_writes_an_escaped_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = OsAutostartManager {
+            identifier: "com.example.app".to_string(),
+            product_name: "App\nHidden=true".to_string(),
+            exe: PathBuf::from("/opt/$HOME/app"),
+            entry_path: Some(dir.path().join("autostart").join("com.example.app.desktop")),
+        };
+
+        manager.enable().unwrap();
+        assert!(manager.is_enabled().unwrap());
+        let entry = std::fs::read_to_string(manager.entry_path().unwrap()).unwrap();
+        assert!(entry.contains(r"Name=App\nHidden=true"));
+        assert!(entry.contains(r#"Exec="/opt/\\$HOME/app""#));
+
+        manager.disable().unwrap();
+        assert!(!manager.is_enabled().unwrap());
+    }
+}
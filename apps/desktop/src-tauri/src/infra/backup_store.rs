@@ -0,0 +1,260 @@
+//! Database backups in `app_data_dir/backups`.
+//!
+//! Backups are taken online with `VACUUM INTO`, which writes a consistent,
+//! compacted copy without blocking readers.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use async_trait::async_trait;
+use time::format_description::well_known::Rfc3339;
+use time::macros::format_description;
+use time::OffsetDateTime;
+use sqlx::SqlitePool;
+use tokio::sync::Mutex;
+use tracing::{error, info, warn};
+
+use crate::domain::backups::{BackupInfo, IBackupStore};
+use crate::error::AppError;
+use crate::infra::db::{self, Database};
+
+const BACKUP_PREFIX: &str = "db-";
+/// Snapshots taken before a restore; rotation keeps them
+const PRE_RESTORE_PREFIX: &str = "db-pre-restore-";
+const BACKUP_EXTENSION: &str = "sqlite";
+
+pub struct SqliteBackupStore {
+    db: Arc<Database>,
+    dir: PathBuf,
+    /// Serializes backup/restore so a restore never races a running backup
+    lock: Mutex<()>,
+}
+
+impl SqliteBackupStore {
+    pub fn new(db: Arc<Database>, dir: PathBuf) -> Self {
+        Self { db, dir, lock: Mutex::new(()) }
+    }
+
+    /// Copy the database through `pool` into a new backup file named `<prefix><timestamp>`.
+    async fn snapshot(&self, pool: &SqlitePool, prefix: &str) -> Result<BackupInfo, AppError> {
+        fs::create_dir_all(&self.dir)?;
+
+        let stamp = OffsetDateTime::now_utc()
+            .format(format_description!("[year][month][day]-[hour][minute][second]"))
+            .map_err(|e| AppError::Unknown(e.to_string()))?;
+
+        // Avoid clobbering a backup taken within the same second
+        let mut path = self.dir.join(format!("{}{}.{}", prefix, stamp, BACKUP_EXTENSION));
+        let mut suffix = 1;
+        while path.exists() {
+            path = self.dir.join(format!("{}{}-{}.{}", prefix, stamp, suffix, BACKUP_EXTENSION));
+            suffix += 1;
+        }
+
+        sqlx::query("VACUUM INTO ?")
+            .bind(path.to_string_lossy().to_string())
+            .execute(pool)
+            .await?;
+
+        info!("Database backup created: {:?}", path);
+        backup_info(&path)
+    }
+}
+
+#[async_trait]
+impl IBackupStore for SqliteBackupStore {
+    async fn create(&self) -> Result<BackupInfo, AppError> {
+        let _guard = self.lock.lock().await;
+        let pool = self.db.pool().await;
+        self.snapshot(&pool, BACKUP_PREFIX).await
+    }
+
+    async fn list(&self) -> Result<Vec<BackupInfo>, AppError> {
+        list_backups(&self.dir)
+    }
+
+    async fn delete(&self, name: &str) -> Result<(), AppError> {
+        let path = resolve_backup(&self.dir, name)?;
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    async fn restore(&self, name: &str) -> Result<(), AppError> {
+        let _guard = self.lock.lock().await;
+        let backup = resolve_backup(&self.dir, name)?;
+
+        // Let running queries finish and hold off new ones until the new pool is in place,
+        // so no caller hits a closed pool or writes to the file being replaced
+        let db = self.db.exclusive().await;
+
+        // Keep the current state in case the restore was a mistake
+        let safety = self.snapshot(&db.pool(), PRE_RESTORE_PREFIX).await?;
+        info!("Restoring {} (current database saved as {})", name, safety.name);
+
+        let db_path = self.db.path().to_path_buf();
+        db::close_pool(&db.pool()).await;
+
+        // From here on the pool is closed: every error path must reopen a database
+        match replace_file(&backup, &db_path, self.db.options()).await {
+            Ok(pool) => {
+                db.replace_pool(pool);
+                info!("Database restored from {}", name);
+                Ok(())
+            }
+            Err(e) => {
+                // Put the previous database back so the app keeps working
+                warn!("Restoring {} failed, rolling back: {:?}", name, e);
+                match replace_file(&self.dir.join(&safety.name), &db_path, self.db.options()).await {
+                    Ok(pool) => {
+                        db.replace_pool(pool);
+                    }
+                    Err(rollback) => {
+                        error!("Rolling back to {} failed, the database is unavailable: {:?}", safety.name, rollback);
+                    }
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Copy `source` over the closed database at `db_path` and open it.
+async fn replace_file(source: &Path, db_path: &Path, options: &db::DbOptions) -> Result<SqlitePool, AppError> {
+    // WAL/SHM files belong to the old database and must not be replayed onto the new one
+    for suffix in ["-wal", "-shm"] {
+        let sidecar = PathBuf::from(format!("{}{}", db_path.to_string_lossy(), suffix));
+        if sidecar.exists() {
+            fs::remove_file(&sidecar)?;
+        }
+    }
+    fs::copy(source, db_path)?;
+    db::connect(db_path, options).await
+}
+
+/// Backups in `dir`, newest first. Works without an open database (recovery mode).
+pub fn list_backups(dir: &Path) -> Result<Vec<BackupInfo>, AppError> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_backup = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.starts_with(BACKUP_PREFIX))
+            && path.extension().is_some_and(|ext| ext == BACKUP_EXTENSION);
+        if is_backup {
+            backups.push(backup_info(&path)?);
+        }
+    }
+
+    // RFC 3339 timestamps sort chronologically; names break ties
+    backups.sort_by(|a, b| b.created_at.cmp(&a.created_at).then_with(|| b.name.cmp(&a.name)));
+    Ok(backups)
+}
+
+/// Resolve a backup name to a path in `dir`, rejecting anything outside it.
+pub fn resolve_backup(dir: &Path, name: &str) -> Result<PathBuf, AppError> {
+    let valid = name.starts_with(BACKUP_PREFIX)
+        && Path::new(name).extension().is_some_and(|ext| ext == BACKUP_EXTENSION)
+        && !name.contains(['/', '\\'])
+        && !name.contains("..");
+    if !valid {
+        return Err(AppError::Domain(format!("Invalid backup name: {}", name)));
+    }
+
+    let path = dir.join(name);
+    if !path.is_file() {
+        return Err(AppError::NotFound(format!("Backup {} not found", name)));
+    }
+    Ok(path)
+}
+
+fn backup_info(path: &Path) -> Result<BackupInfo, AppError> {
+    let metadata = fs::metadata(path)?;
+    let created_at = metadata
+        .modified()
+        .map(OffsetDateTime::from)
+        .unwrap_or_else(|_| OffsetDateTime::now_utc())
+        .format(&Rfc3339)
+        .map_err(|e| AppError::Unknown(e.to_string()))?;
+
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    Ok(BackupInfo {
+        pre_restore: name.starts_with(PRE_RESTORE_PREFIX),
+        name,
+        size_bytes: metadata.len(),
+        created_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::db::DbOptions;
+
+    async fn setup() -> (tempfile::TempDir, Arc<Database>, SqliteBackupStore) {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(Database::open(dir.path().join("app.db"), DbOptions::default()).await.unwrap());
+        let store = SqliteBackupStore::new(db.clone(), dir.path().join("backups"));
+        (dir, db, store)
+    }
+
+    async fn execute(db: &Database, sql: &str) {
+        sqlx::query(sql).execute(&*db.pool().await).await.unwrap();
+    }
+
+    async fn usernames(db: &Database) -> Vec<String> {
+        sqlx::query_scalar("SELECT username FROM users ORDER BY username")
+            .fetch_all(&*db.pool().await)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn restore_brings_back_the_backup() {
+        let (_dir, db, store) = setup().await;
+        execute(&db, "INSERT INTO users (id, username) VALUES ('u1', 'alice')").await;
+        let backup = store.create().await.unwrap();
+        execute(&db, "INSERT INTO users (id, username) VALUES ('u2', 'bob')").await;
+
+        store.restore(&backup.name).await.unwrap();
+        assert_eq!(usernames(&db).await, ["alice"]);
+
+        // The state before the restore was kept, marked so rotation leaves it alone
+        let backups = store.list().await.unwrap();
+        let safety = backups.iter().find(|b| b.pre_restore).expect("no pre-restore snapshot");
+        assert!(safety.name.starts_with(PRE_RESTORE_PREFIX));
+        assert!(!backups.iter().find(|b| b.name == backup.name).unwrap().pre_restore);
+        store.restore(&safety.name).await.unwrap();
+        assert_eq!(usernames(&db).await, ["alice", "bob"]);
+    }
+
+    #[tokio::test]
+    async fn failed_restore_rolls_back() {
+        let (dir, db, store) = setup().await;
+        execute(&db, "INSERT INTO users (id, username) VALUES ('u1', 'alice')").await;
+        fs::create_dir_all(dir.path().join("backups")).unwrap();
+        fs::write(dir.path().join("backups/db-broken.sqlite"), vec![0xab; 4096]).unwrap();
+
+        assert!(store.restore("db-broken.sqlite").await.is_err());
+        // The previous database is open again and writable
+        assert_eq!(usernames(&db).await, ["alice"]);
+        execute(&db, "INSERT INTO users (id, username) VALUES ('u2', 'bob')").await;
+        assert_eq!(usernames(&db).await, ["alice", "bob"]);
+    }
+
+    #[tokio::test]
+    async fn names_outside_the_directory_are_rejected() {
+        let (_dir, _db, store) = setup().await;
+        for name in ["../app.db", "db-../../app.sqlite", "other.sqlite", "db-x.txt"] {
+            assert!(matches!(store.restore(name).await, Err(AppError::Domain(_))), "{}", name);
+        }
+        assert!(matches!(store.restore("db-missing.sqlite").await, Err(AppError::NotFound(_))));
+    }
+}
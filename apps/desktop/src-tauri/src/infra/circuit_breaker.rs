@@ -0,0 +1,272 @@
+//! Per-host circuit breakers for the HTTP client.
+//!
+//! A host whose requests keep failing gets its circuit opened: requests to it
+//! are rejected without being sent until the open period has passed. Then a
+//! single trial request is let through, which closes the circuit again or
+//! reopens it. State changes are published as `CircuitBreakerChanged` events.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+use crate::domain::events::{DomainEvent, IEventPublisher};
+use crate::domain::http::{CircuitBreakerPolicy, CircuitBreakerStatus, CircuitState};
+use crate::error::AppError;
+
+#[derive(Default)]
+struct Circuit {
+    consecutive_failures: u32,
+    /// Set while the circuit is open or half-open
+    open_until: Option<Instant>,
+    /// When the trial request of a half-open circuit was let through
+    trial_started: Option<Instant>,
+}
+
+impl Circuit {
+    fn state(&self) -> CircuitState {
+        match (self.open_until, self.trial_started) {
+            (None, _) => CircuitState::Closed,
+            (Some(_), Some(_)) => CircuitState::HalfOpen,
+            (Some(_), None) => CircuitState::Open,
+        }
+    }
+}
+
+pub struct CircuitBreakers {
+    /// Hosts that failed since their last success
+    circuits: Mutex<HashMap<String, Circuit>>,
+    publisher: Arc<dyn IEventPublisher>,
+}
+
+impl CircuitBreakers {
+    pub fn new(publisher: Arc<dyn IEventPublisher>) -> Self {
+        Self {
+            circuits: Mutex::new(HashMap::new()),
+            publisher,
+        }
+    }
+
+    /// Check whether a request to `host` may be sent. Fails if its circuit is open.
+    pub fn acquire(&self, host: &str, policy: &CircuitBreakerPolicy) -> Result<(), AppError> {
+        if policy.failure_threshold == 0 {
+            return Ok(());
+        }
+
+        {
+            let mut circuits = self.circuits.lock().unwrap_or_else(|e| e.into_inner());
+            let Some(circuit) = circuits.get_mut(host) else { return Ok(()) };
+            let Some(open_until) = circuit.open_until else { return Ok(()) };
+
+            let now = Instant::now();
+            if now < open_until {
+                return Err(open_error(host, open_until - now));
+            }
+            // A trial whose caller went away never reports back; give up on it after another period
+            let open_for = Duration::from_secs(policy.open_secs);
+            if circuit.trial_started.is_some_and(|started| now < started + open_for) {
+                return Err(AppError::Io(format!(
+                    "Circuit breaker for {} is half-open, waiting for the trial request",
+                    host
+                )));
+            }
+            circuit.trial_started = Some(now);
+        }
+
+        info!("Circuit breaker for {} is half-open, sending a trial request", host);
+        self.publish(host, CircuitState::HalfOpen);
+        Ok(())
+    }
+
+    pub fn record_success(&self, host: &str) {
+        let previous = self
+            .circuits
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(host)
+            .map(|circuit| circuit.state());
+
+        if matches!(previous, Some(CircuitState::Open | CircuitState::HalfOpen)) {
+            info!("Circuit breaker for {} closed", host);
+            self.publish(host, CircuitState::Closed);
+        }
+    }
+
+    pub fn record_failure(&self, host: &str, policy: &CircuitBreakerPolicy) {
+        if policy.failure_threshold == 0 {
+            return;
+        }
+
+        let opened = {
+            let mut circuits = self.circuits.lock().unwrap_or_else(|e| e.into_inner());
+            let circuit = circuits.entry(host.to_string()).or_default();
+            circuit.consecutive_failures += 1;
+
+            // A failed trial reopens the circuit right away
+            let trips = match circuit.state() {
+                CircuitState::Closed => circuit.consecutive_failures >= policy.failure_threshold,
+                CircuitState::HalfOpen => true,
+                CircuitState::Open => false,
+            };
+            if trips {
+                circuit.open_until = Some(Instant::now() + Duration::from_secs(policy.open_secs));
+                circuit.trial_started = None;
+            }
+            trips.then_some(circuit.consecutive_failures)
+        };
+
+        if let Some(failures) = opened {
+            warn!("Circuit breaker for {} opened after {} consecutive failures", host, failures);
+            self.publish(host, CircuitState::Open);
+        }
+    }
+
+    /// Hosts that failed since their last success, most failures first.
+    pub fn statuses(&self) -> Vec<CircuitBreakerStatus> {
+        let now = Instant::now();
+        let circuits = self.circuits.lock().unwrap_or_else(|e| e.into_inner());
+        let mut statuses: Vec<CircuitBreakerStatus> = circuits
+            .iter()
+            .map(|(host, circuit)| CircuitBreakerStatus {
+                host: host.clone(),
+                state: circuit.state(),
+                consecutive_failures: circuit.consecutive_failures,
+                retry_in_ms: circuit
+                    .open_until
+                    .filter(|_| circuit.trial_started.is_none())
+                    .map(|until| until.saturating_duration_since(now).as_millis() as u64),
+            })
+            .collect();
+        statuses.sort_by_key(|status| Reverse(status.consecutive_failures));
+        statuses
+    }
+
+    fn publish(&self, host: &str, state: CircuitState) {
+        self.publisher.publish(DomainEvent::CircuitBreakerChanged { host: host.to_string(), state });
+    }
+}
+
+fn open_error(host: &str, remaining: Duration) -> AppError {
+    AppError::Io(format!(
+        "Circuit breaker for {} is open, retry in {}s",
+        host,
+        remaining.as_secs_f64().ceil() as u64
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::event_publisher::RecordingEventPublisher;
+
+    const HOST: &str = "api.example.com";
+
+    fn breakers() -> (CircuitBreakers, Arc<RecordingEventPublisher>) {
+        let publisher = Arc::new(RecordingEventPublisher::new());
+        (CircuitBreakers::new(publisher.clone()), publisher)
+    }
+
+    /// States published for `HOST`, oldest first
+    fn published(publisher: &RecordingEventPublisher) -> Vec<CircuitState> {
+        publisher
+            .take()
+            .into_iter()
+            .filter_map(|event| match event {
+                DomainEvent::CircuitBreakerChanged { host, state } if host == HOST => Some(state),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn state(breakers: &CircuitBreakers) -> Option<CircuitState> {
+        breakers.statuses().into_iter().find(|s| s.host == HOST).map(|s| s.state)
+    }
+
+    #[test]
+    fn opens_after_threshold_and_rejects() {
+        let (breakers, publisher) = breakers();
+        let policy = CircuitBreakerPolicy { failure_threshold: 3, open_secs: 60 };
+
+        for _ in 0..2 {
+            breakers.record_failure(HOST, &policy);
+            assert!(breakers.acquire(HOST, &policy).is_ok());
+        }
+        assert_eq!(state(&breakers), Some(CircuitState::Closed));
+        assert!(published(&publisher).is_empty());
+
+        breakers.record_failure(HOST, &policy);
+        assert_eq!(state(&breakers), Some(CircuitState::Open));
+        assert_eq!(published(&publisher), vec![CircuitState::Open]);
+        assert!(matches!(breakers.acquire(HOST, &policy), Err(AppError::Io(_))));
+        // Other hosts are unaffected
+        assert!(breakers.acquire("other.example.com", &policy).is_ok());
+
+        let status = &breakers.statuses()[0];
+        assert_eq!(status.consecutive_failures, 3);
+        assert!(status.retry_in_ms.is_some_and(|ms| ms > 0 && ms <= 60_000));
+    }
+
+    #[test]
+    fn success_resets_the_failure_count() {
+        let (breakers, publisher) = breakers();
+        let policy = CircuitBreakerPolicy { failure_threshold: 2, open_secs: 60 };
+
+        breakers.record_failure(HOST, &policy);
+        breakers.record_success(HOST);
+        assert_eq!(state(&breakers), None);
+        breakers.record_failure(HOST, &policy);
+        assert_eq!(state(&breakers), Some(CircuitState::Closed));
+        // Closing a circuit that never opened publishes nothing
+        assert!(published(&publisher).is_empty());
+    }
+
+    #[test]
+    fn half_open_lets_one_trial_through() {
+        let (breakers, publisher) = breakers();
+        let policy = CircuitBreakerPolicy { failure_threshold: 1, open_secs: 0 };
+        breakers.record_failure(HOST, &policy);
+
+        // The open period has passed: the trial is let through
+        assert!(breakers.acquire(HOST, &policy).is_ok());
+        assert_eq!(state(&breakers), Some(CircuitState::HalfOpen));
+        assert_eq!(published(&publisher), vec![CircuitState::Open, CircuitState::HalfOpen]);
+
+        // A second request waits for the trial while it is outstanding
+        let waiting = CircuitBreakerPolicy { open_secs: 60, ..policy.clone() };
+        assert!(breakers.acquire(HOST, &waiting).is_err());
+
+        breakers.record_success(HOST);
+        assert_eq!(state(&breakers), None);
+        assert_eq!(published(&publisher), vec![CircuitState::Closed]);
+    }
+
+    #[test]
+    fn failed_trial_reopens() {
+        let (breakers, publisher) = breakers();
+        let policy = CircuitBreakerPolicy { failure_threshold: 3, open_secs: 0 };
+        for _ in 0..3 {
+            breakers.record_failure(HOST, &policy);
+        }
+        assert!(breakers.acquire(HOST, &policy).is_ok());
+
+        // One failure is enough once half-open
+        breakers.record_failure(HOST, &policy);
+        assert_eq!(state(&breakers), Some(CircuitState::Open));
+        assert_eq!(
+            published(&publisher),
+            vec![CircuitState::Open, CircuitState::HalfOpen, CircuitState::Open]
+        );
+    }
+
+    #[test]
+    fn zero_threshold_disables_the_breaker() {
+        let (breakers, publisher) = breakers();
+        let policy = CircuitBreakerPolicy { failure_threshold: 0, open_secs: 60 };
+        for _ in 0..10 {
+            breakers.record_failure(HOST, &policy);
+        }
+        assert!(breakers.acquire(HOST, &policy).is_ok());
+        assert!(breakers.statuses().is_empty());
+        assert!(published(&publisher).is_empty());
+    }
+}
@@ -0,0 +1,207 @@
+//! Loads the non-database configuration layers:
+//! bundled defaults < system config file < user config file < `APP_*` env vars < CLI.
+//!
+//! Config files are JSON:
+//! ```json
+//! { "settings": { "theme_mode": "dark" }, "locked": ["theme_mode"] }
+//! ```
+//! `locked` is only honored in the system-wide file, so administrators can pin values:
+//! no layer above it (user file, env, CLI or the database) overrides a locked key.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::Deserialize;
+use tauri::{AppHandle, Manager, Runtime};
+use tracing::{info, warn};
+
+use crate::domain::config::{ConfigLayer, StaticConfig};
+
+/// Defaults compiled into the binary.
+const BUNDLED_DEFAULTS: &str = include_str!("../../config/defaults.json");
+
+const CONFIG_FILE_NAME: &str = "config.json";
+const ENV_PREFIX: &str = "APP_";
+const CLI_FLAG: &str = "--config";
+
+#[derive(Debug, Default, Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    settings: HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    locked: Vec<String>,
+}
+
+impl ConfigFile {
+    /// Settings are stored as strings in the database, so non-string JSON
+    /// values (numbers, booleans) are kept in their JSON text form.
+    fn string_settings(self) -> HashMap<String, String> {
+        self.settings
+            .into_iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    serde_json::Value::String(s) => s,
+                    other => other.to_string(),
+                };
+                (key, value)
+            })
+            .collect()
+    }
+}
+
+/// Build the static configuration from every layer below the database.
+/// Missing files are skipped; malformed files are logged and skipped.
+pub fn load<R: Runtime>(app: &AppHandle<R>) -> StaticConfig {
+    let mut config = StaticConfig::default();
+
+    match serde_json::from_str::<ConfigFile>(BUNDLED_DEFAULTS) {
+        Ok(defaults) => config.apply(ConfigLayer::Default, defaults.string_settings()),
+        Err(e) => warn!("Invalid bundled config defaults: {}", e),
+    }
+
+    if let Some(path) = system_config_path(&app.config().identifier) {
+        if let Some(file) = read_config_file(&path) {
+            for key in &file.locked {
+                config.lock(key.clone());
+            }
+            config.apply(ConfigLayer::SystemFile, file.string_settings());
+        }
+    }
+
+    match app.path().app_config_dir() {
+        Ok(dir) => {
+            if let Some(file) = read_config_file(&dir.join(CONFIG_FILE_NAME)) {
+                if !file.locked.is_empty() {
+                    warn!("Ignoring `locked` in user config file; only the system config file can lock keys");
+                }
+                config.apply(ConfigLayer::UserFile, file.string_settings());
+            }
+        }
+        Err(e) => warn!("Failed to resolve app config dir: {}", e),
+    }
+
+    config.apply(ConfigLayer::Env, env_overrides(std::env::vars()));
+    config.apply(ConfigLayer::Cli, cli_overrides(std::env::args().skip(1)));
+
+    config
+}
+
+/// System-wide config file location, shared by every user of the machine.
+fn system_config_path(identifier: &str) -> Option<PathBuf> {
+    #[cfg(target_os = "linux")]
+    let base = Some(PathBuf::from("/etc"));
+    #[cfg(target_os = "macos")]
+    let base = Some(PathBuf::from("/Library/Application Support"));
+    #[cfg(target_os = "windows")]
+    let base = std::env::var_os("ProgramData").map(PathBuf::from);
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    let base: Option<PathBuf> = None;
+
+    base.map(|dir| dir.join(identifier).join(CONFIG_FILE_NAME))
+}
+
+fn read_config_file(path: &Path) -> Option<ConfigFile> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+        Err(e) => {
+            warn!("Failed to read config file {:?}: {}", path, e);
+            return None;
+        }
+    };
+
+    match serde_json::from_str(&content) {
+        Ok(file) => {
+            info!("Loaded config file: {:?}", path);
+            Some(file)
+        }
+        Err(e) => {
+            warn!("Ignoring malformed config file {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// `APP_THEME_MODE=dark` -> `theme_mode = "dark"`
+fn env_overrides(vars: impl Iterator<Item = (String, String)>) -> HashMap<String, String> {
+    vars.filter_map(|(name, value)| {
+        let key = name.strip_prefix(ENV_PREFIX)?;
+        (!key.is_empty()).then(|| (key.to_lowercase(), value))
+    })
+    .collect()
+}
+
+/// `--config theme_mode=dark` or `--config=theme_mode=dark`
+fn cli_overrides(mut args: impl Iterator<Item = String>) -> HashMap<String, String> {
+    let mut overrides = HashMap::new();
+
+    while let Some(arg) = args.next() {
+        let pair = if arg == CLI_FLAG {
+            match args.next() {
+                Some(pair) => pair,
+                None => break,
+            }
+        } else if let Some(pair) = arg.strip_prefix(CLI_FLAG).and_then(|rest| rest.strip_prefix('=')) {
+            pair.to_string()
+        } else {
+            continue;
+        };
+
+        match pair.split_once('=') {
+            Some((key, value)) if !key.is_empty() => {
+                overrides.insert(key.to_string(), value.to_string());
+            }
+            _ => warn!("Ignoring malformed {} argument: {}", CLI_FLAG, pair),
+        }
+    }
+
+    overrides
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> impl Iterator<Item = String> {
+        args.iter().map(|a| a.to_string()).collect::<Vec<_>>().into_iter()
+    }
+
+    #[test]
+    fn env_vars_with_the_prefix_become_lowercase_keys() {
+        let vars = [("APP_THEME_MODE", "dark"), ("APP_", "empty"), ("HOME", "/root"), ("app_lower", "x")]
+            .map(|(k, v)| (k.to_string(), v.to_string()));
+
+        let overrides = env_overrides(vars.into_iter());
+
+        assert_eq!(overrides, HashMap::from([("theme_mode".to_string(), "dark".to_string())]));
+    }
+
+    #[test]
+    fn cli_accepts_both_flag_forms() {
+        let overrides = cli_overrides(args(&[
+            "--verbose",
+            "--config",
+            "theme_mode=dark",
+            "--config=language=de",
+            "--config=url=https://a.io/?x=1",
+        ]));
+
+        assert_eq!(overrides.len(), 3);
+        assert_eq!(overrides["theme_mode"], "dark");
+        assert_eq!(overrides["language"], "de");
+        // Only the first `=` separates key and value
+        assert_eq!(overrides["url"], "https://a.io/?x=1");
+    }
+
+    #[test]
+    fn malformed_cli_pairs_are_skipped() {
+        let overrides = cli_overrides(args(&["--config", "no_value", "--config==x", "--config"]));
+        assert!(overrides.is_empty());
+    }
+
+    #[test]
+    fn later_cli_values_win() {
+        let overrides = cli_overrides(args(&["--config", "theme_mode=dark", "--config", "theme_mode=light"]));
+        assert_eq!(overrides["theme_mode"], "light");
+    }
+}
@@ -0,0 +1,251 @@
+//! Cookies of the HTTP client, kept in `app_data_dir/cookies.json`.
+//!
+//! Session cookies are kept too: the app is the "browser" here, and APIs that
+//! log in with a session cookie would otherwise need a new login on every
+//! launch. `clear_cookies` logs out. The file is rewritten in the background
+//! shortly after responses set cookies, through a temporary file so a crash
+//! cannot leave it half written, and is only readable by the current user.
+//! In demo mode the jar is never written to disk.
+
+use std::io::{BufReader, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use cookie_store::{CookieDomain, CookieExpiration, CookieStore};
+use reqwest::header::HeaderValue;
+use reqwest::Url;
+use time::format_description::well_known::Rfc3339;
+use tokio::sync::Notify;
+use tracing::warn;
+use crate::domain::http::{CookieInfo, ICookieJar};
+use crate::error::AppError;
+
+/// Cookies set by a burst of responses are written together
+const SAVE_DELAY: Duration = Duration::from_secs(1);
+
+pub struct PersistentCookieJar {
+    store: RwLock<CookieStore>,
+    /// `None` keeps the cookies in memory only
+    path: Option<PathBuf>,
+    /// Wakes the task that saves the jar
+    changed: Notify,
+    /// Held while writing the file, so saves cannot interleave
+    save_lock: Mutex<()>,
+}
+
+impl PersistentCookieJar {
+    /// Load the jar at `path` and start saving changes to it.
+    /// A missing or unreadable file starts an empty jar.
+    pub fn open(path: PathBuf) -> Arc<Self> {
+        let store = match std::fs::File::open(&path) {
+            // Expired cookies are dropped while loading
+            Ok(file) => cookie_store::serde::json::load(BufReader::new(file)).unwrap_or_else(|e| {
+                warn!("Ignoring unreadable cookie jar {:?}: {}", path, e);
+                CookieStore::default()
+            }),
+            Err(_) => CookieStore::default(),
+        };
+
+        let jar = Arc::new(Self::new(store, Some(path)));
+        let saver = jar.clone();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                saver.changed.notified().await;
+                tokio::time::sleep(SAVE_DELAY).await;
+                let jar = saver.clone();
+                match tokio::task::spawn_blocking(move || jar.save()).await {
+                    Ok(Err(e)) => warn!("{}", e),
+                    Err(e) => warn!("Failed to save cookies: {}", e),
+                    Ok(Ok(())) => {}
+                }
+            }
+        });
+        jar
+    }
+
+    /// An empty jar that is never written to disk (demo mode).
+    pub fn in_memory() -> Arc<Self> {
+        Arc::new(Self::new(CookieStore::default(), None))
+    }
+
+    fn new(store: CookieStore, path: Option<PathBuf>) -> Self {
+        Self { store: RwLock::new(store), path, changed: Notify::new(), save_lock: Mutex::new(()) }
+    }
+
+    /// Write the current cookies to disk. Blocking; not called with the store locked.
+    fn save(&self) -> Result<(), AppError> {
+        let Some(path) = &self.path else { return Ok(()) };
+        let _guard = self.save_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let io_error = |e: std::io::Error| AppError::Io(format!("Failed to save cookies to {:?}: {}", path, e));
+
+        // Taken under the save lock, so the last write has the latest cookies
+        let mut contents = Vec::new();
+        {
+            let store = self.store.read().unwrap_or_else(|e| e.into_inner());
+            cookie_store::serde::json::save_incl_expired_and_nonpersistent(&store, &mut contents)
+                .map_err(|e| AppError::Io(format!("Failed to save cookies to {:?}: {}", path, e)))?;
+        }
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(io_error)?;
+        }
+        let temp = path.with_extension("json.tmp");
+        // Created afresh, so a leftover file cannot pass on wider permissions
+        let _ = std::fs::remove_file(&temp);
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&temp).map_err(io_error)?;
+        file.write_all(&contents).map_err(io_error)?;
+        file.sync_all().map_err(io_error)?;
+        drop(file);
+        std::fs::rename(&temp, path).map_err(io_error)
+    }
+}
+
+impl reqwest::cookie::CookieStore for PersistentCookieJar {
+    fn set_cookies(&self, headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let mut store = self.store.write().unwrap_or_else(|e| e.into_inner());
+        let mut changed = false;
+        for header in headers {
+            // Invalid cookies and ones the URL may not set are skipped, like a browser does
+            if let Ok(value) = header.to_str() {
+                changed |= store.parse(value, url).is_ok();
+            }
+        }
+        if changed && self.path.is_some() {
+            self.changed.notify_one();
+        }
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        let store = self.store.read().unwrap_or_else(|e| e.into_inner());
+        let value = store
+            .get_request_values(url)
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("; ");
+        if value.is_empty() {
+            return None;
+        }
+        HeaderValue::from_str(&value).ok()
+    }
+}
+
+impl ICookieJar for PersistentCookieJar {
+    fn list(&self, domain: Option<&str>) -> Vec<CookieInfo> {
+        let store = self.store.read().unwrap_or_else(|e| e.into_inner());
+        let mut cookies: Vec<CookieInfo> = store
+            .iter_unexpired()
+            .filter(|cookie| in_domain(&cookie.domain, domain))
+            .map(|cookie| {
+                let http_only = cookie.http_only().unwrap_or(false);
+                CookieInfo {
+                    domain: String::from(&cookie.domain),
+                    host_only: matches!(cookie.domain, CookieDomain::HostOnly(_)),
+                    path: String::from(&cookie.path),
+                    name: cookie.name().to_string(),
+                    value: (!http_only).then(|| cookie.value().to_string()),
+                    secure: cookie.secure().unwrap_or(false),
+                    http_only,
+                    expires: match &cookie.expires {
+                        CookieExpiration::AtUtc(at) => at.format(&Rfc3339).ok(),
+                        CookieExpiration::SessionEnd => None,
+                    },
+                }
+            })
+            .collect();
+        cookies.sort_by(|a, b| (&a.domain, &a.path, &a.name).cmp(&(&b.domain, &b.path, &b.name)));
+        cookies
+    }
+
+    fn clear(&self, domain: Option<&str>) -> Result<u64, AppError> {
+        let mut store = self.store.write().unwrap_or_else(|e| e.into_inner());
+        // Expired cookies are removed as well, so they leave the file too
+        let doomed: Vec<(String, String, String)> = store
+            .iter_any()
+            .filter(|cookie| in_domain(&cookie.domain, domain))
+            .map(|cookie| (String::from(&cookie.domain), String::from(&cookie.path), cookie.name().to_string()))
+            .collect();
+        for (domain, path, name) in &doomed {
+            store.remove(domain, path, name);
+        }
+        drop(store);
+        // Saved right away, so cleared cookies are gone from disk when this returns
+        self.save()?;
+        Ok(doomed.len() as u64)
+    }
+}
+
+/// Whether a cookie's domain is `filter` or one of its subdomains (any domain without a filter)
+fn in_domain(cookie_domain: &CookieDomain, filter: Option<&str>) -> bool {
+    let Some(filter) = filter else { return true };
+    let filter = filter.trim().trim_start_matches('.').to_ascii_lowercase();
+    let domain = String::from(cookie_domain);
+    domain == filter || domain.ends_with(&format!(".{}", filter))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::cookie::CookieStore as _;
+
+    fn set(jar: &PersistentCookieJar, url: &str, cookies: &[&'static str]) {
+        let headers: Vec<HeaderValue> = cookies.iter().map(|c| HeaderValue::from_static(c)).collect();
+        jar.set_cookies(&mut headers.iter(), &Url::parse(url).unwrap());
+    }
+
+    async fn wait_for(path: &std::path::Path) -> bool {
+        for _ in 0..50 {
+            if path.exists() {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        false
+    }
+
+    #[tokio::test]
+    async fn changes_are_saved_in_the_background() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cookies.json");
+        let jar = PersistentCookieJar::open(path.clone());
+
+        set(&jar, "https://api.example.com/", &["session=abc; Path=/", "theme=dark; Max-Age=3600"]);
+        assert!(wait_for(&path).await, "cookies were not saved");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+
+        // Session cookies survive a restart too
+        let reopened = PersistentCookieJar::open(path.clone());
+        let names: Vec<String> = reopened.list(None).into_iter().map(|c| c.name).collect();
+        assert_eq!(names, ["session", "theme"]);
+        assert!(reopened.cookies(&Url::parse("https://api.example.com/").unwrap()).is_some());
+    }
+
+    #[tokio::test]
+    async fn clear_saves_right_away() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cookies.json");
+        let jar = PersistentCookieJar::open(path.clone());
+        set(&jar, "https://a.example.com/", &["one=1"]);
+        set(&jar, "https://b.example.org/", &["two=2"]);
+
+        assert_eq!(jar.clear(Some("example.com")).unwrap(), 1);
+        let names: Vec<String> = PersistentCookieJar::open(path).list(None).into_iter().map(|c| c.name).collect();
+        assert_eq!(names, ["two"]);
+    }
+
+    #[tokio::test]
+    async fn in_memory_jar_is_never_written() {
+        let jar = PersistentCookieJar::in_memory();
+        set(&jar, "https://api.example.com/", &["session=abc"]);
+        assert_eq!(jar.list(Some("api.example.com")).len(), 1);
+        assert_eq!(jar.clear(None).unwrap(), 1);
+        assert!(jar.list(None).is_empty());
+    }
+}
@@ -0,0 +1,116 @@
+//! Symmetric encryption for secrets stored at rest (AES-256-GCM).
+//!
+//! The 256-bit key comes from one of:
+//! 1. `SECRET_STORE_PASSPHRASE` env var, stretched with Argon2id and a per-install salt.
+//! 2. A random master key file in `app_data_dir` (created on first use).
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use aes_gcm::aead::{Aead, KeyInit, OsRng, Payload};
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use tauri::{AppHandle, Manager, Runtime};
+use tracing::info;
+
+use crate::error::AppError;
+
+const PASSPHRASE_ENV: &str = "SECRET_STORE_PASSPHRASE";
+const MASTER_KEY_FILE: &str = "master.key";
+const SALT_FILE: &str = "master.salt";
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+pub struct SecretCipher {
+    cipher: Aes256Gcm,
+}
+
+impl SecretCipher {
+    /// Load the master key from the passphrase env var or the key file.
+    pub fn load<R: Runtime>(app: &AppHandle<R>) -> Result<Self, AppError> {
+        let dir = app.path().app_data_dir()?;
+        fs::create_dir_all(&dir)?;
+
+        let key = match std::env::var(PASSPHRASE_ENV) {
+            Ok(passphrase) if !passphrase.is_empty() => {
+                info!("Deriving secret store key from passphrase");
+                let salt = read_or_create(&dir.join(SALT_FILE), SALT_LEN)?;
+                derive_key(passphrase.as_bytes(), &salt)?
+            }
+            _ => read_or_create(&dir.join(MASTER_KEY_FILE), KEY_LEN)?,
+        };
+
+        Self::from_key(&key)
+    }
+
+    pub fn from_key(key: &[u8]) -> Result<Self, AppError> {
+        if key.len() != KEY_LEN {
+            return Err(AppError::Crypto(format!(
+                "Master key must be {} bytes, got {}",
+                KEY_LEN,
+                key.len()
+            )));
+        }
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+        Ok(Self { cipher })
+    }
+
+    /// Encrypt `plaintext`, binding it to `aad` (the setting key) so ciphertexts
+    /// can't be swapped between keys. Returns `(nonce, ciphertext)`.
+    pub fn encrypt(&self, plaintext: &[u8], aad: &[u8]) -> Result<(Vec<u8>, Vec<u8>), AppError> {
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+
+        let ciphertext = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad })
+            .map_err(|_| AppError::Crypto("Encryption failed".to_string()))?;
+
+        Ok((nonce.to_vec(), ciphertext))
+    }
+
+    pub fn decrypt(&self, nonce: &[u8], ciphertext: &[u8], aad: &[u8]) -> Result<Vec<u8>, AppError> {
+        if nonce.len() != NONCE_LEN {
+            return Err(AppError::Crypto("Invalid nonce length".to_string()));
+        }
+
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad })
+            .map_err(|_| AppError::Crypto("Decryption failed (wrong master key or corrupted data)".to_string()))
+    }
+}
+
+fn derive_key(passphrase: &[u8], salt: &[u8]) -> Result<Vec<u8>, AppError> {
+    let mut key = vec![0u8; KEY_LEN];
+    argon2::Argon2::default()
+        .hash_password_into(passphrase, salt, &mut key)
+        .map_err(|e| AppError::Crypto(format!("Key derivation failed: {}", e)))?;
+    Ok(key)
+}
+
+/// Read `len` random bytes from `path`, generating the file on first use.
+fn read_or_create(path: &Path, len: usize) -> Result<Vec<u8>, AppError> {
+    if path.exists() {
+        let bytes = fs::read(path)?;
+        if bytes.len() != len {
+            return Err(AppError::Crypto(format!("{:?} is corrupted (expected {} bytes)", path, len)));
+        }
+        return Ok(bytes);
+    }
+
+    let mut bytes = vec![0u8; len];
+    OsRng.fill_bytes(&mut bytes);
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(&bytes)?;
+
+    info!("Created {:?}", path);
+    Ok(bytes)
+}
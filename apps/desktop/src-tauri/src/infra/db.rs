@@ -1,50 +1,330 @@
 use std::fs;
-use std::path::PathBuf;
-use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use sqlx::sqlite::{SqliteAutoVacuum, SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous};
+use sqlx::SqlitePool;
 use tauri::{AppHandle, Manager, Runtime};
-use tracing::{info, error};
+use tokio::sync::{RwLock as AsyncRwLock, RwLockReadGuard, RwLockWriteGuard};
+use tracing::{info, warn};
 
+use crate::domain::config::StaticConfig;
+pub use crate::domain::diagnostics::DbOptions;
 use crate::error::AppError;
+use crate::infra::query_stats::QueryStats;
+
+impl DbOptions {
+    /// Defaults overridden by the static config layers. Invalid values are logged and ignored.
+    pub fn from_config(config: &StaticConfig) -> Self {
+        let mut options = Self::default();
+
+        if let Some(mode) = parse_setting(config, "db_journal_mode", |v| SqliteJournalMode::from_str(v).ok().map(|_| v.to_uppercase())) {
+            options.journal_mode = mode;
+        }
+        if let Some(sync) = parse_setting(config, "db_synchronous", |v| SqliteSynchronous::from_str(v).ok().map(|_| v.to_uppercase())) {
+            options.synchronous = sync;
+        }
+        if let Some(ms) = parse_setting(config, "db_busy_timeout_ms", |v| v.parse().ok()) {
+            options.busy_timeout_ms = ms;
+        }
+        if let Some(on) = parse_setting(config, "db_foreign_keys", |v| v.parse().ok()) {
+            options.foreign_keys = on;
+        }
+        if let Some(mode) = parse_setting(config, "db_auto_vacuum", |v| SqliteAutoVacuum::from_str(v).ok().map(|_| v.to_uppercase())) {
+            options.auto_vacuum = mode;
+        }
+        if let Some(max) = parse_setting(config, "db_max_connections", |v| v.parse().ok().filter(|n| *n > 0)) {
+            options.max_connections = max;
+        }
+        if let Some(ms) = parse_setting(config, "db_acquire_timeout_ms", |v| v.parse().ok()) {
+            options.acquire_timeout_ms = ms;
+        }
+        if let Some(ms) = parse_setting(config, "db_slow_query_ms", |v| v.parse().ok()) {
+            options.slow_query_ms = ms;
+        }
+
+        options
+    }
+
+    fn connect_options(&self, db_path: &Path) -> Result<SqliteConnectOptions, AppError> {
+        let journal_mode = SqliteJournalMode::from_str(&self.journal_mode)
+            .map_err(|e| AppError::Database(format!("Invalid journal mode: {}", e)))?;
+        let synchronous = SqliteSynchronous::from_str(&self.synchronous)
+            .map_err(|e| AppError::Database(format!("Invalid synchronous mode: {}", e)))?;
+        let auto_vacuum = SqliteAutoVacuum::from_str(&self.auto_vacuum)
+            .map_err(|e| AppError::Database(format!("Invalid auto_vacuum mode: {}", e)))?;
+
+        Ok(SqliteConnectOptions::new()
+            .filename(db_path)
+            .create_if_missing(true)
+            .auto_vacuum(auto_vacuum)
+            .journal_mode(journal_mode)
+            .synchronous(synchronous)
+            .busy_timeout(Duration::from_millis(self.busy_timeout_ms))
+            .foreign_keys(self.foreign_keys))
+    }
+}
+
+pub(crate) fn parse_setting<T>(config: &StaticConfig, key: &str, parse: impl Fn(&str) -> Option<T>) -> Option<T> {
+    let (value, layer) = config.get(key)?;
+    let parsed = parse(value.trim());
+    if parsed.is_none() {
+        warn!("Ignoring invalid {} = {:?} from {:?}", key, value, layer);
+    }
+    parsed
+}
+
+/// Shared handle to the SQLite database.
+///
+/// Repositories fetch the pool per call instead of holding their own clone,
+/// so the pool can be swapped (e.g. after restoring a backup) without
+/// rebuilding the handlers that use it. The handle they get keeps the pool
+/// in use until it is dropped; a swap waits for every handle to go away.
+pub struct Database {
+    path: PathBuf,
+    options: DbOptions,
+    pool: RwLock<SqlitePool>,
+    /// Shared by pool users, taken exclusively to swap the pool
+    gate: AsyncRwLock<()>,
+    queries: Arc<QueryStats>,
+}
+
+/// The current pool, held for as long as this handle lives.
+pub struct DbPool<'a> {
+    pool: SqlitePool,
+    _guard: RwLockReadGuard<'a, ()>,
+}
+
+impl Deref for DbPool<'_> {
+    type Target = SqlitePool;
+
+    fn deref(&self) -> &SqlitePool {
+        &self.pool
+    }
+}
+
+/// Sole access to the database: no repository query runs while this is held.
+pub struct ExclusiveDb<'a> {
+    db: &'a Database,
+    _guard: RwLockWriteGuard<'a, ()>,
+}
+
+impl ExclusiveDb<'_> {
+    pub fn pool(&self) -> SqlitePool {
+        self.db.current_pool()
+    }
+
+    /// Replace the pool, returning the previous one so the caller can close it.
+    pub fn replace_pool(&self, pool: SqlitePool) -> SqlitePool {
+        std::mem::replace(&mut *self.db.pool.write().unwrap_or_else(|e| e.into_inner()), pool)
+    }
+}
+
+impl Database {
+    /// Connect to the database file at `path` and bring its schema up to date.
+    pub async fn open(path: PathBuf, options: DbOptions) -> Result<Self, AppError> {
+        let pool = connect(&path, &options).await?;
+        Ok(Self {
+            queries: Arc::new(QueryStats::new(options.slow_query_ms)),
+            path,
+            options,
+            pool: RwLock::new(pool),
+            gate: AsyncRwLock::new(()),
+        })
+    }
+
+    /// Current connection pool. Waits while the pool is being swapped.
+    pub async fn pool(&self) -> DbPool<'_> {
+        let guard = self.gate.read().await;
+        DbPool { pool: self.current_pool(), _guard: guard }
+    }
+
+    /// Wait for every pool user to finish and hold off new ones until the result is dropped.
+    pub async fn exclusive(&self) -> ExclusiveDb<'_> {
+        ExclusiveDb { db: self, _guard: self.gate.write().await }
+    }
+
+    /// The current pool without holding it, for statistics only; queries go through `pool`.
+    pub(crate) fn current_pool(&self) -> SqlitePool {
+        self.pool.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Path of the database file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Options the pool was opened with.
+    pub fn options(&self) -> &DbOptions {
+        &self.options
+    }
+
+    /// Timing and statistics for repository queries.
+    pub fn queries(&self) -> &Arc<QueryStats> {
+        &self.queries
+    }
+
+}
 
 /// Initialize the database:
 /// 1. Ensure directory exists.
-/// 2. Create database file.
-/// 3. Connect.
-/// 4. Run migrations.
-pub async fn init_db<R: Runtime>(app: &AppHandle<R>) -> Result<SqlitePool, AppError> {
+/// 2. Connect (creating the file if missing) with the configured pragmas.
+/// 3. Run migrations.
+/// 4. Run settings key migrations.
+pub async fn init_db<R: Runtime>(app: &AppHandle<R>, options: DbOptions) -> Result<Database, AppError> {
+    Database::open(database_path(app)?, options).await
+}
+
+/// Location of the database file (`app_data_dir/db.sqlite`), creating the directory if needed.
+pub fn database_path<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, AppError> {
     let app_data_dir = app.path().app_data_dir()?;
-    
+
     // Ensure app_data_dir exists
     if !app_data_dir.exists() {
         fs::create_dir_all(&app_data_dir)?;
     }
 
-    let db_path = app_data_dir.join("db.sqlite");
-    let db_url = format!("sqlite:{}", db_path.to_string_lossy());
-
-    info!("Connecting to database at: {}", db_url);
+    Ok(app_data_dir.join("db.sqlite"))
+}
 
-    // Create the file if it doesn't exist (SqlitePool won't create it by default without specific options usually, 
-    // but using sqlx::migrate! often handles setup. However, standard practice is ensuring the file exists 
-    // for local embedded usage to avoid connection errors).
-    if !db_path.exists() {
-        fs::File::create(&db_path)?;
+/// Close `pool` and wait until every connection is closed, so the file can be replaced.
+/// `SqlitePool::close` may return while a connection is still on its way back to the pool,
+/// and SQLite shares WAL state between connections of a process.
+pub async fn close_pool(pool: &SqlitePool) {
+    pool.close().await;
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+    while pool.size() > 0 {
+        if tokio::time::Instant::now() >= deadline {
+            warn!("{} database connections are still open", pool.size());
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
     }
+}
+
+/// Open a pool for the database file at `db_path` and bring its schema up to date.
+pub async fn connect(db_path: &Path, options: &DbOptions) -> Result<SqlitePool, AppError> {
+    info!("Connecting to database at: {:?} ({:?})", db_path, options);
 
+    // Pragmas are applied to every new connection in the pool
     let pool = SqlitePoolOptions::new()
-        .max_connections(5)
-        .connect(&db_url)
+        .max_connections(options.max_connections)
+        .acquire_timeout(Duration::from_millis(options.acquire_timeout_ms))
+        .connect_with(options.connect_options(db_path)?)
         .await
         .map_err(|e| AppError::Database(format!("Failed to connect: {}", e)))?;
 
     // Run Migrations (Embedded in binary)
-    // sqlx looks for the 'migrations' folder relative to CARGO_MANIFEST_DIR at compile time.
-    sqlx::migrate!("./migrations")
+    crate::infra::db_migrations::MIGRATOR
         .run(&pool)
         .await
         .map_err(|e| AppError::Database(format!("Migration failed: {}", e)))?;
 
     info!("Database migrations applied successfully.");
 
+    // Settings key migrations (rename/convert/drop keys between app versions)
+    crate::infra::settings_migrations::run(&pool).await?;
+
     Ok(pool)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::time::Duration;
+    use crate::domain::config::ConfigLayer;
+
+    fn options(values: &[(&str, &str)]) -> DbOptions {
+        let mut config = StaticConfig::default();
+        let values: HashMap<String, String> = values.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        config.apply(ConfigLayer::UserFile, values);
+        DbOptions::from_config(&config)
+    }
+
+    #[test]
+    fn config_overrides_the_defaults() {
+        let options = options(&[
+            ("db_journal_mode", "delete"),
+            ("db_synchronous", " full "),
+            ("db_busy_timeout_ms", "100"),
+            ("db_foreign_keys", "false"),
+            ("db_auto_vacuum", "none"),
+            ("db_max_connections", "2"),
+            ("db_acquire_timeout_ms", "1000"),
+            ("db_slow_query_ms", "50"),
+        ]);
+
+        assert_eq!(options.journal_mode, "DELETE");
+        assert_eq!(options.synchronous, "FULL");
+        assert_eq!(options.busy_timeout_ms, 100);
+        assert!(!options.foreign_keys);
+        assert_eq!(options.auto_vacuum, "NONE");
+        assert_eq!(options.max_connections, 2);
+        assert_eq!(options.acquire_timeout_ms, 1000);
+        assert_eq!(options.slow_query_ms, 50);
+    }
+
+    #[test]
+    fn invalid_values_fall_back_to_the_defaults() {
+        let options = options(&[
+            ("db_journal_mode", "sideways"),
+            ("db_synchronous", "sometimes"),
+            ("db_busy_timeout_ms", "-1"),
+            ("db_foreign_keys", "yes"),
+            ("db_auto_vacuum", "always"),
+            ("db_max_connections", "0"),
+            ("db_acquire_timeout_ms", "soon"),
+            ("db_slow_query_ms", "1.5"),
+        ]);
+
+        let defaults = DbOptions::default();
+        assert_eq!(options.journal_mode, defaults.journal_mode);
+        assert_eq!(options.synchronous, defaults.synchronous);
+        assert_eq!(options.busy_timeout_ms, defaults.busy_timeout_ms);
+        assert_eq!(options.foreign_keys, defaults.foreign_keys);
+        assert_eq!(options.auto_vacuum, defaults.auto_vacuum);
+        assert_eq!(options.max_connections, defaults.max_connections);
+        assert_eq!(options.acquire_timeout_ms, defaults.acquire_timeout_ms);
+        assert_eq!(options.slow_query_ms, defaults.slow_query_ms);
+    }
+
+    #[tokio::test]
+    async fn overridden_options_open_a_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = options(&[("db_journal_mode", "delete"), ("db_auto_vacuum", "none")]);
+        let db = Database::open(dir.path().join("app.db"), options).await.unwrap();
+
+        let mode: String = sqlx::query_scalar("PRAGMA journal_mode").fetch_one(&*db.pool().await).await.unwrap();
+        assert_eq!(mode, "delete");
+    }
+
+    #[tokio::test]
+    async fn exclusive_access_waits_for_pool_users() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(Database::open(dir.path().join("app.db"), DbOptions::default()).await.unwrap());
+
+        let user = db.pool().await;
+        let swap = {
+            let db = db.clone();
+            tokio::spawn(async move {
+                let exclusive = db.exclusive().await;
+                exclusive.pool().close().await;
+                let pool = connect(db.path(), db.options()).await.unwrap();
+                exclusive.replace_pool(pool);
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!swap.is_finished(), "the swap must wait for the running query");
+
+        // Still usable: the pool is not closed under a running caller
+        sqlx::query("SELECT 1").execute(&*user).await.unwrap();
+        drop(user);
+        swap.await.unwrap();
+
+        // Later callers get the new pool
+        sqlx::query("SELECT 1").execute(&*db.pool().await).await.unwrap();
+    }
+}
@@ -0,0 +1,115 @@
+//! Structured database diagnostics for the Advanced settings page.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use async_trait::async_trait;
+use sqlx::SqliteConnection;
+
+use crate::domain::diagnostics::{DbHealthReport, IDbDiagnostics, MigrationInfo, PoolStats, PragmaReport, TableStats};
+use crate::error::AppError;
+use crate::infra::db::Database;
+
+pub struct SqliteDiagnostics {
+    db: Arc<Database>,
+}
+
+impl SqliteDiagnostics {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl IDbDiagnostics for SqliteDiagnostics {
+    async fn collect(&self) -> Result<DbHealthReport, AppError> {
+        collect(&self.db).await
+    }
+}
+
+/// Collect a full diagnostics report for the live database.
+async fn collect(db: &Database) -> Result<DbHealthReport, AppError> {
+    let pool = db.pool().await;
+    let mut conn = pool.acquire().await?;
+
+    let integrity_check: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check")
+        .fetch_all(&mut *conn)
+        .await?;
+    let healthy = integrity_check.len() == 1 && integrity_check[0] == "ok";
+
+    let page_size = pragma_i64(&mut conn, "page_size").await?;
+    let page_count = pragma_i64(&mut conn, "page_count").await?;
+    let freelist_count = pragma_i64(&mut conn, "freelist_count").await?;
+
+    let pragmas = PragmaReport {
+        journal_mode: sqlx::query_scalar("PRAGMA journal_mode").fetch_one(&mut *conn).await?,
+        synchronous: pragma_i64(&mut conn, "synchronous").await?,
+        busy_timeout_ms: pragma_i64(&mut conn, "busy_timeout").await?,
+        foreign_keys: pragma_i64(&mut conn, "foreign_keys").await? != 0,
+    };
+
+    let migrations = sqlx::query_as::<_, MigrationInfo>(
+        "SELECT version, description, CAST(installed_on AS TEXT) AS installed_on, success, checksum FROM _sqlx_migrations ORDER BY version",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let settings_migrations: Vec<i64> = sqlx::query_scalar("SELECT version FROM settings_migrations ORDER BY version")
+        .fetch_all(&mut *conn)
+        .await?;
+
+    let tables = table_stats(&mut conn).await?;
+    drop(conn);
+
+    let file_size_bytes = file_size(db.path().to_path_buf());
+    let wal_size_bytes = file_size(PathBuf::from(format!("{}-wal", db.path().to_string_lossy())));
+
+    Ok(DbHealthReport {
+        healthy,
+        integrity_check,
+        file_path: db.path().to_string_lossy().to_string(),
+        file_size_bytes,
+        wal_size_bytes,
+        page_size,
+        page_count,
+        freelist_count,
+        pragmas,
+        options: db.options().clone(),
+        migrations,
+        settings_migrations,
+        tables,
+        pool: PoolStats {
+            size: pool.size(),
+            idle: pool.num_idle(),
+            max_connections: db.options().max_connections,
+        },
+    })
+}
+
+async fn pragma_i64(conn: &mut SqliteConnection, name: &str) -> Result<i64, AppError> {
+    let value = sqlx::query_scalar(&format!("PRAGMA {}", name))
+        .fetch_one(conn)
+        .await?;
+    Ok(value)
+}
+
+async fn table_stats(conn: &mut SqliteConnection) -> Result<Vec<TableStats>, AppError> {
+    let names: Vec<String> = sqlx::query_scalar(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut tables = Vec::with_capacity(names.len());
+    for name in names {
+        let rows: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM \"{}\"", name.replace('"', "\"\"")))
+            .fetch_one(&mut *conn)
+            .await?;
+        tables.push(TableStats { name, rows });
+    }
+    Ok(tables)
+}
+
+fn file_size(path: PathBuf) -> u64 {
+    fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
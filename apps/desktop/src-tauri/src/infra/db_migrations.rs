@@ -0,0 +1,180 @@
+//! Schema migrations embedded at compile time, their status and rollback.
+//!
+//! Every migration is a `<version>_<name>.up.sql` / `.down.sql` pair. A rollback
+//! only lasts until the next launch, which applies pending migrations again.
+
+use std::collections::HashMap;
+use serde::Serialize;
+use sqlx::migrate::Migrator;
+
+use crate::error::AppError;
+use crate::infra::db::Database;
+
+// sqlx looks for the 'migrations' folder relative to CARGO_MANIFEST_DIR at compile time.
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+#[derive(Debug, Serialize)]
+pub struct MigrationStatus {
+    /// Highest applied version, `None` for an empty database
+    pub current_version: Option<i64>,
+    /// Highest version this build knows about
+    pub latest_version: Option<i64>,
+    pub migrations: Vec<MigrationEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MigrationEntry {
+    pub version: i64,
+    pub description: String,
+    pub state: MigrationState,
+    pub installed_on: Option<String>,
+    /// Whether a down migration exists
+    pub reversible: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// Applied, but the migration file has changed since
+    ChecksumMismatch,
+    /// Applied, but unknown to this build (database written by a newer version)
+    Unknown,
+    /// Started but did not finish
+    Failed,
+}
+
+#[derive(sqlx::FromRow)]
+struct AppliedRow {
+    version: i64,
+    description: String,
+    installed_on: String,
+    success: bool,
+    checksum: Vec<u8>,
+}
+
+/// Applied and pending migrations, in version order.
+pub async fn status(db: &Database) -> Result<MigrationStatus, AppError> {
+    let applied: HashMap<i64, AppliedRow> = sqlx::query_as::<_, AppliedRow>(
+        "SELECT version, description, CAST(installed_on AS TEXT) AS installed_on, success, checksum FROM _sqlx_migrations",
+    )
+    .fetch_all(&*db.pool().await)
+    .await?
+    .into_iter()
+    .map(|row| (row.version, row))
+    .collect();
+
+    let mut migrations: Vec<MigrationEntry> = MIGRATOR
+        .iter()
+        .filter(|m| m.migration_type.is_up_migration())
+        .map(|m| {
+            let row = applied.get(&m.version);
+            let state = match row {
+                None => MigrationState::Pending,
+                Some(row) if !row.success => MigrationState::Failed,
+                Some(row) if row.checksum != *m.checksum => MigrationState::ChecksumMismatch,
+                Some(_) => MigrationState::Applied,
+            };
+            MigrationEntry {
+                version: m.version,
+                description: m.description.to_string(),
+                state,
+                installed_on: row.map(|r| r.installed_on.clone()),
+                reversible: has_down(m.version),
+            }
+        })
+        .collect();
+
+    for row in applied.values().filter(|row| !MIGRATOR.version_exists(row.version)) {
+        migrations.push(MigrationEntry {
+            version: row.version,
+            description: row.description.clone(),
+            state: MigrationState::Unknown,
+            installed_on: Some(row.installed_on.clone()),
+            reversible: false,
+        });
+    }
+    migrations.sort_by_key(|m| m.version);
+
+    Ok(MigrationStatus {
+        current_version: applied.keys().max().copied(),
+        latest_version: MIGRATOR.iter().map(|m| m.version).max(),
+        migrations,
+    })
+}
+
+/// Revert every applied migration above `target_version` (0 reverts all of them).
+pub async fn rollback(db: &Database, target_version: i64) -> Result<MigrationStatus, AppError> {
+    if target_version != 0 && !MIGRATOR.version_exists(target_version) {
+        return Err(AppError::NotFound(format!("Migration {} not found", target_version)));
+    }
+
+    let current = status(db).await?;
+    let to_revert: Vec<&MigrationEntry> = current
+        .migrations
+        .iter()
+        .filter(|m| m.version > target_version && m.state != MigrationState::Pending)
+        .collect();
+    if to_revert.is_empty() {
+        return Err(AppError::Domain(format!("Nothing to roll back above version {}", target_version)));
+    }
+    if let Some(m) = to_revert.iter().find(|m| !m.reversible) {
+        return Err(AppError::Domain(format!("Migration {} ({}) cannot be reverted", m.version, m.description)));
+    }
+
+    MIGRATOR
+        .undo(&*db.pool().await, target_version)
+        .await
+        .map_err(|e| AppError::Database(format!("Rollback failed: {}", e)))?;
+
+    status(db).await
+}
+
+fn has_down(version: i64) -> bool {
+    MIGRATOR
+        .iter()
+        .any(|m| m.version == version && m.migration_type.is_down_migration())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use sqlx::SqlitePool;
+
+    async fn schema(pool: &SqlitePool) -> Vec<(String, Option<String>)> {
+        sqlx::query_as("SELECT name, sql FROM sqlite_master WHERE name NOT LIKE 'sqlite_%' ORDER BY name")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn every_migration_can_be_reverted_and_applied_again() {
+        let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+        MIGRATOR.run(&pool).await.unwrap();
+        let migrated = schema(&pool).await;
+
+        let mut versions: Vec<i64> = MIGRATOR
+            .iter()
+            .filter(|m| m.migration_type.is_up_migration())
+            .map(|m| m.version)
+            .collect();
+        versions.sort();
+        // Newest first, so each step reverts one more migration than the last and
+        // applies all of them again
+        for (i, version) in versions.iter().enumerate().rev() {
+            assert!(has_down(*version), "migration {} has no down migration", version);
+            let below = if i == 0 { 0 } else { versions[i - 1] };
+            MIGRATOR.undo(&pool, below).await.unwrap_or_else(|e| panic!("undo {}: {}", version, e));
+            let current: Option<i64> = sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            assert_eq!(current.unwrap_or(0), below);
+            MIGRATOR.run(&pool).await.unwrap_or_else(|e| panic!("redo {}: {}", version, e));
+            assert_eq!(schema(&pool).await, migrated, "migration {} changed the schema on redo", version);
+        }
+    }
+}
@@ -0,0 +1,267 @@
+//! Recovery operations for a database that failed to open at startup.
+//!
+//! Everything here works on files and one-off connections, since there is no
+//! live pool. A successful `reopen` hands the `Database` to the startup code
+//! waiting on the receiver returned by `SqliteRecovery::new`.
+
+use std::fs;
+use std::path::PathBuf;
+use async_trait::async_trait;
+use serde_json::{Map, Value};
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{Connection, SqliteConnection};
+use time::macros::format_description;
+use time::OffsetDateTime;
+use tokio::sync::{oneshot, Mutex};
+use tracing::{info, warn};
+
+use crate::domain::backups::BackupInfo;
+use crate::domain::recovery::{IDatabaseRecovery, SalvageReport, SalvagedTable};
+use crate::error::AppError;
+use crate::infra::backup_store;
+use crate::infra::db::{Database, DbOptions};
+
+const SIDECAR_SUFFIXES: [&str; 2] = ["-wal", "-shm"];
+
+pub struct SqliteRecovery {
+    db_path: PathBuf,
+    backups_dir: PathBuf,
+    options: DbOptions,
+    /// Taken by the first successful `reopen`
+    ready: Mutex<Option<oneshot::Sender<Database>>>,
+}
+
+impl SqliteRecovery {
+    /// Returns the recovery handle and the receiver the opened database is delivered on.
+    pub fn new(db_path: PathBuf, backups_dir: PathBuf, options: DbOptions) -> (Self, oneshot::Receiver<Database>) {
+        let (tx, rx) = oneshot::channel();
+        let recovery = Self {
+            db_path,
+            backups_dir,
+            options,
+            ready: Mutex::new(Some(tx)),
+        };
+        (recovery, rx)
+    }
+
+    fn sidecar(&self, suffix: &str) -> PathBuf {
+        PathBuf::from(format!("{}{}", self.db_path.to_string_lossy(), suffix))
+    }
+
+    fn recovery_dir(&self) -> PathBuf {
+        self.db_path
+            .parent()
+            .map(|dir| dir.join("recovery"))
+            .unwrap_or_else(|| PathBuf::from("recovery"))
+    }
+}
+
+#[async_trait]
+impl IDatabaseRecovery for SqliteRecovery {
+    fn db_path(&self) -> String {
+        self.db_path.to_string_lossy().to_string()
+    }
+
+    fn list_backups(&self) -> Result<Vec<BackupInfo>, AppError> {
+        backup_store::list_backups(&self.backups_dir)
+    }
+
+    async fn reopen(&self) -> Result<(), AppError> {
+        let mut ready = self.ready.lock().await;
+        let Some(tx) = ready.take() else {
+            return Err(AppError::Domain("Database is already open".to_string()));
+        };
+
+        match Database::open(self.db_path.clone(), self.options.clone()).await {
+            Ok(db) => {
+                info!("Database opened from recovery mode");
+                if tx.send(db).is_err() {
+                    return Err(AppError::Unknown("Startup is no longer waiting for the database".to_string()));
+                }
+                Ok(())
+            }
+            Err(e) => {
+                *ready = Some(tx);
+                Err(e)
+            }
+        }
+    }
+
+    async fn move_aside(&self) -> Result<String, AppError> {
+        let stamp = timestamp()?;
+        let file_name = self
+            .db_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "db.sqlite".to_string());
+        let moved_name = format!("{}.broken-{}", file_name, stamp);
+        let moved = self.db_path.with_file_name(&moved_name);
+
+        if self.db_path.exists() {
+            fs::rename(&self.db_path, &moved)?;
+        }
+        for suffix in SIDECAR_SUFFIXES {
+            let sidecar = self.sidecar(suffix);
+            if sidecar.exists() {
+                fs::rename(&sidecar, PathBuf::from(format!("{}{}", moved.to_string_lossy(), suffix)))?;
+            }
+        }
+
+        warn!("Broken database moved aside to {:?}", moved);
+        Ok(moved_name)
+    }
+
+    async fn restore_backup(&self, name: &str) -> Result<(), AppError> {
+        let backup = backup_store::resolve_backup(&self.backups_dir, name)?;
+
+        // Never overwrite the broken file: it may still hold data worth salvaging
+        self.move_aside().await?;
+        fs::copy(&backup, &self.db_path)?;
+
+        info!("Database file replaced with backup {}", name);
+        Ok(())
+    }
+
+    async fn export_salvage(&self) -> Result<SalvageReport, AppError> {
+        let options = SqliteConnectOptions::new()
+            .filename(&self.db_path)
+            .read_only(true);
+        let mut conn = SqliteConnection::connect_with(&options)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to open database read-only: {}", e)))?;
+
+        let names: Vec<String> = sqlx::query_scalar(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+        )
+        .fetch_all(&mut conn)
+        .await?;
+
+        let mut data = Map::new();
+        let mut tables = Vec::with_capacity(names.len());
+        for name in names {
+            match read_table(&mut conn, &name).await {
+                Ok(rows) => {
+                    tables.push(SalvagedTable { name: name.clone(), rows: rows.len(), error: None });
+                    data.insert(name, Value::Array(rows));
+                }
+                Err(e) => {
+                    warn!("Could not salvage table {}: {:?}", name, e);
+                    tables.push(SalvagedTable { name, rows: 0, error: Some(e.to_string()) });
+                }
+            }
+        }
+        let _ = conn.close().await;
+
+        let dir = self.recovery_dir();
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("salvage-{}.json", timestamp()?));
+        let json = serde_json::to_vec_pretty(&Value::Object(data))
+            .map_err(|e| AppError::Unknown(e.to_string()))?;
+        fs::write(&path, json)?;
+
+        info!("Salvaged {} tables to {:?}", tables.len(), path);
+        Ok(SalvageReport {
+            path: path.to_string_lossy().to_string(),
+            tables,
+        })
+    }
+}
+
+/// Read all rows of a table as JSON objects. BLOB columns are hex-encoded.
+pub(crate) async fn read_table(conn: &mut SqliteConnection, table: &str) -> Result<Vec<Value>, AppError> {
+    let columns: Vec<String> = sqlx::query_scalar("SELECT name FROM pragma_table_info(?)")
+        .bind(table)
+        .fetch_all(&mut *conn)
+        .await?;
+    if columns.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let fields = columns
+        .iter()
+        .map(|c| {
+            let ident = quote_ident(c);
+            format!(
+                "'{}', CASE WHEN typeof({ident}) = 'blob' THEN hex({ident}) ELSE {ident} END",
+                c.replace('\'', "''"),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let sql = format!("SELECT json_object({}) FROM {}", fields, quote_ident(table));
+
+    let rows: Vec<String> = sqlx::query_scalar(&sql).fetch_all(&mut *conn).await?;
+    rows.iter()
+        .map(|row| serde_json::from_str(row).map_err(|e| AppError::Unknown(e.to_string())))
+        .collect()
+}
+
+pub(crate) fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn timestamp() -> Result<String, AppError> {
+    OffsetDateTime::now_utc()
+        .format(format_description!("[year][month][day]-[hour][minute][second]"))
+        .map_err(|e| AppError::Unknown(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn salvage_writes_every_readable_table() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("app.db");
+        let options = SqliteConnectOptions::new().filename(&db_path).create_if_missing(true);
+        let mut conn = SqliteConnection::connect_with(&options).await.unwrap();
+        sqlx::query("CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT, data BLOB)")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO notes (body, data) VALUES ('first', x'0aff'), ('second', NULL)")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE empty (id INTEGER)").execute(&mut conn).await.unwrap();
+        conn.close().await.unwrap();
+
+        let (recovery, _opened) = SqliteRecovery::new(db_path, dir.path().join("backups"), DbOptions::default());
+        let report = recovery.export_salvage().await.unwrap();
+
+        let names: Vec<(&str, usize)> = report.tables.iter().map(|t| (t.name.as_str(), t.rows)).collect();
+        assert_eq!(names, [("empty", 0), ("notes", 2)]);
+        let salvaged: Value = serde_json::from_slice(&fs::read(&report.path).unwrap()).unwrap();
+        assert_eq!(
+            salvaged["notes"],
+            serde_json::json!([
+                { "id": 1, "body": "first", "data": "0AFF" },
+                { "id": 2, "body": "second", "data": null },
+            ])
+        );
+        assert!(PathBuf::from(&report.path).starts_with(dir.path().join("recovery")));
+    }
+
+    #[tokio::test]
+    async fn restore_keeps_the_broken_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("app.db");
+        fs::write(&db_path, b"broken").unwrap();
+        let backups = dir.path().join("backups");
+        fs::create_dir_all(&backups).unwrap();
+        fs::write(backups.join("db-20240101-000000.sqlite"), b"backup").unwrap();
+
+        let (recovery, _opened) = SqliteRecovery::new(db_path.clone(), backups, DbOptions::default());
+        recovery.restore_backup("db-20240101-000000.sqlite").await.unwrap();
+
+        assert_eq!(fs::read(&db_path).unwrap(), b"backup");
+        let moved: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| name.starts_with("app.db.broken-"))
+            .collect();
+        assert_eq!(moved.len(), 1);
+        assert_eq!(fs::read(dir.path().join(&moved[0])).unwrap(), b"broken");
+    }
+}
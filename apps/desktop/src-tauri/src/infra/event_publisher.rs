@@ -1,13 +1,26 @@
+use std::sync::{Arc, RwLock};
 use tauri::{AppHandle, Emitter, Runtime};
-use crate::domain::events::{IEventPublisher, DomainEvent};
+use crate::domain::events::{IEventPublisher, IEventSubscriber, DomainEvent};
 
 pub struct TauriEventPublisher<R: Runtime> {
     app_handle: AppHandle<R>,
+    subscribers: RwLock<Vec<Arc<dyn IEventSubscriber>>>,
 }
 
 impl<R: Runtime> TauriEventPublisher<R> {
     pub fn new(app_handle: AppHandle<R>) -> Self {
-        Self { app_handle }
+        Self {
+            app_handle,
+            subscribers: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Register an in-process subscriber that receives every published event.
+    pub fn subscribe(&self, subscriber: Arc<dyn IEventSubscriber>) {
+        self.subscribers
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(subscriber);
     }
 }
 
@@ -20,8 +33,58 @@ impl<R: Runtime> IEventPublisher for TauriEventPublisher<R> {
         // Log emission for debugging
         tracing::debug!(target: "backend", event = event_name, "Publishing event");
 
+        // Notify backend subscribers first so caches are consistent
+        // by the time the frontend reacts to the event.
+        for subscriber in self.subscribers.read().unwrap_or_else(|e| e.into_inner()).iter() {
+            subscriber.on_event(&event);
+        }
+
         if let Err(e) = self.app_handle.emit(event_name, &event) {
             tracing::error!(target: "backend", "Failed to emit event {}: {:?}", event_name, e);
         }
     }
 }
+
+/// Publisher that keeps every event in memory instead of emitting it to the
+/// frontend, for exercising handlers without a running Tauri app.
+#[cfg(test)]
+#[derive(Default)]
+pub struct RecordingEventPublisher {
+    events: RwLock<Vec<DomainEvent>>,
+    subscribers: RwLock<Vec<Arc<dyn IEventSubscriber>>>,
+}
+
+#[cfg(test)]
+impl RecordingEventPublisher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an in-process subscriber, as with `TauriEventPublisher`.
+    pub fn subscribe(&self, subscriber: Arc<dyn IEventSubscriber>) {
+        self.subscribers
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(subscriber);
+    }
+
+    /// Events published so far, oldest first.
+    pub fn events(&self) -> Vec<DomainEvent> {
+        self.events.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Return the recorded events and start over with an empty list.
+    pub fn take(&self) -> Vec<DomainEvent> {
+        std::mem::take(&mut *self.events.write().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+#[cfg(test)]
+impl IEventPublisher for RecordingEventPublisher {
+    fn publish(&self, event: DomainEvent) {
+        for subscriber in self.subscribers.read().unwrap_or_else(|e| e.into_inner()).iter() {
+            subscriber.on_event(&event);
+        }
+        self.events.write().unwrap_or_else(|e| e.into_inner()).push(event);
+    }
+}
@@ -0,0 +1,224 @@
+//! Which local files the HTTP client and data archives may read and write for the webview.
+//!
+//! Multipart uploads, downloads and archive export/import name their files by
+//! path, so a compromised frontend could otherwise upload any file the user can
+//! read, or write over any file the user can write. A path must resolve (following symlinks) into
+//! the downloads directory, or be a file or directory the user picked through
+//! the dialog plugin, which adds it to the fs plugin scope. The app data
+//! directory is always refused, even inside a picked directory: it holds the
+//! master key, the database, backups and cookies.
+
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_fs::FsExt;
+
+use crate::error::AppError;
+
+#[derive(Clone)]
+pub struct FileScope {
+    /// Directories the webview may use; canonicalized when checked, as they may not exist yet
+    roots: Vec<PathBuf>,
+    /// Directories refused even if inside a root or picked
+    denied: Vec<PathBuf>,
+    /// Paths the user picked; `None` without the fs plugin
+    picked: Option<tauri::fs::Scope>,
+}
+
+impl FileScope {
+    pub fn new(roots: Vec<PathBuf>, denied: Vec<PathBuf>, picked: Option<tauri::fs::Scope>) -> Self {
+        Self { roots, denied, picked }
+    }
+
+    /// The downloads directory plus whatever the user picks, but never the app data directory.
+    pub fn for_app<R: Runtime>(app: &AppHandle<R>) -> Self {
+        let paths = app.path();
+        let roots = paths.download_dir().into_iter().collect();
+        let denied = paths.app_data_dir().into_iter().collect();
+        Self::new(roots, denied, app.try_fs_scope())
+    }
+
+    /// Resolve a file to upload or import.
+    pub fn check_read(&self, path: &str) -> Result<PathBuf, AppError> {
+        let resolved = std::fs::canonicalize(path)
+            .map_err(|e| AppError::Io(format!("Failed to open {}: {}", path, e)))?;
+        if !self.contains(&resolved) {
+            return Err(AppError::ScopeViolation(format!(
+                "{} is outside the directories the app may read",
+                path
+            )));
+        }
+        Ok(resolved)
+    }
+
+    /// Resolve a download or export destination. Its directory must exist; an existing
+    /// file is only replaced with `overwrite`, and never through a symlink.
+    pub fn check_write(&self, path: &str, overwrite: bool) -> Result<PathBuf, AppError> {
+        let given = Path::new(path);
+        let (Some(dir), Some(name)) = (given.parent(), given.file_name()) else {
+            return Err(AppError::Domain(format!("{} is not a file path", path)));
+        };
+        let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+        let resolved = std::fs::canonicalize(dir)
+            .map_err(|e| AppError::Io(format!("Failed to open {}: {}", dir.display(), e)))?
+            .join(name);
+        if !self.contains(&resolved) {
+            return Err(AppError::ScopeViolation(format!(
+                "{} is outside the directories the app may write",
+                path
+            )));
+        }
+
+        if let Ok(existing) = std::fs::symlink_metadata(&resolved) {
+            if !existing.is_file() {
+                return Err(AppError::Domain(format!("{} exists and is not a regular file", path)));
+            }
+            if !overwrite {
+                return Err(AppError::Domain(format!("{} already exists", path)));
+            }
+        }
+        Ok(resolved)
+    }
+
+    fn contains(&self, resolved: &Path) -> bool {
+        let canonical = |dir: &PathBuf| std::fs::canonicalize(dir).unwrap_or_else(|_| dir.clone());
+        if self.denied.iter().map(canonical).any(|dir| resolved.starts_with(dir)) {
+            return false;
+        }
+        self.roots
+            .iter()
+            .filter_map(|root| std::fs::canonicalize(root).ok())
+            .any(|root| resolved.starts_with(root))
+            || self.picked.as_ref().is_some_and(|scope| scope.is_allowed(resolved))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope(root: &Path) -> FileScope {
+        FileScope::new(vec![root.to_path_buf()], Vec::new(), None)
+    }
+
+    /// A root holding the app data directory, like a picked home directory
+    fn scope_with_app_data(root: &Path) -> (FileScope, PathBuf) {
+        let app_data = root.join("app-data");
+        std::fs::create_dir(&app_data).unwrap();
+        std::fs::write(app_data.join("master.key"), b"key").unwrap();
+        (FileScope::new(vec![root.to_path_buf()], vec![app_data.clone()], None), app_data)
+    }
+
+    #[test]
+    fn files_under_a_root_are_readable() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join("sub")).unwrap();
+        let file = root.path().join("sub/report.pdf");
+        std::fs::write(&file, b"%PDF").unwrap();
+
+        let resolved = scope(root.path()).check_read(&file.to_string_lossy()).unwrap();
+        assert_eq!(resolved, std::fs::canonicalize(&file).unwrap());
+    }
+
+    #[test]
+    fn files_elsewhere_are_refused() {
+        let root = tempfile::tempdir().unwrap();
+        let other = tempfile::tempdir().unwrap();
+        let file = other.path().join("id_rsa");
+        std::fs::write(&file, b"key").unwrap();
+
+        let result = scope(root.path()).check_read(&file.to_string_lossy());
+        assert!(matches!(result, Err(AppError::ScopeViolation(_))));
+
+        // Not through `..` either
+        let escaped = root.path().join("..").join(other.path().file_name().unwrap()).join("id_rsa");
+        let result = scope(root.path()).check_read(&escaped.to_string_lossy());
+        assert!(matches!(result, Err(AppError::ScopeViolation(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_out_of_a_root_are_refused() {
+        let root = tempfile::tempdir().unwrap();
+        let other = tempfile::tempdir().unwrap();
+        let file = other.path().join("id_rsa");
+        std::fs::write(&file, b"key").unwrap();
+        let link = root.path().join("innocent.txt");
+        std::os::unix::fs::symlink(&file, &link).unwrap();
+
+        let result = scope(root.path()).check_read(&link.to_string_lossy());
+        assert!(matches!(result, Err(AppError::ScopeViolation(_))));
+    }
+
+    #[test]
+    fn app_data_is_never_readable() {
+        let root = tempfile::tempdir().unwrap();
+        let (scope, app_data) = scope_with_app_data(root.path());
+
+        let result = scope.check_read(&app_data.join("master.key").to_string_lossy());
+        assert!(matches!(result, Err(AppError::ScopeViolation(_))));
+        // Not through a symlink either
+        #[cfg(unix)]
+        {
+            let link = root.path().join("photo.jpg");
+            std::os::unix::fs::symlink(app_data.join("master.key"), &link).unwrap();
+            assert!(matches!(scope.check_read(&link.to_string_lossy()), Err(AppError::ScopeViolation(_))));
+        }
+    }
+
+    #[test]
+    fn new_files_under_a_root_are_writable() {
+        let root = tempfile::tempdir().unwrap();
+        let target = root.path().join("setup.exe");
+
+        let resolved = scope(root.path()).check_write(&target.to_string_lossy(), false).unwrap();
+        assert_eq!(resolved, std::fs::canonicalize(root.path()).unwrap().join("setup.exe"));
+    }
+
+    #[test]
+    fn existing_files_need_overwrite() {
+        let root = tempfile::tempdir().unwrap();
+        let target = root.path().join("setup.exe");
+        std::fs::write(&target, b"old").unwrap();
+        let target = target.to_string_lossy();
+
+        assert!(matches!(scope(root.path()).check_write(&target, false), Err(AppError::Domain(_))));
+        assert!(scope(root.path()).check_write(&target, true).is_ok());
+        // Directories are never replaced
+        let result = scope(root.path()).check_write(&root.path().to_string_lossy(), true);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn writes_elsewhere_are_refused() {
+        let root = tempfile::tempdir().unwrap();
+        let other = tempfile::tempdir().unwrap();
+
+        let result = scope(root.path()).check_write(&other.path().join(".bashrc").to_string_lossy(), true);
+        assert!(matches!(result, Err(AppError::ScopeViolation(_))));
+        let escaped = root.path().join("..").join(other.path().file_name().unwrap()).join(".bashrc");
+        let result = scope(root.path()).check_write(&escaped.to_string_lossy(), true);
+        assert!(matches!(result, Err(AppError::ScopeViolation(_))));
+        assert!(scope(root.path()).check_write(&root.path().join("..").to_string_lossy(), true).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_are_not_written_through() {
+        let root = tempfile::tempdir().unwrap();
+        let other = tempfile::tempdir().unwrap();
+        let file = other.path().join(".bashrc");
+        std::fs::write(&file, b"rc").unwrap();
+        let link = root.path().join("notes.txt");
+        std::os::unix::fs::symlink(&file, &link).unwrap();
+
+        let result = scope(root.path()).check_write(&link.to_string_lossy(), true);
+        assert!(matches!(result, Err(AppError::Domain(_))));
+    }
+
+    #[test]
+    fn missing_files_are_io_errors() {
+        let root = tempfile::tempdir().unwrap();
+        let result = scope(root.path()).check_read(&root.path().join("nope").to_string_lossy());
+        assert!(matches!(result, Err(AppError::Io(_))));
+    }
+}
@@ -1,12 +1,73 @@
-use reqwest::{Client, Method, Url};
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use futures_util::StreamExt;
+use reqwest::header::{
+    HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, CACHE_CONTROL, CONTENT_RANGE, CONTENT_TYPE, ETAG,
+    IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_RANGE, LAST_MODIFIED, PRAGMA, RANGE, RETRY_AFTER,
+};
+use reqwest::multipart::{Form, Part};
+use reqwest::redirect::Policy;
+use reqwest::{Certificate, Client, Method, NoProxy, Proxy, RequestBuilder, Response, StatusCode, Url};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
 use std::time::Duration;
+use time::format_description::well_known::Rfc2822;
+use time::OffsetDateTime;
+use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+use crate::domain::downloads::{DownloadedFile, IFileDownloader, ProgressFn, TransferProgress};
+use crate::domain::events::IEventPublisher;
+use crate::domain::http::{
+    CacheMode, CacheStatus, CachedResponse, CircuitBreakerPolicy, CircuitBreakerStatus, ClientOptions,
+    HttpSettings, IHttpCacheRepository, IHttpClient, RedirectPolicy, RetryPolicy,
+};
 use crate::error::AppError;
+use crate::infra::circuit_breaker::CircuitBreakers;
+use crate::infra::cookie_jar::PersistentCookieJar;
+use crate::infra::file_scope::FileScope;
+use crate::infra::http_scope::{scope_error, HttpScope, ScopedResolver};
+
+/// Time allowed to establish a connection, independent of the request timeout
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Oldest cached responses beyond this are dropped
+const MAX_CACHE_ENTRIES: u32 = 1000;
+/// Larger responses are not cached
+const MAX_CACHED_BODY: usize = 10 * 1024 * 1024;
+/// Redirect limits clients are built for; others are rounded down to one of them
+const REDIRECT_LIMITS: [usize; 6] = [1, 2, 3, 5, 10, 20];
+/// Statuses cacheable by default (RFC 9110, 15.1)
+const CACHEABLE_STATUSES: [u16; 7] = [200, 203, 204, 300, 301, 404, 410];
 
 #[derive(Clone)]
 pub struct HttpClient {
-    inner: Client,
+    /// Replaced as a whole when the client options change
+    clients: Arc<Mutex<ClientPool>>,
+    /// Applied to requests without their own policies; updated when the settings change
+    settings: Arc<RwLock<HttpSettings>>,
+    circuits: Arc<CircuitBreakers>,
+    /// URLs requests may reach; fixed at startup
+    scope: Arc<HttpScope>,
+    /// Local files uploads may read
+    files: Arc<FileScope>,
+    /// Response cache; unset without a local database
+    cache: Arc<OnceLock<Arc<dyn IHttpCacheRepository>>>,
+}
+
+/// Clients built from the same options, one per redirect limit in `REDIRECT_LIMITS`
+/// and with or without cookies (reqwest only supports these per client)
+struct ClientPool {
+    options: ClientOptions,
+    scope: Arc<HttpScope>,
+    cookies: Arc<PersistentCookieJar>,
+    proxy: Option<Proxy>,
+    certificates: Vec<Certificate>,
+    clients: HashMap<(usize, bool), Client>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -14,76 +75,1208 @@ pub struct HttpRequest {
     pub method: String,
     pub url: String,
     pub headers: Option<HashMap<String, String>>,
-    pub body: Option<serde_json::Value>,
+    pub body: Option<HttpBody>,
     pub query: Option<HashMap<String, String>>,
+    /// Name of a stored secret to send as `Authorization: Bearer <secret>`.
+    /// Resolved in the backend, so the token never passes through the webview.
+    /// Only sent to the hosts in the secret's `secret_hosts_<name>` static config key.
+    pub auth_secret: Option<String>,
+    /// Total time allowed for the request, overriding `http_timeout_secs`
+    pub timeout_ms: Option<u64>,
+    /// Defaults to following up to 10 redirects
+    pub redirect: Option<RedirectPolicy>,
+    /// Replaces the global retry policy; omitted fields take their defaults
+    pub retry: Option<RetryPolicy>,
+    /// Replaces the global circuit breaker policy for this request
+    pub circuit_breaker: Option<CircuitBreakerPolicy>,
+    /// Defaults to `default` if `http_cache_enabled` is set, `no_store` otherwise
+    pub cache: Option<CacheMode>,
+    /// Send and store cookies; defaults to `true`. Without them the jar is neither read nor updated.
+    pub cookies: Option<bool>,
+}
+/// Request body, e.g. `{ "type": "text", "value": "hello" }`.
+/// A `Content-Type` header in the request overrides the default for the body type.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum HttpBody {
+    Json(serde_json::Value),
+    /// Sent as `text/plain`
+    Text(String),
+    /// Sent as `application/x-www-form-urlencoded`
+    Form(HashMap<String, String>),
+    Multipart(Vec<MultipartField>),
+    /// Base64-encoded raw bytes, sent as `application/octet-stream`
+    Bytes(String),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MultipartField {
+    Text {
+        name: String,
+        value: String,
+    },
+    /// Streams the file at `path`, which must be in the downloads directory or
+    /// picked by the user, and not in app data (see `FileScope`). The file name defaults
+    /// to the path's last component, the content type is guessed from its extension.
+    File {
+        name: String,
+        path: String,
+        file_name: Option<String>,
+        content_type: Option<String>,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct HttpResponse {
     pub status: u16,
     pub headers: HashMap<String, String>,
-    pub body: serde_json::Value,
+    pub body: HttpResponseBody,
+    #[serde(default)]
+    pub cache: CacheStatus,
+}
+
+/// Response body decoded by `Content-Type`, e.g. `{ "type": "json", "value": {...} }`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum HttpResponseBody {
+    Json(serde_json::Value),
+    Text(String),
+    /// Base64-encoded bytes (images, archives, anything not text)
+    Bytes(String),
+    Empty,
 }
 
 impl HttpClient {
-    pub fn new() -> Result<Self, AppError> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(30))
-            .user_agent("Tauri-React-Template/0.1.0")
-            .build()
-            .map_err(|e| AppError::Unknown(format!("Failed to build http client: {}", e)))?;
+    pub fn new(
+        scope: HttpScope,
+        files: FileScope,
+        cookies: Arc<PersistentCookieJar>,
+        publisher: Arc<dyn IEventPublisher>,
+    ) -> Result<Self, AppError> {
+        let scope = Arc::new(scope);
+        Ok(Self {
+            clients: Arc::new(Mutex::new(ClientPool::new(ClientOptions::default(), scope.clone(), cookies)?)),
+            settings: Arc::new(RwLock::new(HttpSettings::default())),
+            circuits: Arc::new(CircuitBreakers::new(publisher)),
+            scope,
+            files: Arc::new(files),
+            cache: Arc::new(OnceLock::new()),
+        })
+    }
 
-        Ok(Self { inner: client })
+    /// Called once the database is open; until then every request bypasses the cache.
+    pub fn set_cache(&self, cache: Arc<dyn IHttpCacheRepository>) {
+        let _ = self.cache.set(cache);
     }
 
-    pub async fn execute(&self, request: HttpRequest) -> Result<HttpResponse, AppError> {
+    /// Send the request, retrying transient failures as the retry policy allows.
+    /// GET responses are served from and stored in the cache as their headers allow.
+    pub async fn execute(&self, mut request: HttpRequest) -> Result<HttpResponse, AppError> {
         let method = Method::from_bytes(request.method.as_bytes())
             .map_err(|_| AppError::Unknown("Invalid HTTP method".to_string()))?;
-        
-        let url = Url::parse(&request.url)
+
+        let mut url = Url::parse(&request.url)
             .map_err(|_| AppError::Unknown("Invalid URL".to_string()))?;
 
-        let mut req_builder = self.inner.request(method, url);
+        // Add Query Params (sorted, so the cache key does not depend on map order)
+        if let Some(query) = request.query.take() {
+            let mut pairs: Vec<(String, String)> = query.into_iter().collect();
+            pairs.sort();
+            url.query_pairs_mut().extend_pairs(pairs);
+        }
+        self.scope.check(&url)?;
+
+        let cache = match self.cache.get() {
+            Some(cache) if self.cache_mode(&method, &request) != CacheMode::NoStore => cache.clone(),
+            cache => {
+                let response = self.send(&method, &url, &request, HeaderMap::new()).await?;
+                // A successful unsafe request makes the cached GET response stale (RFC 9111, 4.4)
+                if let Some(cache) = cache {
+                    let succeeded = response.status.is_success() || response.status.is_redirection();
+                    if !method.is_safe() && succeeded {
+                        if let Err(e) = cache.remove(Method::GET.as_str(), url.as_str()).await {
+                            warn!("Failed to invalidate cached {}: {:?}", url, e);
+                        }
+                    }
+                }
+                return Ok(response.into_response(CacheStatus::Bypass));
+            }
+        };
+        self.execute_cached(cache.as_ref(), method, url, request).await
+    }
+
+    async fn execute_cached(
+        &self,
+        cache: &dyn IHttpCacheRepository,
+        method: Method,
+        url: Url,
+        request: HttpRequest,
+    ) -> Result<HttpResponse, AppError> {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let stored = match cache.find(method.as_str(), url.as_str()).await {
+            // Stored for a request with other values of the headers named by `Vary`
+            Ok(stored) => stored.filter(|stored| stored.vary == vary_values(stored.vary.keys(), &request)),
+            Err(e) => {
+                warn!("Failed to read the HTTP cache: {:?}", e);
+                None
+            }
+        };
+
+        if let Some(stored) = &stored {
+            if stored.expires_at > now && self.cache_mode(&method, &request) == CacheMode::Default {
+                return Ok(cached_response(stored, CacheStatus::Hit));
+            }
+        }
+
+        // Stale (or revalidation requested): ask whether the stored copy is still current
+        let mut conditional = HeaderMap::new();
+        if let Some(stored) = &stored {
+            if let Some(value) = stored.etag.as_deref().and_then(|v| HeaderValue::from_str(v).ok()) {
+                conditional.insert(IF_NONE_MATCH, value);
+            }
+            if let Some(value) = stored.last_modified.as_deref().and_then(|v| HeaderValue::from_str(v).ok()) {
+                conditional.insert(IF_MODIFIED_SINCE, value);
+            }
+        }
+        let response = self.send(&method, &url, &request, conditional).await?;
 
-        // Add Query Params
-        if let Some(query) = request.query {
-            req_builder = req_builder.query(&query);
+        if response.status == StatusCode::NOT_MODIFIED {
+            if let Some(mut stored) = stored {
+                // The 304 carries the current caching headers; its length is not the body's (RFC 9111, 3.2)
+                stored.headers.extend(response.headers.into_iter().filter(|(name, _)| name != "content-length"));
+                match freshness(&stored.headers, now) {
+                    Some(lifetime) => {
+                        stored.expires_at = now + lifetime;
+                        self.store(cache, &stored).await;
+                    }
+                    None => self.forget(cache, &stored.url).await,
+                }
+                return Ok(cached_response(&stored, CacheStatus::Revalidated));
+            }
         }
 
+        match cache_entry(&url, &request, &response, now) {
+            Some(entry) => self.store(cache, &entry).await,
+            None if stored.is_some() => self.forget(cache, url.as_str()).await,
+            None => {}
+        }
+        Ok(response.into_response(CacheStatus::Miss))
+    }
+
+    /// `NoStore` for requests the cache cannot answer (not a GET, or with a body or their own validators)
+    fn cache_mode(&self, method: &Method, request: &HttpRequest) -> CacheMode {
+        let header = |name: &HeaderName| request_header(request, name.as_str()).unwrap_or_default().to_ascii_lowercase();
+        let uncacheable = *method != Method::GET
+            || request.body.is_some()
+            || header(&CACHE_CONTROL).contains("no-store")
+            || request_header(request, IF_NONE_MATCH.as_str()).is_some()
+            || request_header(request, IF_MODIFIED_SINCE.as_str()).is_some();
+        if uncacheable {
+            return CacheMode::NoStore;
+        }
+
+        let mode = request.cache.unwrap_or_else(|| {
+            let enabled = self.settings.read().unwrap_or_else(|e| e.into_inner()).cache_enabled;
+            if enabled { CacheMode::Default } else { CacheMode::NoStore }
+        });
+        let no_cache = header(&CACHE_CONTROL).contains("no-cache") || header(&PRAGMA).contains("no-cache");
+        if mode == CacheMode::Default && no_cache { CacheMode::NoCache } else { mode }
+    }
+
+    async fn store(&self, cache: &dyn IHttpCacheRepository, entry: &CachedResponse) {
+        let max_bytes = self.settings.read().unwrap_or_else(|e| e.into_inner()).cache_max_bytes;
+        if entry.body.len() as u64 > max_bytes {
+            return self.forget(cache, &entry.url).await;
+        }
+        if let Err(e) = cache.store(entry, MAX_CACHE_ENTRIES, max_bytes).await {
+            warn!("Failed to cache {}: {:?}", entry.url, e);
+        }
+    }
+
+    async fn forget(&self, cache: &dyn IHttpCacheRepository, url: &str) {
+        if let Err(e) = cache.remove(Method::GET.as_str(), url).await {
+            warn!("Failed to remove cached {}: {:?}", url, e);
+        }
+    }
+
+    /// Send the request and read the response, retrying transient failures.
+    async fn send(&self, method: &Method, url: &Url, request: &HttpRequest, extra_headers: HeaderMap) -> Result<RawResponse, AppError> {
+        let host = format!(
+            "{}:{}",
+            url.host_str().unwrap_or_default(),
+            url.port_or_known_default().unwrap_or_default()
+        );
+        let (timeout, retry, breaker) = {
+            let settings = self.settings.read().unwrap_or_else(|e| e.into_inner());
+            (
+                request
+                    .timeout_ms
+                    .map(Duration::from_millis)
+                    .unwrap_or(Duration::from_secs(settings.client.timeout_secs)),
+                request.retry.clone().unwrap_or_else(|| settings.retry.clone()),
+                request.circuit_breaker.clone().unwrap_or_else(|| settings.circuit_breaker.clone()),
+            )
+        };
+        let idempotent = retry.retry_non_idempotent || is_idempotent(method);
+
+        let mut attempt = 0;
+        loop {
+            // Rebuilt for every attempt, since a sent body (e.g. a streamed file) is consumed
+            let req_builder = self
+                .build(method.clone(), url.clone(), request)
+                .await?
+                .headers(extra_headers.clone())
+                .timeout(timeout);
+            self.circuits.acquire(&host, &breaker)?;
+
+            let (result, delay) = match req_builder.send().await {
+                Ok(response) => {
+                    if response.status().is_server_error() {
+                        self.circuits.record_failure(&host, &breaker);
+                    } else {
+                        self.circuits.record_success(&host);
+                    }
+                    let delay = (idempotent && retry.retry_statuses.contains(&response.status().as_u16()))
+                        .then(|| retry_after(response.headers()).unwrap_or_else(|| backoff(&retry, attempt)));
+                    (Ok(response), delay)
+                }
+                Err(e) => {
+                    // Refused before anything was sent; not the host's fault
+                    if let Some(violation) = scope_error(&e) {
+                        return Err(violation);
+                    }
+                    self.circuits.record_failure(&host, &breaker);
+                    // Nothing reached the server if the connection failed, so any method may be retried
+                    let transient = e.is_connect() || (idempotent && (e.is_timeout() || e.is_request()));
+                    (Err(e), transient.then(|| backoff(&retry, attempt)))
+                }
+            };
+
+            match delay {
+                Some(delay) if attempt < retry.max_retries && delay <= Duration::from_millis(retry.max_delay_ms) => {
+                    attempt += 1;
+                    let outcome = match &result {
+                        Ok(response) => format!("status {}", response.status()),
+                        Err(e) => e.to_string(),
+                    };
+                    warn!(
+                        "{} {} failed ({}), retry {}/{} in {}ms",
+                        method, host, outcome, attempt, retry.max_retries, delay.as_millis()
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                _ => {
+                    let response = result.map_err(request_error)?;
+                    return RawResponse::read(response).await;
+                }
+            }
+        }
+    }
+
+    async fn build(&self, method: Method, url: Url, request: &HttpRequest) -> Result<RequestBuilder, AppError> {
+        let max_redirects = match request.redirect.unwrap_or_default() {
+            RedirectPolicy::Follow { max } => max,
+            RedirectPolicy::None => 0,
+        };
+        let cookies = request.cookies.unwrap_or(true);
+        let mut req_builder = self.client(max_redirects, cookies)?.request(method, url);
+
         // Add Headers
-        if let Some(headers) = request.headers {
+        let mut has_content_type = false;
+        if let Some(headers) = &request.headers {
             for (k, v) in headers {
+                has_content_type |= k.eq_ignore_ascii_case(CONTENT_TYPE.as_str());
                 req_builder = req_builder.header(k, v);
             }
         }
 
-        // Add Body (JSON)
-        if let Some(body) = request.body {
-            req_builder = req_builder.json(&body);
+        // Add Body
+        if let Some(body) = &request.body {
+            req_builder = with_body(req_builder, body, has_content_type, &self.files).await?;
         }
 
-        // Execute
-        let response = req_builder.send().await
-            .map_err(|e| AppError::Io(format!("Request failed: {}", e)))?;
+        Ok(req_builder)
+    }
+
+    fn client(&self, max_redirects: usize, cookies: bool) -> Result<Client, AppError> {
+        self.clients.lock().unwrap_or_else(|e| e.into_inner()).client(max_redirects, cookies)
+    }
+}
+
+impl ClientPool {
+    /// Load the proxy and certificates. Fails if the options are invalid.
+    fn new(options: ClientOptions, scope: Arc<HttpScope>, cookies: Arc<PersistentCookieJar>) -> Result<Self, AppError> {
+        let proxy = match &options.proxy_url {
+            Some(url) => {
+                let proxy = Proxy::all(url)
+                    .map_err(|e| AppError::Domain(format!("Invalid proxy URL {}: {}", url, e)))?;
+                Some(proxy.no_proxy(options.no_proxy.as_deref().and_then(NoProxy::from_string)))
+            }
+            None => None,
+        };
+
+        let certificates = match &options.ca_bundle_path {
+            Some(path) => {
+                let pem = std::fs::read(path)
+                    .map_err(|e| AppError::Io(format!("Failed to read CA bundle {}: {}", path, e)))?;
+                Certificate::from_pem_bundle(&pem)
+                    .map_err(|e| AppError::Domain(format!("Invalid CA bundle {}: {}", path, e)))?
+            }
+            None => Vec::new(),
+        };
+
+        let mut pool = Self { options, scope, cookies, proxy, certificates, clients: HashMap::new() };
+        // Build the default client right away, so bad options fail here rather than on the next request
+        pool.client(RedirectPolicy::DEFAULT_MAX, true)?;
+        Ok(pool)
+    }
+
+    fn client(&mut self, max_redirects: usize, cookies: bool) -> Result<Client, AppError> {
+        let max_redirects = redirect_limit(max_redirects);
+        if let Some(client) = self.clients.get(&(max_redirects, cookies)) {
+            return Ok(client.clone());
+        }
+
+        // Every hop must stay inside the scope
+        let redirect = match max_redirects {
+            0 => Policy::none(),
+            max => {
+                let scope = self.scope.clone();
+                Policy::custom(move |attempt| {
+                    if attempt.previous().len() > max {
+                        attempt.error(format!("Too many redirects (more than {})", max))
+                    } else if let Err(violation) = scope.check(attempt.url()) {
+                        attempt.error(violation)
+                    } else {
+                        attempt.follow()
+                    }
+                })
+            }
+        };
+        // No total timeout here: downloads may take any time as long as data keeps arriving
+        let mut builder = Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .read_timeout(Duration::from_secs(self.options.timeout_secs))
+            .user_agent(&self.options.user_agent)
+            .redirect(redirect);
+        if cookies {
+            builder = builder.cookie_provider(self.cookies.clone());
+        }
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+        if self.scope.block_private {
+            // A proxy on the local network is configured by the user, not the webview
+            let proxy_host = self
+                .options
+                .proxy_url
+                .as_deref()
+                .and_then(|url| Url::parse(url).ok())
+                .and_then(|url| url.host_str().map(str::to_string));
+            builder = builder.dns_resolver(ScopedResolver::new(proxy_host));
+        }
+        for certificate in &self.certificates {
+            builder = builder.add_root_certificate(certificate.clone());
+        }
+
+        let client = builder
+            .build()
+            .map_err(|e| AppError::Unknown(format!("Failed to build http client: {}", e)))?;
+        self.clients.insert((max_redirects, cookies), client.clone());
+        Ok(client)
+    }
+}
+
+impl IHttpClient for HttpClient {
+    fn apply_settings(&self, settings: HttpSettings) -> Result<(), AppError> {
+        let options = settings.client.clone();
+        let rebuild = {
+            let mut current = self.settings.write().unwrap_or_else(|e| e.into_inner());
+            let rebuild = current.client != options;
+            // Policies apply even if the new client options turn out to be invalid
+            current.retry = settings.retry;
+            current.circuit_breaker = settings.circuit_breaker;
+            rebuild
+        };
+        if !rebuild {
+            return Ok(());
+        }
+
+        let cookies = self.clients.lock().unwrap_or_else(|e| e.into_inner()).cookies.clone();
+        let pool = ClientPool::new(options.clone(), self.scope.clone(), cookies)?;
+        *self.clients.lock().unwrap_or_else(|e| e.into_inner()) = pool;
+        self.settings.write().unwrap_or_else(|e| e.into_inner()).client = options;
+        info!("HTTP client rebuilt with the new settings");
+        Ok(())
+    }
+
+    fn circuit_breakers(&self) -> Vec<CircuitBreakerStatus> {
+        self.circuits.statuses()
+    }
+}
+
+/// A failed send as an `AppError`, keeping scope violations apart from network errors
+fn request_error(err: reqwest::Error) -> AppError {
+    scope_error(&err).unwrap_or_else(|| AppError::Io(format!("Request failed: {}", err)))
+}
+
+/// A response read in full, before it is decoded for the frontend
+struct RawResponse {
+    status: StatusCode,
+    /// Header names are lowercase
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+impl RawResponse {
+    async fn read(response: Response) -> Result<Self, AppError> {
+        let status = response.status();
 
-        let status = response.status().as_u16();
-        
         // Convert headers to HashMap
-        let mut res_headers = HashMap::new();
+        let mut headers = HashMap::new();
         for (k, v) in response.headers() {
             if let Ok(val_str) = v.to_str() {
-                res_headers.insert(k.to_string(), val_str.to_string());
+                headers.insert(k.to_string(), val_str.to_string());
             }
         }
 
-        // Parse JSON body (if possible, otherwise text/null)
-        let body_json: serde_json::Value = response.json().await
-            .unwrap_or(serde_json::Value::Null);
+        let body = response.bytes().await
+            .map_err(|e| AppError::Io(format!("Failed to read response body: {}", e)))?;
+
+        Ok(Self { status, headers, body: body.to_vec() })
+    }
+
+    fn into_response(self, cache: CacheStatus) -> HttpResponse {
+        HttpResponse {
+            status: self.status.as_u16(),
+            body: decode_body(media_type(&self.headers).as_deref(), &self.body),
+            headers: self.headers,
+            cache,
+        }
+    }
+}
+
+fn cached_response(stored: &CachedResponse, cache: CacheStatus) -> HttpResponse {
+    HttpResponse {
+        status: stored.status,
+        headers: stored.headers.clone(),
+        body: decode_body(media_type(&stored.headers).as_deref(), &stored.body),
+        cache,
+    }
+}
+
+/// The cache entry for a GET response, if it may be stored (RFC 9111, 3)
+fn cache_entry(url: &Url, request: &HttpRequest, response: &RawResponse, now: i64) -> Option<CachedResponse> {
+    if !CACHEABLE_STATUSES.contains(&response.status.as_u16()) || response.body.len() > MAX_CACHED_BODY {
+        return None;
+    }
+
+    // Shared credentials only if the server says the response may be stored (RFC 9111, 3.5)
+    let directives = cache_control(response.headers.get("cache-control").map(String::as_str).unwrap_or_default());
+    let authorized = request_header(request, AUTHORIZATION.as_str()).is_some();
+    if authorized && !directives.contains_key("private") && !directives.contains_key("public") {
+        return None;
+    }
+
+    let vary: Vec<String> = response
+        .headers
+        .get("vary")
+        .map(|value| value.split(',').map(|name| name.trim().to_ascii_lowercase()).filter(|name| !name.is_empty()).collect())
+        .unwrap_or_default();
+    if vary.iter().any(|name| name == "*") {
+        return None;
+    }
 
-        Ok(HttpResponse {
-            status,
-            headers: res_headers,
-            body: body_json,
+    let lifetime = freshness(&response.headers, now)?;
+    Some(CachedResponse {
+        method: Method::GET.to_string(),
+        url: url.to_string(),
+        status: response.status.as_u16(),
+        headers: response.headers.clone(),
+        body: response.body.clone(),
+        vary: vary_values(vary.iter(), request),
+        etag: response.headers.get("etag").cloned(),
+        last_modified: response.headers.get("last-modified").cloned(),
+        expires_at: now + lifetime,
+    })
+}
+
+/// Seconds a response stays fresh (RFC 9111, 4.2.1), or `None` if it is not worth storing:
+/// `no-store`, or nothing to go on (no freshness information and no validator).
+fn freshness(headers: &HashMap<String, String>, now: i64) -> Option<i64> {
+    let directives = cache_control(headers.get("cache-control").map(String::as_str).unwrap_or_default());
+    if directives.contains_key("no-store") {
+        return None;
+    }
+    let has_validator = headers.contains_key("etag") || headers.contains_key("last-modified");
+
+    let age = headers.get("age").and_then(|v| v.trim().parse::<i64>().ok()).unwrap_or(0);
+    let max_age = directives.get("max-age").and_then(|v| v.as_deref()?.parse::<i64>().ok());
+    let lifetime = if directives.contains_key("no-cache") {
+        Some(0)
+    } else if let Some(max_age) = max_age {
+        Some(max_age - age)
+    } else if let Some(expires) = headers.get("expires") {
+        // An invalid Expires (e.g. "0") means already expired
+        let date = headers.get("date").and_then(|v| http_date(v)).unwrap_or(now);
+        Some(http_date(expires).map_or(0, |at| at - date) - age)
+    } else {
+        None
+    };
+
+    let lifetime = lifetime.unwrap_or(0).max(0);
+    (lifetime > 0 || has_validator).then_some(lifetime)
+}
+
+/// `Cache-Control` directives by lowercase name, with unquoted values
+fn cache_control(value: &str) -> HashMap<String, Option<String>> {
+    value
+        .split(',')
+        .filter_map(|directive| {
+            let (name, value) = match directive.split_once('=') {
+                Some((name, value)) => (name, Some(value.trim().trim_matches('"').to_string())),
+                None => (directive, None),
+            };
+            let name = name.trim().to_ascii_lowercase();
+            (!name.is_empty()).then_some((name, value))
         })
+        .collect()
+}
+
+/// The request's values of the headers named by `Vary` (empty if absent)
+fn vary_values<'a>(names: impl Iterator<Item = &'a String>, request: &HttpRequest) -> HashMap<String, String> {
+    names
+        .map(|name| (name.clone(), request_header(request, name).unwrap_or_default().to_string()))
+        .collect()
+}
+
+fn request_header<'a>(request: &'a HttpRequest, name: &str) -> Option<&'a str> {
+    request
+        .headers
+        .as_ref()?
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+/// Unix seconds of an HTTP date (`Sun, 06 Nov 1994 08:49:37 GMT`)
+fn http_date(value: &str) -> Option<i64> {
+    OffsetDateTime::parse(value.trim(), &Rfc2822).ok().map(|at| at.unix_timestamp())
+}
+
+/// Methods whose repetition has no additional effect (RFC 9110, 9.2.2)
+fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::PUT | Method::DELETE | Method::TRACE
+    )
+}
+
+/// Exponential backoff with jitter: a random delay between half and all of
+/// `base_delay_ms * 2^attempt`, capped at `max_delay_ms`.
+fn backoff(policy: &RetryPolicy, attempt: u32) -> Duration {
+    let ceiling = policy
+        .base_delay_ms
+        .saturating_mul(1u64 << attempt.min(32))
+        .min(policy.max_delay_ms);
+    Duration::from_millis(ceiling / 2 + fastrand::u64(0..=ceiling - ceiling / 2))
+}
+
+/// The largest of `REDIRECT_LIMITS` not above `max`, or 0
+fn redirect_limit(max: usize) -> usize {
+    REDIRECT_LIMITS.into_iter().rev().find(|&limit| limit <= max).unwrap_or(0)
+}
+
+/// `Retry-After` as delay seconds or an HTTP date
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let remaining = http_date(value)? - OffsetDateTime::now_utc().unix_timestamp();
+    Some(Duration::from_secs(remaining.max(0) as u64))
+}
+
+#[async_trait]
+impl IFileDownloader for HttpClient {
+    async fn download(
+        &self,
+        url: &str,
+        path: &str,
+        expected_sha256: Option<&str>,
+        overwrite: bool,
+        progress: &ProgressFn<'_>,
+        cancel: CancellationToken,
+    ) -> Result<DownloadedFile, AppError> {
+        // Refuse before transferring anything; `finish` checks again before the rename
+        let target = self.files.check_write(path, overwrite)?;
+        let partial = format!("{}.part", target.to_string_lossy());
+        let result = match self.transfer(url, &partial, &TransferProgress::default(), progress, cancel).await {
+            Ok(_) => self.finish(&partial, path, expected_sha256, overwrite).await,
+            Err(e) => Err(e),
+        };
+        if result.is_err() {
+            let _ = fs::remove_file(&partial).await;
+        }
+        result
+    }
+
+    fn check_target(&self, path: &str, overwrite: bool) -> Result<PathBuf, AppError> {
+        self.files.check_write(path, overwrite)
+    }
+
+    async fn transfer(
+        &self,
+        url: &str,
+        partial: &str,
+        from: &TransferProgress,
+        progress: &ProgressFn<'_>,
+        cancel: CancellationToken,
+    ) -> Result<TransferProgress, AppError> {
+        let parsed = Url::parse(url)
+            .map_err(|_| AppError::Unknown("Invalid URL".to_string()))?;
+        self.scope.check(&parsed)?;
+
+        // Without a validator the server could splice two versions of the file
+        let validator = from.etag.as_deref().or(from.last_modified.as_deref());
+        let offset = if validator.is_some() { from.bytes } else { 0 };
+
+        let mut request = self.client(RedirectPolicy::DEFAULT_MAX, true)?.get(parsed);
+        if let (true, Some(validator)) = (offset > 0, validator) {
+            request = request
+                .header(RANGE, format!("bytes={}-", offset))
+                .header(IF_RANGE, validator);
+        }
+
+        let response = tokio::select! {
+            response = request.send() => response.map_err(request_error)?,
+            _ = cancel.cancelled() => return Err(cancelled()),
+        };
+
+        let status = response.status();
+        if offset > 0 && status == StatusCode::RANGE_NOT_SATISFIABLE {
+            // Complete only if the partial file is exactly as long as the resource
+            let total = from.total.or_else(|| unsatisfied_range_total(response.headers()));
+            if total == Some(offset) {
+                return Ok(TransferProgress { bytes: offset, total, ..from.clone() });
+            }
+            warn!("Partial download of {} does not match the resource, starting over", url);
+            return self.transfer(url, partial, &TransferProgress::default(), progress, cancel).await;
+        }
+        if !status.is_success() {
+            return Err(AppError::Io(format!("Download failed with status {}", status)));
+        }
+
+        // 200 instead of 206: the range was ignored or the resource changed, start over
+        let resumed = offset > 0 && status == StatusCode::PARTIAL_CONTENT;
+        let start = if resumed { offset } else { 0 };
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v: &HeaderValue| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let mut current = TransferProgress {
+            bytes: start,
+            total: response.content_length().map(|len| start + len),
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        };
+
+        let mut file = if resumed {
+            fs::OpenOptions::new().append(true).open(partial).await?
+        } else {
+            fs::File::create(partial).await?
+        };
+        progress(&current);
+
+        let mut stream = response.bytes_stream();
+        loop {
+            let chunk = tokio::select! {
+                chunk = stream.next() => chunk,
+                _ = cancel.cancelled() => {
+                    file.flush().await?;
+                    return Err(cancelled());
+                }
+            };
+            let Some(chunk) = chunk else { break };
+            let chunk = chunk.map_err(|e| AppError::Io(format!("Download interrupted: {}", e)))?;
+
+            file.write_all(&chunk).await?;
+            current.bytes += chunk.len() as u64;
+            progress(&current);
+        }
+        file.flush().await?;
+        file.sync_all().await?;
+
+        Ok(current)
+    }
+
+    async fn finish(
+        &self,
+        partial: &str,
+        path: &str,
+        expected_sha256: Option<&str>,
+        overwrite: bool,
+    ) -> Result<DownloadedFile, AppError> {
+        let target = self.files.check_write(path, overwrite)?;
+        let file = hash_file(partial).await?;
+        if let Some(expected) = expected_sha256 {
+            if !file.sha256.eq_ignore_ascii_case(expected.trim()) {
+                let _ = fs::remove_file(partial).await;
+                return Err(AppError::Domain(format!(
+                    "Checksum mismatch: expected {}, got {}",
+                    expected, file.sha256
+                )));
+            }
+        }
+
+        fs::rename(partial, target).await?;
+        Ok(file)
+    }
+}
+
+/// Length of the resource from the `Content-Range: bytes */<length>` of a 416 response
+fn unsatisfied_range_total(headers: &HeaderMap) -> Option<u64> {
+    let value = headers.get(CONTENT_RANGE)?.to_str().ok()?;
+    value.trim().strip_prefix("bytes */")?.parse().ok()
+}
+
+async fn hash_file(path: &str) -> Result<DownloadedFile, AppError> {
+    let mut file = fs::File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut bytes = 0u64;
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        bytes += read as u64;
+    }
+
+    Ok(DownloadedFile {
+        bytes,
+        sha256: format!("{:x}", hasher.finalize()),
+    })
+}
+
+fn cancelled() -> AppError {
+    AppError::Domain("Download was cancelled".to_string())
+}
+
+/// Attach the body. The default Content-Type is only set if the request has none.
+async fn with_body(
+    builder: RequestBuilder,
+    body: &HttpBody,
+    has_content_type: bool,
+    files: &FileScope,
+) -> Result<RequestBuilder, AppError> {
+    let default_type = |builder: RequestBuilder, value: &str| {
+        if has_content_type { builder } else { builder.header(CONTENT_TYPE, value) }
+    };
+
+    Ok(match body {
+        HttpBody::Json(value) => builder.json(value),
+        HttpBody::Text(text) => default_type(builder, "text/plain; charset=utf-8").body(text.clone()),
+        HttpBody::Form(fields) => builder.form(fields),
+        HttpBody::Multipart(fields) => builder.multipart(multipart_form(fields, files).await?),
+        HttpBody::Bytes(encoded) => {
+            let bytes = BASE64
+                .decode(encoded.trim())
+                .map_err(|e| AppError::Domain(format!("Request body is not valid base64: {}", e)))?;
+            default_type(builder, "application/octet-stream").body(bytes)
+        }
+    })
+}
+
+async fn multipart_form(fields: &[MultipartField], files: &FileScope) -> Result<Form, AppError> {
+    let mut form = Form::new();
+    for field in fields {
+        form = match field {
+            MultipartField::Text { name, value } => form.text(name.clone(), value.clone()),
+            MultipartField::File { name, path, file_name, content_type } => {
+                // Open the resolved path, so a symlink swapped in after the check is not followed
+                let resolved = files.check_read(path)?;
+                let mut part = Part::file(&resolved)
+                    .await
+                    .map_err(|e| AppError::Io(format!("Failed to open {}: {}", path, e)))?;
+                // Named after the path as given, not the file a symlink points to
+                let file_name = file_name
+                    .clone()
+                    .or_else(|| Path::new(path).file_name().map(|n| n.to_string_lossy().to_string()));
+                if let Some(file_name) = file_name {
+                    part = part.file_name(file_name);
+                }
+                if let Some(content_type) = content_type {
+                    part = part
+                        .mime_str(content_type)
+                        .map_err(|_| AppError::Domain(format!("Invalid content type {}", content_type)))?;
+                }
+                form.part(name.clone(), part)
+            }
+        };
+    }
+    Ok(form)
+}
+
+/// Media type without parameters, lowercased (`text/html; charset=utf-8` -> `text/html`).
+fn media_type(headers: &HashMap<String, String>) -> Option<String> {
+    let value = headers.get(CONTENT_TYPE.as_str())?;
+    let media_type = value.split(';').next()?.trim().to_ascii_lowercase();
+    (!media_type.is_empty()).then_some(media_type)
+}
+
+fn decode_body(content_type: Option<&str>, bytes: &[u8]) -> HttpResponseBody {
+    if bytes.is_empty() {
+        return HttpResponseBody::Empty;
+    }
+
+    let is_json = content_type.is_some_and(|ct| ct == "application/json" || ct.ends_with("+json"));
+    if is_json {
+        if let Ok(value) = serde_json::from_slice(bytes) {
+            return HttpResponseBody::Json(value);
+        }
+    }
+
+    // Without a Content-Type, anything that is valid UTF-8 is treated as text
+    let is_text = content_type.is_none_or(|ct| {
+        is_json
+            || ct.starts_with("text/")
+            || ct.ends_with("+xml")
+            || matches!(
+                ct,
+                "application/xml" | "application/javascript" | "application/x-www-form-urlencoded"
+            )
+    });
+    if is_text {
+        if let Ok(text) = std::str::from_utf8(bytes) {
+            return HttpResponseBody::Text(text.to_string());
+        }
+    }
+
+    HttpResponseBody::Bytes(BASE64.encode(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::infra::event_publisher::RecordingEventPublisher;
+
+    #[test]
+    fn empty_bodies_decode_to_empty() {
+        assert!(matches!(decode_body(Some("application/json"), b""), HttpResponseBody::Empty));
+        assert!(matches!(decode_body(None, b""), HttpResponseBody::Empty));
+    }
+
+    #[test]
+    fn json_types_decode_to_json() {
+        for content_type in ["application/json", "application/problem+json"] {
+            match decode_body(Some(content_type), br#"{"ok":true}"#) {
+                HttpResponseBody::Json(value) => assert_eq!(value, json!({ "ok": true })),
+                other => panic!("{} decoded to {:?}", content_type, other),
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_json_falls_back_to_text() {
+        assert!(matches!(
+            decode_body(Some("application/json"), b"{not json"),
+            HttpResponseBody::Text(text) if text == "{not json"
+        ));
+    }
+
+    #[test]
+    fn text_types_decode_to_text() {
+        for content_type in ["text/html", "application/xml", "image/svg+xml", "application/x-www-form-urlencoded"] {
+            assert!(
+                matches!(decode_body(Some(content_type), b"<a/>"), HttpResponseBody::Text(_)),
+                "{} should decode to text",
+                content_type
+            );
+        }
+        // Without a Content-Type, valid UTF-8 is text
+        assert!(matches!(decode_body(None, "héllo".as_bytes()), HttpResponseBody::Text(text) if text == "héllo"));
+    }
+
+    #[test]
+    fn binary_decodes_to_base64() {
+        let png = [0x89, b'P', b'N', b'G', 0xff];
+        assert!(matches!(decode_body(Some("image/png"), &png), HttpResponseBody::Bytes(b) if b == BASE64.encode(png)));
+        // Text bytes under a binary type stay bytes
+        assert!(matches!(decode_body(Some("application/octet-stream"), b"abc"), HttpResponseBody::Bytes(_)));
+        // Invalid UTF-8 under a text type too
+        assert!(matches!(decode_body(Some("text/plain"), &png), HttpResponseBody::Bytes(_)));
+        assert!(matches!(decode_body(None, &png), HttpResponseBody::Bytes(_)));
+    }
+
+    #[test]
+    fn media_type_drops_parameters() {
+        let headers = HashMap::from([("content-type".to_string(), "Text/HTML; charset=utf-8".to_string())]);
+        assert_eq!(media_type(&headers).as_deref(), Some("text/html"));
+        assert_eq!(media_type(&HashMap::new()), None);
+    }
+
+    fn headers(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn cache_control_parses_directives() {
+        let directives = cache_control(r#"Max-Age=60, no-cache="set-cookie", private,, "#);
+        assert_eq!(directives.get("max-age"), Some(&Some("60".to_string())));
+        assert_eq!(directives.get("no-cache"), Some(&Some("set-cookie".to_string())));
+        assert_eq!(directives.get("private"), Some(&None));
+        assert_eq!(directives.len(), 3);
+        assert!(cache_control("").is_empty());
+    }
+
+    #[test]
+    fn freshness_follows_max_age_then_expires() {
+        let now = 1_700_000_000;
+        assert_eq!(freshness(&headers(&[("cache-control", "max-age=60")]), now), Some(60));
+        // Age counts against the lifetime
+        assert_eq!(freshness(&headers(&[("cache-control", "max-age=60"), ("age", "20")]), now), Some(40));
+        // max-age wins over Expires
+        let expires = headers(&[
+            ("cache-control", "public, max-age=5"),
+            ("date", "Sun, 06 Nov 1994 08:49:37 GMT"),
+            ("expires", "Sun, 06 Nov 1994 09:49:37 GMT"),
+        ]);
+        assert_eq!(freshness(&expires, now), Some(5));
+        // Expires is relative to Date, not to the local clock
+        let expires = headers(&[("date", "Sun, 06 Nov 1994 08:49:37 GMT"), ("expires", "Sun, 06 Nov 1994 09:49:37 GMT")]);
+        assert_eq!(freshness(&expires, now), Some(3600));
+    }
+
+    #[test]
+    fn freshness_without_lifetime_needs_a_validator() {
+        let now = 1_700_000_000;
+        assert_eq!(freshness(&HashMap::new(), now), None);
+        assert_eq!(freshness(&headers(&[("etag", "\"v1\"")]), now), Some(0));
+        assert_eq!(freshness(&headers(&[("last-modified", "Sun, 06 Nov 1994 08:49:37 GMT")]), now), Some(0));
+        // Stale or invalid lifetimes count as none
+        assert_eq!(freshness(&headers(&[("expires", "0")]), now), None);
+        assert_eq!(freshness(&headers(&[("cache-control", "max-age=10"), ("age", "30")]), now), None);
+        assert_eq!(freshness(&headers(&[("cache-control", "no-cache, max-age=60"), ("etag", "\"v1\"")]), now), Some(0));
+        assert_eq!(freshness(&headers(&[("cache-control", "no-store, max-age=60")]), now), None);
+    }
+
+    fn get(request_headers: &[(&str, &str)]) -> HttpRequest {
+        serde_json::from_value(json!({
+            "method": "GET",
+            "url": "https://api.example.com/items",
+            "headers": headers(request_headers),
+        }))
+        .unwrap()
+    }
+
+    fn ok(response_headers: &[(&str, &str)]) -> RawResponse {
+        RawResponse { status: StatusCode::OK, headers: headers(response_headers), body: b"[]".to_vec() }
+    }
+
+    #[test]
+    fn authorized_responses_are_only_stored_when_marked() {
+        let url = Url::parse("https://api.example.com/items").unwrap();
+        let now = 1_700_000_000;
+        let authorized = get(&[("Authorization", "Bearer token")]);
+
+        assert!(cache_entry(&url, &get(&[]), &ok(&[("cache-control", "max-age=60")]), now).is_some());
+        assert!(cache_entry(&url, &authorized, &ok(&[("cache-control", "max-age=60")]), now).is_none());
+        assert!(cache_entry(&url, &authorized, &ok(&[("etag", "\"v1\"")]), now).is_none());
+        for marked in ["private, max-age=60", "public, max-age=60"] {
+            let entry = cache_entry(&url, &authorized, &ok(&[("cache-control", marked)]), now);
+            assert_eq!(entry.map(|e| e.expires_at), Some(now + 60), "{}", marked);
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_with_jitter_up_to_max() {
+        let policy = RetryPolicy { base_delay_ms: 100, max_delay_ms: 1_000, ..RetryPolicy::default() };
+        for (attempt, ceiling) in [(0, 100), (1, 200), (2, 400), (3, 800), (4, 1_000), (40, 1_000)] {
+            for _ in 0..50 {
+                let delay = backoff(&policy, attempt).as_millis() as u64;
+                assert!(
+                    (ceiling / 2..=ceiling).contains(&delay),
+                    "attempt {}: {}ms not within {}..={}",
+                    attempt,
+                    delay,
+                    ceiling / 2,
+                    ceiling
+                );
+            }
+        }
+        // Huge base delays saturate instead of overflowing
+        let policy = RetryPolicy { base_delay_ms: u64::MAX, max_delay_ms: 5_000, ..RetryPolicy::default() };
+        assert!(backoff(&policy, 10) <= Duration::from_millis(5_000));
+    }
+
+    #[test]
+    fn retry_after_reads_seconds_and_dates() {
+        let with = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(RETRY_AFTER, HeaderValue::from_str(value).unwrap());
+            retry_after(&headers)
+        };
+        assert_eq!(retry_after(&HeaderMap::new()), None);
+        assert_eq!(with("120"), Some(Duration::from_secs(120)));
+        assert_eq!(with(" 0 "), Some(Duration::ZERO));
+        assert_eq!(with("soon"), None);
+        assert_eq!(with("-5"), None);
+
+        // Dates in the past mean now
+        assert_eq!(with("Wed, 21 Oct 2015 07:28:00 GMT"), Some(Duration::ZERO));
+        let later = (OffsetDateTime::now_utc() + time::Duration::minutes(10)).format(&Rfc2822).unwrap();
+        let delay = with(&later).unwrap();
+        assert!(delay > Duration::from_secs(590) && delay <= Duration::from_secs(600), "{:?}", delay);
+    }
+
+    #[test]
+    fn redirect_limits_round_down() {
+        for (max, limit) in [(0, 0), (1, 1), (4, 3), (5, 5), (9, 5), (10, 10), (19, 10), (20, 20), (usize::MAX, 20)] {
+            assert_eq!(redirect_limit(max), limit, "max {}", max);
+        }
+    }
+
+    #[tokio::test]
+    async fn uploads_from_app_data_are_refused() {
+        let root = tempfile::tempdir().unwrap();
+        let app_data = root.path().join("app-data");
+        std::fs::create_dir(&app_data).unwrap();
+        std::fs::write(app_data.join("master.key"), b"key").unwrap();
+        std::fs::write(root.path().join("report.txt"), b"report").unwrap();
+        // The app data directory sits inside a readable root, as in a picked home directory
+        let files = FileScope::new(vec![root.path().to_path_buf()], vec![app_data.clone()], None);
+        let field = |path: std::path::PathBuf| MultipartField::File {
+            name: "file".to_string(),
+            path: path.to_string_lossy().to_string(),
+            file_name: None,
+            content_type: None,
+        };
+
+        let result = multipart_form(&[field(app_data.join("master.key"))], &files).await;
+        assert!(matches!(result, Err(AppError::ScopeViolation(_))));
+        assert!(multipart_form(&[field(root.path().join("report.txt"))], &files).await.is_ok());
+    }
+
+    /// Serves `head` then `body` to every connection, then keeps it open until `hold` has passed
+    async fn serve(head: &'static str, body: Vec<u8>, hold: Duration) -> String {
+        use tokio::io::AsyncBufReadExt;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let body = body.clone();
+                tokio::spawn(async move {
+                    let mut stream = tokio::io::BufReader::new(stream);
+                    let mut line = String::new();
+                    while stream.read_line(&mut line).await.unwrap_or(0) > 2 {
+                        line.clear();
+                    }
+                    let _ = stream.get_mut().write_all(head.as_bytes()).await;
+                    let _ = stream.get_mut().write_all(&body).await;
+                    tokio::time::sleep(hold).await;
+                });
+            }
+        });
+        format!("http://{}/file.bin", addr)
+    }
+
+    fn downloader(root: &Path) -> HttpClient {
+        let scope = HttpScope { block_private: false, ..HttpScope::default() };
+        let files = FileScope::new(vec![root.to_path_buf()], vec![root.join("app-data")], None);
+        HttpClient::new(scope, files, PersistentCookieJar::in_memory(), Arc::new(RecordingEventPublisher::new())).unwrap()
+    }
+
+    #[tokio::test]
+    async fn cancelled_downloads_leave_no_partial_file() {
+        let root = tempfile::tempdir().unwrap();
+        let target = root.path().join("file.bin");
+        // Ten of a thousand bytes, then the server stalls
+        let url = serve("HTTP/1.1 200 OK\r\nContent-Length: 1000\r\n\r\n", vec![b'x'; 10], Duration::from_secs(30)).await;
+
+        let cancel = CancellationToken::new();
+        let on_progress = cancel.clone();
+        let progress = move |p: &TransferProgress| {
+            if p.bytes > 0 {
+                on_progress.cancel();
+            }
+        };
+        let result = downloader(root.path())
+            .download(&url, &target.to_string_lossy(), None, false, &progress, cancel)
+            .await;
+
+        assert!(matches!(result, Err(AppError::Domain(message)) if message.contains("cancelled")));
+        assert!(!target.exists());
+        assert!(!root.path().join("file.bin.part").exists());
+    }
+
+    #[tokio::test]
+    async fn checksum_mismatch_leaves_no_target() {
+        let root = tempfile::tempdir().unwrap();
+        let target = root.path().join("file.bin");
+        let url = serve("HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\n", b"hello".to_vec(), Duration::ZERO).await;
+        let client = downloader(root.path());
+        let path = target.to_string_lossy();
+
+        let wrong = "0".repeat(64);
+        let result = client.download(&url, &path, Some(&wrong), false, &|_| {}, CancellationToken::new()).await;
+        assert!(matches!(result, Err(AppError::Domain(message)) if message.contains("Checksum mismatch")));
+        assert!(!target.exists());
+        assert!(!root.path().join("file.bin.part").exists());
+
+        // sha256("hello")
+        let right = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        let file = client.download(&url, &path, Some(right), false, &|_| {}, CancellationToken::new()).await.unwrap();
+        assert_eq!(file.bytes, 5);
+        assert_eq!(std::fs::read(&target).unwrap(), b"hello");
+    }
+
+    #[tokio::test]
+    async fn existing_targets_need_overwrite() {
+        let root = tempfile::tempdir().unwrap();
+        let target = root.path().join("file.bin");
+        std::fs::write(&target, b"old").unwrap();
+        let url = serve("HTTP/1.1 200 OK\r\nContent-Length: 3\r\n\r\n", b"new".to_vec(), Duration::ZERO).await;
+        let client = downloader(root.path());
+        let path = target.to_string_lossy();
+
+        let result = client.download(&url, &path, None, false, &|_| {}, CancellationToken::new()).await;
+        assert!(matches!(result, Err(AppError::Domain(message)) if message.contains("already exists")));
+        assert_eq!(std::fs::read(&target).unwrap(), b"old");
+
+        client.download(&url, &path, None, true, &|_| {}, CancellationToken::new()).await.unwrap();
+        assert_eq!(std::fs::read(&target).unwrap(), b"new");
+    }
+
+    #[tokio::test]
+    async fn downloads_into_app_data_are_refused() {
+        let root = tempfile::tempdir().unwrap();
+        let app_data = root.path().join("app-data");
+        std::fs::create_dir(&app_data).unwrap();
+        std::fs::write(app_data.join("db.sqlite"), b"db").unwrap();
+        let url = serve("HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\n", b"evil".to_vec(), Duration::ZERO).await;
+
+        let path = app_data.join("db.sqlite");
+        let result = downloader(root.path())
+            .download(&url, &path.to_string_lossy(), None, true, &|_| {}, CancellationToken::new())
+            .await;
+        assert!(matches!(result, Err(AppError::ScopeViolation(_))));
+        assert_eq!(std::fs::read(&path).unwrap(), b"db");
+    }
+
+    #[test]
+    fn unsatisfied_range_total_reads_content_range() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(unsatisfied_range_total(&headers), None);
+        headers.insert(CONTENT_RANGE, HeaderValue::from_static("bytes */1024"));
+        assert_eq!(unsatisfied_range_total(&headers), Some(1024));
+        headers.insert(CONTENT_RANGE, HeaderValue::from_static("bytes 0-9/1024"));
+        assert_eq!(unsatisfied_range_total(&headers), None);
     }
 }
@@ -0,0 +1,334 @@
+//! Which URLs the HTTP client may reach.
+//!
+//! Requests come from the webview, so a compromised frontend could use the
+//! client to probe the local machine or network (SSRF). Every request and every
+//! redirect hop is checked against the scope, and host names are resolved
+//! through `ScopedResolver`, which refuses private addresses at connect time.
+//!
+//! The scope is read from the config layers below the database only (config
+//! files, `APP_HTTP_SCOPE_*` env vars, CLI), so the webview cannot widen it
+//! with `set_config`:
+//! - `http_scope_schemes`: comma-separated, default `https,http`
+//! - `http_scope_hosts`: comma-separated globs such as `*.example.com`, default `*`
+//! - `http_scope_ports`: comma-separated, default any
+//! - `http_scope_block_private`: reject loopback, private, link-local and other
+//!   special-purpose addresses, default `true`
+
+use std::error::Error;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::Url;
+
+use crate::domain::config::StaticConfig;
+use crate::error::AppError;
+use crate::infra::db::parse_setting;
+
+#[derive(Debug, Clone)]
+pub struct HttpScope {
+    pub schemes: Vec<String>,
+    /// Host globs; `*` matches any run of characters
+    pub hosts: Vec<String>,
+    /// Empty = any port
+    pub ports: Vec<u16>,
+    pub block_private: bool,
+}
+
+impl Default for HttpScope {
+    fn default() -> Self {
+        Self {
+            schemes: vec!["https".to_string(), "http".to_string()],
+            hosts: vec!["*".to_string()],
+            ports: Vec::new(),
+            block_private: true,
+        }
+    }
+}
+
+/// A request outside the scope, carried through reqwest's errors
+/// (from the redirect policy or the resolver) back to `AppError::ScopeViolation`.
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct ScopeError(String);
+
+impl From<ScopeError> for AppError {
+    fn from(err: ScopeError) -> Self {
+        AppError::ScopeViolation(err.0)
+    }
+}
+
+impl HttpScope {
+    /// Defaults overridden by the static config layers. Invalid values are logged and ignored.
+    pub fn from_config(config: &StaticConfig) -> Self {
+        let mut scope = Self::default();
+
+        if let Some(schemes) = parse_setting(config, "http_scope_schemes", |v| Some(list(v))) {
+            scope.schemes = schemes;
+        }
+        if let Some(hosts) = parse_setting(config, "http_scope_hosts", |v| Some(list(v))) {
+            scope.hosts = hosts;
+        }
+        if let Some(ports) = parse_setting(config, "http_scope_ports", |v| {
+            list(v).iter().map(|port| port.parse().ok()).collect()
+        }) {
+            scope.ports = ports;
+        }
+        if let Some(block) = parse_setting(config, "http_scope_block_private", |v| v.parse().ok()) {
+            scope.block_private = block;
+        }
+
+        scope
+    }
+
+    /// Fail if `url` is outside the scope (an `AppError::ScopeViolation` once converted).
+    pub fn check(&self, url: &Url) -> Result<(), ScopeError> {
+        if !self.schemes.iter().any(|scheme| scheme == url.scheme()) {
+            return Err(ScopeError(format!("Scheme {} is not allowed", url.scheme())));
+        }
+
+        let Some(host) = url.host_str() else {
+            return Err(ScopeError(format!("{} has no host", url)));
+        };
+        // IPv6 literals come in brackets
+        let name = host.trim_start_matches('[').trim_end_matches(']').to_ascii_lowercase();
+        if !self.hosts.iter().any(|pattern| glob_match(pattern, &name)) {
+            return Err(ScopeError(format!("Host {} is not allowed", name)));
+        }
+
+        if !self.ports.is_empty() {
+            let port = url.port_or_known_default().unwrap_or_default();
+            if !self.ports.contains(&port) {
+                return Err(ScopeError(format!("Port {} is not allowed", port)));
+            }
+        }
+
+        // Names are checked again once resolved, see `ScopedResolver`
+        if self.block_private {
+            let private = match name.parse::<IpAddr>() {
+                Ok(ip) => is_private(ip),
+                Err(_) => {
+                    let domain = name.trim_end_matches('.');
+                    domain == "localhost" || domain.ends_with(".localhost")
+                }
+            };
+            if private {
+                return Err(ScopeError(format!("Host {} is a private address", name)));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The scope violation behind a reqwest error, if that is why it failed.
+pub fn scope_error(err: &reqwest::Error) -> Option<AppError> {
+    let mut source = err.source();
+    while let Some(err) = source {
+        if let Some(violation) = err.downcast_ref::<ScopeError>() {
+            return Some(AppError::ScopeViolation(violation.0.clone()));
+        }
+        source = err.source();
+    }
+    None
+}
+
+/// DNS resolver that refuses names resolving to private addresses, so a
+/// public name pointed at the local network is caught when connecting.
+pub struct ScopedResolver {
+    /// Resolved without the check, e.g. a proxy on the local network
+    trusted: Option<String>,
+}
+
+impl ScopedResolver {
+    pub fn new(trusted: Option<String>) -> Arc<Self> {
+        Arc::new(Self { trusted: trusted.map(|host| host.to_ascii_lowercase()) })
+    }
+}
+
+impl Resolve for ScopedResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_ascii_lowercase();
+        let trusted = self.trusted.as_deref() == Some(host.as_str());
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            // One private address is enough; the connector may pick any of them
+            if !trusted && addrs.iter().any(|addr| is_private(addr.ip())) {
+                return Err(ScopeError(format!("Host {} resolves to a private address", host)).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Loopback, private, link-local (including cloud metadata), shared, benchmarking and
+/// unspecified addresses, also when embedded in an IPv4-mapped or NAT64 address
+fn is_private(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_private_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped().or_else(|| nat64_embedded(ip)) {
+            Some(embedded) => is_private_v4(embedded),
+            None => {
+                let first = ip.segments()[0];
+                ip.is_loopback()
+                    || ip.is_unspecified()
+                    || (first & 0xfe00) == 0xfc00 // unique local, fc00::/7
+                    || (first & 0xffc0) == 0xfe80 // link-local, fe80::/10
+            }
+        },
+    }
+}
+
+fn is_private_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || a == 0 // "this network", 0.0.0.0/8, reaches the local host on most systems
+        || (a == 100 && (b & 0xc0) == 64) // carrier-grade NAT, 100.64.0.0/10
+        || (a == 192 && b == 0 && c == 0) // IETF protocol assignments, 192.0.0.0/24
+        || (a == 198 && (b & 0xfe) == 18) // benchmarking, 198.18.0.0/15
+}
+
+/// The IPv4 address inside the well-known NAT64 prefix 64:ff9b::/96
+fn nat64_embedded(ip: Ipv6Addr) -> Option<Ipv4Addr> {
+    let segments = ip.segments();
+    if segments[..6] != [0x64, 0xff9b, 0, 0, 0, 0] {
+        return None;
+    }
+    let [.., a, b, c, d] = ip.octets();
+    Some(Ipv4Addr::new(a, b, c, d))
+}
+
+fn list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|item| item.trim().to_ascii_lowercase())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Glob match where `*` matches any run of characters (including none)
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let Some((prefix, rest)) = pattern.split_once('*') else {
+        return pattern == text;
+    };
+    let Some(mut remaining) = text.strip_prefix(prefix) else {
+        return false;
+    };
+
+    let mut parts: Vec<&str> = rest.split('*').collect();
+    let suffix = parts.pop().unwrap_or_default();
+    for part in parts {
+        match remaining.find(part) {
+            Some(at) => remaining = &remaining[at + part.len()..],
+            None => return false,
+        }
+    }
+    remaining.len() >= suffix.len() && remaining.ends_with(suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(value: &str) -> Url {
+        Url::parse(value).unwrap()
+    }
+
+    #[test]
+    fn glob_matches_runs_of_characters() {
+        assert!(glob_match("*", ""));
+        assert!(glob_match("*", "api.example.com"));
+        assert!(glob_match("api.example.com", "api.example.com"));
+        assert!(!glob_match("api.example.com", "api.example.co"));
+        assert!(glob_match("*.example.com", "api.example.com"));
+        assert!(glob_match("*.example.com", "a.b.example.com"));
+        assert!(!glob_match("*.example.com", "example.com"));
+        assert!(!glob_match("*.example.com", "evilexample.com"));
+        assert!(!glob_match("*.example.com", "api.example.com.evil.net"));
+        assert!(glob_match("api.*.com", "api.example.com"));
+        assert!(glob_match("a*b*c", "abc"));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(!glob_match("a*b*c", "axxcyyb"));
+        // Prefix and suffix may not overlap
+        assert!(!glob_match("ab*ba", "aba"));
+        assert!(glob_match("ab*ba", "abba"));
+    }
+
+    #[test]
+    fn default_scope_allows_public_urls() {
+        let scope = HttpScope::default();
+        assert!(scope.check(&url("https://api.example.com/v1")).is_ok());
+        assert!(scope.check(&url("http://93.184.216.34:8080/")).is_ok());
+        assert!(scope.check(&url("https://[2606:2800:220:1::1]/")).is_ok());
+    }
+
+    #[test]
+    fn schemes_hosts_and_ports_are_restricted() {
+        let scope = HttpScope {
+            schemes: vec!["https".to_string()],
+            hosts: vec!["*.example.com".to_string(), "example.org".to_string()],
+            ports: vec![443, 8443],
+            block_private: true,
+        };
+        assert!(scope.check(&url("https://api.example.com/")).is_ok());
+        assert!(scope.check(&url("https://API.Example.com:8443/")).is_ok());
+        assert!(scope.check(&url("https://example.org/")).is_ok());
+
+        assert!(scope.check(&url("http://api.example.com/")).is_err());
+        assert!(scope.check(&url("ftp://api.example.com/")).is_err());
+        assert!(scope.check(&url("https://example.com/")).is_err());
+        assert!(scope.check(&url("https://api.example.org/")).is_err());
+        assert!(scope.check(&url("https://api.example.com:8080/")).is_err());
+        assert!(scope.check(&url("file:///etc/passwd")).is_err());
+    }
+
+    #[test]
+    fn private_hosts_are_refused() {
+        let scope = HttpScope::default();
+        for blocked in [
+            "http://localhost/",
+            "http://app.localhost./",
+            "http://127.0.0.1/",
+            "http://10.1.2.3/",
+            "http://172.16.0.1/",
+            "http://192.168.1.1/",
+            "http://169.254.169.254/latest/meta-data",
+            "http://100.64.0.1/",
+            "http://0.0.0.0/",
+            "http://0.1.2.3/",
+            "http://192.0.0.170/",
+            "http://198.18.0.1/",
+            "http://198.19.255.255/",
+            "http://255.255.255.255/",
+            "http://[::1]/",
+            "http://[::]/",
+            "http://[fd00::1]/",
+            "http://[fe80::1]/",
+            "http://[::ffff:127.0.0.1]/",
+            "http://[64:ff9b::7f00:1]/",
+            "http://[64:ff9b::a9fe:a9fe]/",
+        ] {
+            assert!(
+                matches!(scope.check(&url(blocked)), Err(ScopeError(message)) if message.contains("private")),
+                "{} should be refused",
+                blocked
+            );
+        }
+
+        // Neighbours of the blocked ranges stay reachable
+        for allowed in [
+            "http://100.128.0.1/",
+            "http://192.0.1.1/",
+            "http://198.20.0.1/",
+            "http://[64:ff9b::808:808]/",
+            "http://[64:ff9b:1::a00:1]/",
+        ] {
+            assert!(scope.check(&url(allowed)).is_ok(), "{} should be allowed", allowed);
+        }
+
+        let open = HttpScope { block_private: false, ..HttpScope::default() };
+        assert!(open.check(&url("http://127.0.0.1:1420/")).is_ok());
+    }
+}
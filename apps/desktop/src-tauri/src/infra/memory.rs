@@ -0,0 +1,1086 @@
+//! In-memory implementations of the domain traits.
+//!
+//! Used by demo mode (`demo_mode=true`) to run the app without a database, and
+//! by tests that exercise handlers without a real database file. State lives for
+//! the lifetime of the instance only; backups are snapshots in memory too, while
+//! archives are real files in the SQLite archive format.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use async_trait::async_trait;
+use serde_json::{json, Map, Value};
+use time::format_description::well_known::Rfc3339;
+use time::macros::format_description;
+use time::OffsetDateTime;
+
+use crate::domain::archive::{ArchiveManifest, IArchiveStore};
+use crate::domain::autostart::IAutostartManager;
+use crate::domain::backups::{BackupInfo, IBackupStore};
+use crate::domain::config::IConfigRepository;
+use crate::domain::diagnostics::{DbHealthReport, DbOptions, IDbDiagnostics, PoolStats, PragmaReport, TableStats};
+use crate::domain::downloads::{Download, DownloadState, IDownloadRepository, TransferProgress};
+use crate::domain::feature_flags::{FeatureFlag, IFeatureFlagRepository, UpsertFeatureFlagCmd};
+use crate::domain::http::{CachedResponse, HttpCacheEntry, IHttpCacheRepository};
+use crate::domain::maintenance::{IDatabaseMaintenance, MaintenanceReport};
+use crate::domain::secrets::{ISecretRepository, SecretInfo};
+use crate::domain::users::{IUserRepository, User};
+use crate::error::AppError;
+use crate::infra::archive::{self, Rows};
+use crate::infra::db_migrations::MIGRATOR;
+
+/// Current time in SQLite's `CURRENT_TIMESTAMP` format, so timestamps look the same as from the database.
+fn now() -> String {
+    OffsetDateTime::now_utc()
+        .format(format_description!("[year]-[month]-[day] [hour]:[minute]:[second]"))
+        .unwrap_or_default()
+}
+
+// ============ Users ============
+
+#[derive(Default)]
+pub struct InMemoryUserRepository {
+    /// In creation order
+    users: RwLock<Vec<User>>,
+}
+
+impl InMemoryUserRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl IUserRepository for InMemoryUserRepository {
+    async fn create(&self, user: User) -> Result<User, AppError> {
+        let mut users = self.users.write().unwrap_or_else(|e| e.into_inner());
+        if users.iter().any(|u| u.id == user.id) {
+            return Err(AppError::Database(format!("User {} already exists", user.id)));
+        }
+        if user.email.is_some() && users.iter().any(|u| u.email == user.email) {
+            return Err(AppError::Database("Email is already in use".to_string()));
+        }
+
+        let timestamp = now();
+        let created = User {
+            created_at: timestamp.clone(),
+            updated_at: timestamp,
+            ..user
+        };
+        users.push(created.clone());
+        Ok(created)
+    }
+
+    async fn list(&self) -> Result<Vec<User>, AppError> {
+        // Newest first, like the SQLite repository
+        let users = self.users.read().unwrap_or_else(|e| e.into_inner());
+        Ok(users.iter().rev().cloned().collect())
+    }
+
+    async fn find_by_id(&self, id: &str) -> Result<Option<User>, AppError> {
+        let users = self.users.read().unwrap_or_else(|e| e.into_inner());
+        Ok(users.iter().find(|u| u.id == id).cloned())
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), AppError> {
+        let mut users = self.users.write().unwrap_or_else(|e| e.into_inner());
+        let before = users.len();
+        users.retain(|u| u.id != id);
+        if users.len() == before {
+            return Err(AppError::NotFound(format!("User {} not found", id)));
+        }
+        Ok(())
+    }
+}
+
+// ============ Config ============
+
+#[derive(Default)]
+pub struct InMemoryConfigRepository {
+    values: RwLock<HashMap<String, String>>,
+}
+
+impl InMemoryConfigRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl IConfigRepository for InMemoryConfigRepository {
+    async fn get(&self, key: &str) -> Result<Option<String>, AppError> {
+        Ok(self.values.read().unwrap_or_else(|e| e.into_inner()).get(key).cloned())
+    }
+
+    async fn set(&self, key: &str, value: &str) -> Result<(), AppError> {
+        self.values
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    async fn get_all(&self) -> Result<HashMap<String, String>, AppError> {
+        Ok(self.values.read().unwrap_or_else(|e| e.into_inner()).clone())
+    }
+
+    async fn set_many(&self, values: &HashMap<String, String>) -> Result<(), AppError> {
+        // A single lock acquisition keeps the batch atomic for readers
+        self.values
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .extend(values.iter().map(|(k, v)| (k.clone(), v.clone())));
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        match self.values.write().unwrap_or_else(|e| e.into_inner()).remove(key) {
+            Some(_) => Ok(()),
+            None => Err(AppError::NotFound(format!("Setting {} not found", key))),
+        }
+    }
+
+    async fn clear(&self, keep: &[&str]) -> Result<u64, AppError> {
+        let mut values = self.values.write().unwrap_or_else(|e| e.into_inner());
+        let count = values.len();
+        values.retain(|key, _| keep.contains(&key.as_str()));
+        Ok((count - values.len()) as u64)
+    }
+}
+
+// ============ Secrets ============
+
+/// Keeps secrets in plain memory; nothing is persisted, so there is nothing to encrypt.
+#[derive(Default)]
+pub struct InMemorySecretRepository {
+    /// key -> (value, updated_at), sorted by key
+    secrets: RwLock<BTreeMap<String, (String, String)>>,
+}
+
+impl InMemorySecretRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ISecretRepository for InMemorySecretRepository {
+    async fn get(&self, key: &str) -> Result<Option<String>, AppError> {
+        let secrets = self.secrets.read().unwrap_or_else(|e| e.into_inner());
+        Ok(secrets.get(key).map(|(value, _)| value.clone()))
+    }
+
+    async fn set(&self, key: &str, value: &str) -> Result<(), AppError> {
+        self.secrets
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key.to_string(), (value.to_string(), now()));
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        match self.secrets.write().unwrap_or_else(|e| e.into_inner()).remove(key) {
+            Some(_) => Ok(()),
+            None => Err(AppError::NotFound(format!("Secret {} not found", key))),
+        }
+    }
+
+    async fn list(&self) -> Result<Vec<SecretInfo>, AppError> {
+        let secrets = self.secrets.read().unwrap_or_else(|e| e.into_inner());
+        Ok(secrets
+            .iter()
+            .map(|(key, (_, updated_at))| SecretInfo {
+                key: key.clone(),
+                updated_at: updated_at.clone(),
+            })
+            .collect())
+    }
+}
+
+// ============ Feature Flags ============
+
+#[derive(Default)]
+pub struct InMemoryFeatureFlagRepository {
+    /// Sorted by key
+    flags: RwLock<BTreeMap<String, FeatureFlag>>,
+    /// (flag_key, user_id) -> enabled
+    overrides: RwLock<HashMap<(String, String), bool>>,
+}
+
+impl InMemoryFeatureFlagRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl IFeatureFlagRepository for InMemoryFeatureFlagRepository {
+    async fn list(&self) -> Result<Vec<FeatureFlag>, AppError> {
+        Ok(self.flags.read().unwrap_or_else(|e| e.into_inner()).values().cloned().collect())
+    }
+
+    async fn find(&self, key: &str) -> Result<Option<FeatureFlag>, AppError> {
+        Ok(self.flags.read().unwrap_or_else(|e| e.into_inner()).get(key).cloned())
+    }
+
+    async fn upsert(&self, cmd: &UpsertFeatureFlagCmd) -> Result<FeatureFlag, AppError> {
+        let mut flags = self.flags.write().unwrap_or_else(|e| e.into_inner());
+        let timestamp = now();
+        let created_at = flags
+            .get(&cmd.key)
+            .map(|existing| existing.created_at.clone())
+            .unwrap_or_else(|| timestamp.clone());
+
+        let flag = FeatureFlag {
+            key: cmd.key.clone(),
+            description: cmd.description.clone(),
+            default_enabled: cmd.default_enabled,
+            rollout_percentage: cmd.rollout_percentage,
+            expires_at: cmd.expires_at.clone(),
+            created_at,
+            updated_at: timestamp,
+        };
+        flags.insert(cmd.key.clone(), flag.clone());
+        Ok(flag)
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        if self.flags.write().unwrap_or_else(|e| e.into_inner()).remove(key).is_none() {
+            return Err(AppError::NotFound(format!("Feature flag {} not found", key)));
+        }
+        self.overrides
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|(flag_key, _), _| flag_key != key);
+        Ok(())
+    }
+
+    async fn get_override(&self, key: &str, user_id: &str) -> Result<Option<bool>, AppError> {
+        let overrides = self.overrides.read().unwrap_or_else(|e| e.into_inner());
+        Ok(overrides.get(&(key.to_string(), user_id.to_string())).copied())
+    }
+
+    async fn list_overrides(&self, user_id: &str) -> Result<HashMap<String, bool>, AppError> {
+        let overrides = self.overrides.read().unwrap_or_else(|e| e.into_inner());
+        Ok(overrides
+            .iter()
+            .filter(|((_, uid), _)| uid == user_id)
+            .map(|((flag_key, _), enabled)| (flag_key.clone(), *enabled))
+            .collect())
+    }
+
+    async fn set_override(&self, key: &str, user_id: &str, enabled: bool) -> Result<(), AppError> {
+        // Mirrors the foreign key on feature_flag_overrides
+        if !self.flags.read().unwrap_or_else(|e| e.into_inner()).contains_key(key) {
+            return Err(AppError::Database(format!("Feature flag {} does not exist", key)));
+        }
+        self.overrides
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert((key.to_string(), user_id.to_string()), enabled);
+        Ok(())
+    }
+
+    async fn clear_override(&self, key: &str, user_id: &str) -> Result<(), AppError> {
+        self.overrides
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&(key.to_string(), user_id.to_string()));
+        Ok(())
+    }
+}
+
+// ============ Autostart ============
+
+/// Tracks the login item state without touching the OS.
+#[derive(Default)]
+pub struct InMemoryAutostartManager {
+    enabled: AtomicBool,
+}
+
+impl InMemoryAutostartManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl IAutostartManager for InMemoryAutostartManager {
+    fn is_enabled(&self) -> Result<bool, AppError> {
+        Ok(self.enabled.load(Ordering::SeqCst))
+    }
+
+    fn enable(&self) -> Result<(), AppError> {
+        self.enabled.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn disable(&self) -> Result<(), AppError> {
+        self.enabled.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn location(&self) -> Option<String> {
+        None
+    }
+}
+
+// ============ Database ============
+
+/// Column names of the archived tables, as in the SQLite schema
+const USER_COLUMNS: [&str; 6] = ["id", "username", "email", "role", "created_at", "updated_at"];
+const SETTING_COLUMNS: [&str; 3] = ["key", "value", "updated_at"];
+const FLAG_COLUMNS: [&str; 7] =
+    ["key", "description", "default_enabled", "rollout_percentage", "expires_at", "created_at", "updated_at"];
+const OVERRIDE_COLUMNS: [&str; 3] = ["flag_key", "user_id", "enabled"];
+
+/// The in-memory repositories that together stand in for the database, so
+/// backups and archives can read and replace all of their data at once.
+#[derive(Clone)]
+pub struct InMemoryDatabase {
+    pub config: Arc<InMemoryConfigRepository>,
+    pub users: Arc<InMemoryUserRepository>,
+    pub feature_flags: Arc<InMemoryFeatureFlagRepository>,
+    pub secrets: Arc<InMemorySecretRepository>,
+}
+
+/// A copy of everything in an `InMemoryDatabase`
+#[derive(Clone)]
+struct Snapshot {
+    users: Vec<User>,
+    settings: HashMap<String, String>,
+    secrets: BTreeMap<String, (String, String)>,
+    flags: BTreeMap<String, FeatureFlag>,
+    overrides: HashMap<(String, String), bool>,
+}
+
+impl InMemoryDatabase {
+    pub fn new() -> Self {
+        Self {
+            config: Arc::new(InMemoryConfigRepository::new()),
+            users: Arc::new(InMemoryUserRepository::new()),
+            feature_flags: Arc::new(InMemoryFeatureFlagRepository::new()),
+            secrets: Arc::new(InMemorySecretRepository::new()),
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            users: self.users.users.read().unwrap_or_else(|e| e.into_inner()).clone(),
+            settings: self.config.values.read().unwrap_or_else(|e| e.into_inner()).clone(),
+            secrets: self.secrets.secrets.read().unwrap_or_else(|e| e.into_inner()).clone(),
+            flags: self.feature_flags.flags.read().unwrap_or_else(|e| e.into_inner()).clone(),
+            overrides: self.feature_flags.overrides.read().unwrap_or_else(|e| e.into_inner()).clone(),
+        }
+    }
+
+    fn restore(&self, snapshot: Snapshot) {
+        *self.users.users.write().unwrap_or_else(|e| e.into_inner()) = snapshot.users;
+        *self.config.values.write().unwrap_or_else(|e| e.into_inner()) = snapshot.settings;
+        *self.secrets.secrets.write().unwrap_or_else(|e| e.into_inner()) = snapshot.secrets;
+        *self.feature_flags.flags.write().unwrap_or_else(|e| e.into_inner()) = snapshot.flags;
+        *self.feature_flags.overrides.write().unwrap_or_else(|e| e.into_inner()) = snapshot.overrides;
+    }
+
+    /// The archived tables as rows of the SQLite schema (booleans as 0/1), keyed by table name.
+    /// Secrets are left out, as in SQLite archives.
+    fn tables(&self) -> BTreeMap<String, Vec<Value>> {
+        let snapshot = self.snapshot();
+        let timestamp = now();
+
+        let users = snapshot
+            .users
+            .iter()
+            .map(|u| {
+                json!({
+                    "id": u.id,
+                    "username": u.username,
+                    "email": u.email,
+                    "role": u.role,
+                    "created_at": u.created_at,
+                    "updated_at": u.updated_at,
+                })
+            })
+            .collect();
+
+        let mut settings: Vec<(&String, &String)> = snapshot.settings.iter().collect();
+        settings.sort();
+        let settings = settings
+            .into_iter()
+            .map(|(key, value)| json!({ "key": key, "value": value, "updated_at": timestamp }))
+            .collect();
+
+        let flags = snapshot
+            .flags
+            .values()
+            .map(|f| {
+                json!({
+                    "key": f.key,
+                    "description": f.description,
+                    "default_enabled": f.default_enabled as i64,
+                    "rollout_percentage": f.rollout_percentage,
+                    "expires_at": f.expires_at,
+                    "created_at": f.created_at,
+                    "updated_at": f.updated_at,
+                })
+            })
+            .collect();
+
+        let mut overrides: Vec<(&(String, String), &bool)> = snapshot.overrides.iter().collect();
+        overrides.sort();
+        let overrides = overrides
+            .into_iter()
+            .map(|((flag_key, user_id), enabled)| {
+                json!({ "flag_key": flag_key, "user_id": user_id, "enabled": *enabled as i64 })
+            })
+            .collect();
+
+        BTreeMap::from([
+            ("feature_flag_overrides".to_string(), overrides),
+            ("feature_flags".to_string(), flags),
+            ("system_settings".to_string(), settings),
+            ("users".to_string(), users),
+        ])
+    }
+
+    /// Replace the data of every archived table with `tables`, like an import into SQLite:
+    /// tables missing from `tables` are cleared and missing columns take their defaults.
+    /// Nothing changes if any row is invalid.
+    fn load(&self, tables: &BTreeMap<String, Rows>) -> Result<(), AppError> {
+        let mut snapshot = Snapshot {
+            secrets: self.snapshot().secrets,
+            users: Vec::new(),
+            settings: HashMap::new(),
+            flags: BTreeMap::new(),
+            overrides: HashMap::new(),
+        };
+        let timestamp = now();
+
+        for (table, rows) in tables {
+            let columns: &[&str] = match table.as_str() {
+                "users" => &USER_COLUMNS,
+                "system_settings" => &SETTING_COLUMNS,
+                "feature_flags" => &FLAG_COLUMNS,
+                "feature_flag_overrides" => &OVERRIDE_COLUMNS,
+                // Settings key migrations only ever run against SQLite
+                "settings_migrations" => continue,
+                _ => return Err(AppError::Domain(format!("Archive contains unknown table {}", table))),
+            };
+
+            for row in rows {
+                if let Some(unknown) = row.keys().find(|key| !columns.contains(&key.as_str())) {
+                    return Err(AppError::Domain(format!("Archive table {} has unknown column {}", table, unknown)));
+                }
+                match table.as_str() {
+                    "users" => snapshot.users.push(User {
+                        id: required(table, row, "id")?,
+                        username: required(table, row, "username")?,
+                        email: text(row, "email"),
+                        role: text(row, "role").unwrap_or_else(|| "user".to_string()),
+                        created_at: text(row, "created_at").unwrap_or_else(|| timestamp.clone()),
+                        updated_at: text(row, "updated_at").unwrap_or_else(|| timestamp.clone()),
+                    }),
+                    "system_settings" => {
+                        snapshot.settings.insert(required(table, row, "key")?, required(table, row, "value")?);
+                    }
+                    "feature_flags" => {
+                        let key = required(table, row, "key")?;
+                        snapshot.flags.insert(key.clone(), FeatureFlag {
+                            key,
+                            description: text(row, "description").unwrap_or_default(),
+                            default_enabled: boolean(row, "default_enabled").unwrap_or(false),
+                            rollout_percentage: row.get("rollout_percentage").and_then(Value::as_i64),
+                            expires_at: text(row, "expires_at"),
+                            created_at: text(row, "created_at").unwrap_or_else(|| timestamp.clone()),
+                            updated_at: text(row, "updated_at").unwrap_or_else(|| timestamp.clone()),
+                        });
+                    }
+                    _ => {
+                        let enabled = boolean(row, "enabled").ok_or_else(|| missing(table, "enabled"))?;
+                        snapshot
+                            .overrides
+                            .insert((required(table, row, "flag_key")?, required(table, row, "user_id")?), enabled);
+                    }
+                }
+            }
+        }
+
+        // Mirrors the foreign key on feature_flag_overrides
+        if let Some((flag_key, _)) = snapshot.overrides.keys().find(|(key, _)| !snapshot.flags.contains_key(key)) {
+            return Err(AppError::Database(format!("Feature flag {} does not exist", flag_key)));
+        }
+
+        self.restore(snapshot);
+        Ok(())
+    }
+
+    /// Rows per archived table, for diagnostics.
+    fn row_counts(&self) -> Vec<TableStats> {
+        self.tables()
+            .into_iter()
+            .map(|(name, rows)| TableStats { name, rows: rows.len() as i64 })
+            .collect()
+    }
+
+    /// Approximate size of the data: the archived tables as JSON.
+    fn size_bytes(&self) -> u64 {
+        serde_json::to_vec(&self.tables()).map(|bytes| bytes.len() as u64).unwrap_or(0)
+    }
+}
+
+fn text(row: &Map<String, Value>, column: &str) -> Option<String> {
+    match row.get(column)? {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// SQLite archives hold booleans as 0/1
+fn boolean(row: &Map<String, Value>, column: &str) -> Option<bool> {
+    match row.get(column)? {
+        Value::Bool(b) => Some(*b),
+        Value::Number(n) => n.as_i64().map(|n| n != 0),
+        _ => None,
+    }
+}
+
+fn required(table: &str, row: &Map<String, Value>, column: &str) -> Result<String, AppError> {
+    text(row, column).ok_or_else(|| missing(table, column))
+}
+
+fn missing(table: &str, column: &str) -> AppError {
+    AppError::Domain(format!("Archive table {} has a row without {}", table, column))
+}
+
+// ============ Backups ============
+
+const BACKUP_PREFIX: &str = "db-";
+/// Snapshots taken before a restore; rotation keeps them
+const PRE_RESTORE_PREFIX: &str = "db-pre-restore-";
+
+/// Keeps backups as snapshots in memory, named like the SQLite backup files.
+pub struct InMemoryBackupStore {
+    db: InMemoryDatabase,
+    /// Oldest first
+    backups: RwLock<Vec<(BackupInfo, Snapshot)>>,
+}
+
+impl InMemoryBackupStore {
+    pub fn new(db: InMemoryDatabase) -> Self {
+        Self { db, backups: RwLock::new(Vec::new()) }
+    }
+
+    fn snapshot(&self, prefix: &str) -> Result<BackupInfo, AppError> {
+        let now = OffsetDateTime::now_utc();
+        let stamp = now
+            .format(format_description!("[year][month][day]-[hour][minute][second]"))
+            .map_err(|e| AppError::Unknown(e.to_string()))?;
+
+        let mut backups = self.backups.write().unwrap_or_else(|e| e.into_inner());
+        // Avoid clobbering a backup taken within the same second
+        let mut name = format!("{}{}", prefix, stamp);
+        let mut suffix = 1;
+        while backups.iter().any(|(info, _)| info.name == name) {
+            name = format!("{}{}-{}", prefix, stamp, suffix);
+            suffix += 1;
+        }
+
+        let info = BackupInfo {
+            pre_restore: prefix == PRE_RESTORE_PREFIX,
+            name,
+            size_bytes: self.db.size_bytes(),
+            created_at: now.format(&Rfc3339).map_err(|e| AppError::Unknown(e.to_string()))?,
+        };
+        backups.push((info.clone(), self.db.snapshot()));
+        Ok(info)
+    }
+}
+
+#[async_trait]
+impl IBackupStore for InMemoryBackupStore {
+    async fn create(&self) -> Result<BackupInfo, AppError> {
+        self.snapshot(BACKUP_PREFIX)
+    }
+
+    async fn list(&self) -> Result<Vec<BackupInfo>, AppError> {
+        let backups = self.backups.read().unwrap_or_else(|e| e.into_inner());
+        Ok(backups.iter().rev().map(|(info, _)| info.clone()).collect())
+    }
+
+    async fn delete(&self, name: &str) -> Result<(), AppError> {
+        let mut backups = self.backups.write().unwrap_or_else(|e| e.into_inner());
+        let count = backups.len();
+        backups.retain(|(info, _)| info.name != name);
+        if backups.len() == count {
+            return Err(AppError::NotFound(format!("Backup {} not found", name)));
+        }
+        Ok(())
+    }
+
+    async fn restore(&self, name: &str) -> Result<(), AppError> {
+        let snapshot = self
+            .backups
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .find(|(info, _)| info.name == name)
+            .map(|(_, snapshot)| snapshot.clone())
+            .ok_or_else(|| AppError::NotFound(format!("Backup {} not found", name)))?;
+
+        // Keep the current state in case the restore was a mistake
+        self.snapshot(PRE_RESTORE_PREFIX)?;
+        self.db.restore(snapshot);
+        Ok(())
+    }
+}
+
+// ============ Archives ============
+
+/// Exports and imports the same archive files as SQLite, so demo data can be
+/// carried into a real install and back.
+pub struct InMemoryArchiveStore {
+    db: InMemoryDatabase,
+}
+
+impl InMemoryArchiveStore {
+    pub fn new(db: InMemoryDatabase) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl IArchiveStore for InMemoryArchiveStore {
+    async fn export(&self, path: &str) -> Result<ArchiveManifest, AppError> {
+        // The data matches the latest schema
+        let schema_version = MIGRATOR.iter().map(|m| m.version).max().unwrap_or(0);
+        archive::write_tables(Path::new(path), schema_version, &self.db.tables())
+    }
+
+    async fn import(&self, path: &str) -> Result<ArchiveManifest, AppError> {
+        let contents = archive::read_tables(Path::new(path))?;
+        self.db.load(&contents.tables)?;
+        Ok(contents.manifest)
+    }
+}
+
+// ============ Maintenance ============
+
+/// Nothing to optimize or reclaim in memory; reports the data size before and after.
+pub struct InMemoryMaintenance {
+    db: InMemoryDatabase,
+}
+
+impl InMemoryMaintenance {
+    pub fn new(db: InMemoryDatabase) -> Self {
+        Self { db }
+    }
+
+    fn report(&self, full_vacuum: bool) -> Result<MaintenanceReport, AppError> {
+        let size = self.db.size_bytes();
+        Ok(MaintenanceReport {
+            started_at: OffsetDateTime::now_utc()
+                .format(&Rfc3339)
+                .map_err(|e| AppError::Unknown(e.to_string()))?,
+            duration_ms: 0,
+            full_vacuum,
+            bytes_before: size,
+            bytes_after: size,
+            bytes_reclaimed: 0,
+        })
+    }
+}
+
+#[async_trait]
+impl IDatabaseMaintenance for InMemoryMaintenance {
+    async fn optimize(&self) -> Result<MaintenanceReport, AppError> {
+        self.report(false)
+    }
+
+    async fn compact(&self) -> Result<MaintenanceReport, AppError> {
+        self.report(true)
+    }
+
+    fn is_idle(&self) -> bool {
+        true
+    }
+}
+
+// ============ Diagnostics ============
+
+/// A health report describing the in-memory data: always healthy, no file, pool or migrations.
+pub struct InMemoryDiagnostics {
+    db: InMemoryDatabase,
+}
+
+impl InMemoryDiagnostics {
+    pub fn new(db: InMemoryDatabase) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl IDbDiagnostics for InMemoryDiagnostics {
+    async fn collect(&self) -> Result<DbHealthReport, AppError> {
+        Ok(DbHealthReport {
+            healthy: true,
+            integrity_check: vec!["ok".to_string()],
+            file_path: ":memory:".to_string(),
+            file_size_bytes: self.db.size_bytes(),
+            wal_size_bytes: 0,
+            page_size: 0,
+            page_count: 0,
+            freelist_count: 0,
+            pragmas: PragmaReport {
+                journal_mode: "memory".to_string(),
+                synchronous: 0,
+                busy_timeout_ms: 0,
+                foreign_keys: true,
+            },
+            options: DbOptions::default(),
+            migrations: Vec::new(),
+            settings_migrations: Vec::new(),
+            tables: self.db.row_counts(),
+            pool: PoolStats { size: 0, idle: 0, max_connections: 0 },
+        })
+    }
+}
+
+// ============ Downloads ============
+
+#[derive(Default)]
+pub struct InMemoryDownloadRepository {
+    /// In creation order
+    downloads: RwLock<Vec<Download>>,
+}
+
+impl InMemoryDownloadRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn update(&self, id: &str, f: impl FnOnce(&mut Download)) -> Result<(), AppError> {
+        let mut downloads = self.downloads.write().unwrap_or_else(|e| e.into_inner());
+        let download = downloads
+            .iter_mut()
+            .find(|d| d.id == id)
+            .ok_or_else(|| AppError::NotFound(format!("Download {} not found", id)))?;
+        f(download);
+        download.updated_at = now();
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl IDownloadRepository for InMemoryDownloadRepository {
+    async fn insert(&self, download: &Download) -> Result<Download, AppError> {
+        let mut downloads = self.downloads.write().unwrap_or_else(|e| e.into_inner());
+        if downloads.iter().any(|d| d.id == download.id) {
+            return Err(AppError::Database(format!("Download {} already exists", download.id)));
+        }
+
+        let timestamp = now();
+        let created = Download {
+            created_at: timestamp.clone(),
+            updated_at: timestamp,
+            ..download.clone()
+        };
+        downloads.push(created.clone());
+        Ok(created)
+    }
+
+    async fn list(&self) -> Result<Vec<Download>, AppError> {
+        let downloads = self.downloads.read().unwrap_or_else(|e| e.into_inner());
+        Ok(downloads.iter().rev().cloned().collect())
+    }
+
+    async fn find(&self, id: &str) -> Result<Option<Download>, AppError> {
+        let downloads = self.downloads.read().unwrap_or_else(|e| e.into_inner());
+        Ok(downloads.iter().find(|d| d.id == id).cloned())
+    }
+
+    async fn list_queued(&self) -> Result<Vec<Download>, AppError> {
+        let downloads = self.downloads.read().unwrap_or_else(|e| e.into_inner());
+        Ok(downloads.iter().filter(|d| d.state == DownloadState::Queued).cloned().collect())
+    }
+
+    async fn set_state(&self, id: &str, state: DownloadState, error: Option<&str>) -> Result<(), AppError> {
+        self.update(id, |d| {
+            d.state = state;
+            d.error = error.map(str::to_string);
+        })
+    }
+
+    async fn save_progress(&self, id: &str, progress: &TransferProgress) -> Result<(), AppError> {
+        // Like the SQL UPDATE, a missing download is not an error
+        match self.update(id, |d| {
+            d.bytes_done = progress.bytes as i64;
+            d.total_bytes = progress.total.map(|total| total as i64);
+            d.etag = progress.etag.clone();
+            d.last_modified = progress.last_modified.clone();
+        }) {
+            Err(AppError::NotFound(_)) => Ok(()),
+            result => result,
+        }
+    }
+
+    async fn requeue_interrupted(&self) -> Result<u64, AppError> {
+        let mut downloads = self.downloads.write().unwrap_or_else(|e| e.into_inner());
+        let timestamp = now();
+        let mut requeued = 0;
+        for download in downloads.iter_mut().filter(|d| d.state == DownloadState::Running) {
+            download.state = DownloadState::Queued;
+            download.updated_at = timestamp.clone();
+            requeued += 1;
+        }
+        Ok(requeued)
+    }
+}
+
+// ============ HTTP Cache ============
+
+#[derive(Default)]
+pub struct InMemoryHttpCacheRepository {
+    /// (stored_at, response), oldest first
+    entries: RwLock<Vec<(String, CachedResponse)>>,
+}
+
+impl InMemoryHttpCacheRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl IHttpCacheRepository for InMemoryHttpCacheRepository {
+    async fn find(&self, method: &str, url: &str) -> Result<Option<CachedResponse>, AppError> {
+        let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
+        Ok(entries
+            .iter()
+            .find(|(_, r)| r.method == method && r.url == url)
+            .map(|(_, r)| r.clone()))
+    }
+
+    async fn store(&self, response: &CachedResponse, max_entries: u32, max_bytes: u64) -> Result<(), AppError> {
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|(_, r)| !(r.method == response.method && r.url == response.url));
+        entries.push((now(), response.clone()));
+
+        let excess = entries.len().saturating_sub(max_entries as usize);
+        entries.drain(..excess);
+
+        // Newest first, keep entries while the running total of their bodies fits
+        let mut total = 0u64;
+        let keep = entries
+            .iter()
+            .rev()
+            .take_while(|(_, r)| {
+                total += r.body.len() as u64;
+                total <= max_bytes
+            })
+            .count();
+        let evicted = entries.len() - keep;
+        entries.drain(..evicted);
+        Ok(())
+    }
+
+    async fn remove(&self, method: &str, url: &str) -> Result<(), AppError> {
+        self.entries
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|(_, r)| !(r.method == method && r.url == url));
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<HttpCacheEntry>, AppError> {
+        let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
+        Ok(entries
+            .iter()
+            .rev()
+            .map(|(stored_at, r)| HttpCacheEntry {
+                method: r.method.clone(),
+                url: r.url.clone(),
+                status: r.status as i64,
+                size: r.body.len() as i64,
+                etag: r.etag.clone(),
+                last_modified: r.last_modified.clone(),
+                expires_at: r.expires_at,
+                stored_at: stored_at.clone(),
+            })
+            .collect())
+    }
+
+    async fn clear(&self) -> Result<u64, AppError> {
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        let count = entries.len() as u64;
+        entries.clear();
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::config::IConfigRepository;
+    use crate::infra::archive::SqliteArchiveStore;
+    use crate::infra::db::Database;
+    use crate::infra::repo_feature_flags::SqliteFeatureFlagRepository;
+    use crate::infra::repo_users::SqliteUserRepository;
+
+    fn user(id: &str) -> User {
+        User {
+            id: id.to_string(),
+            username: format!("user-{}", id),
+            email: None,
+            role: "user".to_string(),
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    /// Two users, a setting, a secret and a flag with an override
+    async fn seeded() -> InMemoryDatabase {
+        let db = InMemoryDatabase::new();
+        db.users.create(user("1")).await.unwrap();
+        db.users.create(user("2")).await.unwrap();
+        db.config.set("theme", "dark").await.unwrap();
+        db.secrets.set("token", "s3cret").await.unwrap();
+        let flag = UpsertFeatureFlagCmd {
+            key: "beta".to_string(),
+            description: "Beta features".to_string(),
+            default_enabled: true,
+            rollout_percentage: Some(50),
+            expires_at: None,
+        };
+        db.feature_flags.upsert(&flag).await.unwrap();
+        db.feature_flags.set_override("beta", "1", false).await.unwrap();
+        db
+    }
+
+    fn response(url: &str, size: usize) -> CachedResponse {
+        CachedResponse {
+            method: "GET".to_string(),
+            url: url.to_string(),
+            status: 200,
+            headers: HashMap::new(),
+            body: vec![b'x'; size],
+            vary: HashMap::new(),
+            etag: None,
+            last_modified: None,
+            expires_at: 0,
+        }
+    }
+
+    async fn urls(repo: &InMemoryHttpCacheRepository) -> Vec<String> {
+        repo.list().await.unwrap().into_iter().map(|entry| entry.url).collect()
+    }
+
+    #[tokio::test]
+    async fn http_cache_evicts_the_oldest_beyond_the_limits() {
+        let repo = InMemoryHttpCacheRepository::new();
+        for (url, size) in [("https://a/1", 400), ("https://a/2", 300), ("https://a/3", 200)] {
+            repo.store(&response(url, size), 10, 1000).await.unwrap();
+        }
+        assert_eq!(urls(&repo).await, ["https://a/3", "https://a/2", "https://a/1"]);
+
+        // 500 + 200 + 300 > 1000: the oldest goes
+        repo.store(&response("https://a/4", 500), 10, 1000).await.unwrap();
+        assert_eq!(urls(&repo).await, ["https://a/4", "https://a/3", "https://a/2"]);
+
+        // Replacing an entry frees its old size
+        repo.store(&response("https://a/2", 100), 10, 1000).await.unwrap();
+        assert_eq!(urls(&repo).await, ["https://a/2", "https://a/4", "https://a/3"]);
+
+        repo.store(&response("https://a/5", 10), 2, 1000).await.unwrap();
+        assert_eq!(urls(&repo).await, ["https://a/5", "https://a/2"]);
+        assert!(repo.find("GET", "https://a/4").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn restoring_a_backup_brings_back_all_data_and_keeps_the_current_state() {
+        let db = seeded().await;
+        let store = InMemoryBackupStore::new(db.clone());
+        let backup = store.create().await.unwrap();
+        assert!(!backup.pre_restore);
+        assert!(backup.size_bytes > 0);
+
+        db.users.delete("2").await.unwrap();
+        db.config.set("theme", "light").await.unwrap();
+        db.secrets.delete("token").await.unwrap();
+        db.feature_flags.delete("beta").await.unwrap();
+
+        store.restore(&backup.name).await.unwrap();
+        assert_eq!(db.users.list().await.unwrap().len(), 2);
+        assert_eq!(db.config.get("theme").await.unwrap().as_deref(), Some("dark"));
+        assert_eq!(db.secrets.get("token").await.unwrap().as_deref(), Some("s3cret"));
+        assert_eq!(db.feature_flags.get_override("beta", "1").await.unwrap(), Some(false));
+
+        let backups = store.list().await.unwrap();
+        assert_eq!(backups.len(), 2);
+        assert!(backups[0].pre_restore);
+        assert_ne!(backups[0].name, backup.name);
+
+        store.delete(&backup.name).await.unwrap();
+        assert!(matches!(store.restore(&backup.name).await, Err(AppError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn archives_round_trip_through_memory_and_sqlite() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("demo.zip");
+        let path = path.to_str().unwrap();
+        let source = seeded().await;
+        let manifest = InMemoryArchiveStore::new(source).export(path).await.unwrap();
+        assert!(manifest.tables.iter().all(|t| t.name != "secret_settings"));
+
+        // Import replaces everything but the secrets, which archives leave out
+        let target = InMemoryDatabase::new();
+        target.users.create(user("3")).await.unwrap();
+        target.secrets.set("kept", "yes").await.unwrap();
+        InMemoryArchiveStore::new(target.clone()).import(path).await.unwrap();
+        let ids: Vec<String> = target.users.list().await.unwrap().into_iter().map(|u| u.id).collect();
+        assert_eq!(ids.len(), 2);
+        assert!(!ids.contains(&"3".to_string()));
+        assert_eq!(target.config.get("theme").await.unwrap().as_deref(), Some("dark"));
+        assert_eq!(target.secrets.get("kept").await.unwrap().as_deref(), Some("yes"));
+        let flag = target.feature_flags.find("beta").await.unwrap().unwrap();
+        assert!(flag.default_enabled);
+        assert_eq!(flag.rollout_percentage, Some(50));
+        assert_eq!(target.feature_flags.get_override("beta", "1").await.unwrap(), Some(false));
+
+        // The same file imports into SQLite, and SQLite archives import back into memory
+        let sqlite = Arc::new(Database::open(dir.path().join("app.db"), DbOptions::default()).await.unwrap());
+        SqliteArchiveStore::new(sqlite.clone()).import(path).await.unwrap();
+        assert_eq!(SqliteUserRepository::new(sqlite.clone()).list().await.unwrap().len(), 2);
+        let flags = SqliteFeatureFlagRepository::new(sqlite.clone());
+        assert_eq!(flags.get_override("beta", "1").await.unwrap(), Some(false));
+
+        let exported = dir.path().join("sqlite.zip");
+        let exported = exported.to_str().unwrap();
+        SqliteArchiveStore::new(sqlite).export(exported).await.unwrap();
+        let back = InMemoryDatabase::new();
+        InMemoryArchiveStore::new(back.clone()).import(exported).await.unwrap();
+        assert_eq!(back.users.list().await.unwrap().len(), 2);
+        assert!(back.feature_flags.find("beta").await.unwrap().unwrap().default_enabled);
+    }
+
+    #[tokio::test]
+    async fn invalid_archives_change_nothing() {
+        let db = seeded().await;
+        let mut tables = BTreeMap::new();
+        tables.insert("users".to_string(), vec![json!({ "id": "9", "username": "x" })]);
+        tables.insert("feature_flag_overrides".to_string(), vec![json!({ "flag_key": "gone", "user_id": "9", "enabled": 1 })]);
+        let rows = |tables: &BTreeMap<String, Vec<Value>>| -> BTreeMap<String, Rows> {
+            tables
+                .iter()
+                .map(|(name, rows)| (name.clone(), rows.iter().map(|r| r.as_object().unwrap().clone()).collect()))
+                .collect()
+        };
+        assert!(matches!(db.load(&rows(&tables)), Err(AppError::Database(_))));
+
+        tables.remove("feature_flag_overrides");
+        tables.insert("unknown".to_string(), Vec::new());
+        assert!(matches!(db.load(&rows(&tables)), Err(AppError::Domain(_))));
+        assert_eq!(db.users.list().await.unwrap().len(), 2);
+    }
+}
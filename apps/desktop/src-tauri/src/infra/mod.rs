@@ -1,6 +1,28 @@
 pub mod logging;
 pub mod db;
+pub mod archive;
+pub mod backup_store;
+pub mod db_diagnostics;
+pub mod db_maintenance;
+pub mod db_migrations;
+pub mod db_recovery;
+pub mod query_stats;
+pub mod settings_migrations;
+pub mod config_layers;
 pub mod repo_config;
 pub mod repo_users;
+pub mod repo_feature_flags;
+pub mod repo_secrets;
+pub mod repo_downloads;
+pub mod repo_http_cache;
+pub mod memory;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+pub mod crypto;
 pub mod event_publisher;
 pub mod http;
+pub mod circuit_breaker;
+pub mod cookie_jar;
+pub mod http_scope;
+pub mod file_scope;
+pub mod autostart;
@@ -0,0 +1,259 @@
+//! PostgreSQL backend (`postgres` cargo feature) for pointing the app at a shared team database.
+//!
+//! Selected at startup by a `postgres://` (or `postgresql://`) `db_url` setting. Keep the
+//! password out of the URL (static settings are visible in the settings UI): `PGPASSWORD`
+//! and `~/.pgpass` are honored. Schema migrations live in `./migrations_postgres` and
+//! mirror `./migrations` up to 20250105; the download queue (20250106, 20250108) and HTTP cache
+//! (20250107) tables are SQLite-only, since those features stay on the local file.
+//! So do secrets: they are encrypted with each machine's own key and would be unreadable
+//! to teammates, which leaves the shared `secret_settings` table unused.
+//!
+//! The tests run against the database in `DATABASE_URL`, each in a schema of its own,
+//! and are skipped when it is not set.
+//!
+//! Timestamps are `TIMESTAMPTZ` columns, read back as text in the same
+//! `YYYY-MM-DD HH:MM:SS` (UTC) form SQLite's `CURRENT_TIMESTAMP` produces.
+
+pub mod repo_config;
+pub mod repo_feature_flags;
+pub mod repo_users;
+
+use std::str::FromStr;
+use std::time::Duration;
+use sqlx::migrate::Migrator;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::{PgPool, Postgres, Transaction};
+use tracing::info;
+
+use crate::error::AppError;
+use crate::infra::db::DbOptions;
+use crate::infra::settings_migrations::{SettingsMigration, SettingsOp, MIGRATIONS};
+
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations_postgres");
+
+/// Whether `url` selects this backend.
+pub fn is_postgres_url(url: &str) -> bool {
+    url.starts_with("postgres://") || url.starts_with("postgresql://")
+}
+
+/// Open a pool and bring the schema and settings up to date.
+/// Pool sizing comes from `DbOptions`; the SQLite pragmas do not apply.
+pub async fn connect(url: &str, options: &DbOptions) -> Result<PgPool, AppError> {
+    let connect_options = PgConnectOptions::from_str(url)
+        .map_err(|e| AppError::Database(format!("Invalid PostgreSQL URL: {}", e)))?;
+    info!(
+        "Connecting to PostgreSQL at {}:{}",
+        connect_options.get_host(),
+        connect_options.get_port(),
+    );
+
+    let pool = PgPoolOptions::new()
+        .max_connections(options.max_connections)
+        .acquire_timeout(Duration::from_millis(options.acquire_timeout_ms))
+        .connect_with(connect_options)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to connect: {}", e)))?;
+
+    MIGRATOR
+        .run(&pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Migration failed: {}", e)))?;
+    info!("PostgreSQL migrations applied successfully.");
+
+    run_settings_migrations(&pool, MIGRATIONS)
+        .await
+        .map_err(|e| AppError::Database(format!("Settings migration failed: {}", e)))?;
+
+    Ok(pool)
+}
+
+/// Same as `settings_migrations::run`, in PostgreSQL's dialect.
+async fn run_settings_migrations(pool: &PgPool, migrations: &[SettingsMigration]) -> Result<(), AppError> {
+    let applied: Vec<i64> = sqlx::query_scalar("SELECT version FROM settings_migrations")
+        .fetch_all(pool)
+        .await?;
+
+    for migration in migrations.iter().filter(|m| !applied.contains(&m.version)) {
+        let mut tx = pool.begin().await?;
+
+        for op in migration.ops {
+            apply(&mut tx, op).await?;
+        }
+
+        sqlx::query("INSERT INTO settings_migrations (version, description) VALUES ($1, $2)")
+            .bind(migration.version)
+            .bind(migration.description)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        info!("Applied settings migration {}: {}", migration.version, migration.description);
+    }
+
+    Ok(())
+}
+
+async fn apply(tx: &mut Transaction<'_, Postgres>, op: &SettingsOp) -> Result<(), AppError> {
+    match op {
+        SettingsOp::Rename { from, to } => {
+            sqlx::query("INSERT INTO system_settings (key, value, updated_at) SELECT $1, value, now() FROM system_settings WHERE key = $2 ON CONFLICT (key) DO NOTHING")
+                .bind(to)
+                .bind(from)
+                .execute(&mut **tx)
+                .await?;
+            delete(tx, from).await?;
+        }
+        SettingsOp::Convert { key, convert } => {
+            let value: Option<String> = sqlx::query_scalar("SELECT value FROM system_settings WHERE key = $1")
+                .bind(key)
+                .fetch_optional(&mut **tx)
+                .await?;
+
+            match value.as_deref().map(convert) {
+                Some(Some(converted)) => {
+                    sqlx::query("UPDATE system_settings SET value = $1, updated_at = now() WHERE key = $2")
+                        .bind(converted)
+                        .bind(key)
+                        .execute(&mut **tx)
+                        .await?;
+                }
+                Some(None) => delete(tx, key).await?,
+                None => {}
+            }
+        }
+        SettingsOp::Drop { key } => delete(tx, key).await?,
+    }
+    Ok(())
+}
+
+async fn delete(tx: &mut Transaction<'_, Postgres>, key: &str) -> Result<(), AppError> {
+    sqlx::query("DELETE FROM system_settings WHERE key = $1")
+        .bind(key)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+/// A migrated schema of its own in the `DATABASE_URL` database, dropped by `close`.
+#[cfg(test)]
+pub(crate) struct TestDb {
+    pub pool: PgPool,
+    admin: PgPool,
+    schema: String,
+}
+
+#[cfg(test)]
+impl TestDb {
+    /// `None` (the test is skipped) when `DATABASE_URL` is not set.
+    pub async fn create() -> Option<Self> {
+        let Ok(url) = std::env::var("DATABASE_URL") else {
+            eprintln!("DATABASE_URL is not set, skipping PostgreSQL test");
+            return None;
+        };
+        let admin = PgPool::connect(&url).await.expect("Failed to connect to DATABASE_URL");
+        let schema = format!("test_{}", uuid::Uuid::new_v4().simple());
+        sqlx::query(&format!("CREATE SCHEMA {}", schema))
+            .execute(&admin)
+            .await
+            .expect("Failed to create test schema");
+
+        let separator = if url.contains('?') { '&' } else { '?' };
+        let url = format!("{}{}options=-c%20search_path%3D{}", url, separator, schema);
+        let pool = connect(&url, &DbOptions::default()).await.expect("Failed to migrate test schema");
+        Some(Self { pool, admin, schema })
+    }
+
+    pub async fn close(self) {
+        self.pool.close().await;
+        sqlx::query(&format!("DROP SCHEMA {} CASCADE", self.schema))
+            .execute(&self.admin)
+            .await
+            .expect("Failed to drop test schema");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn value(pool: &PgPool, key: &str) -> Option<String> {
+        sqlx::query_scalar("SELECT value FROM system_settings WHERE key = $1")
+            .bind(key)
+            .fetch_optional(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn set(pool: &PgPool, key: &str, value: &str) {
+        sqlx::query("INSERT INTO system_settings (key, value) VALUES ($1, $2)")
+            .bind(key)
+            .bind(value)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn settings_migrations_apply_once() {
+        let Some(db) = TestDb::create().await else { return };
+        set(&db.pool, "theme", "dark").await;
+        set(&db.pool, "font_size", "14px").await;
+        set(&db.pool, "legacy_flag", "1").await;
+        set(&db.pool, "window_mode", "tiled").await;
+
+        const MIGRATIONS: &[SettingsMigration] = &[
+            SettingsMigration {
+                version: 1,
+                description: "Rename theme to theme_mode",
+                ops: &[SettingsOp::Rename { from: "theme", to: "theme_mode" }],
+            },
+            SettingsMigration {
+                version: 2,
+                description: "Font size in points, drop unknown window modes",
+                ops: &[
+                    SettingsOp::Convert { key: "font_size", convert: |v| v.strip_suffix("px").map(str::to_string) },
+                    SettingsOp::Convert { key: "window_mode", convert: |v| (v == "floating").then(|| v.to_string()) },
+                    SettingsOp::Drop { key: "legacy_flag" },
+                ],
+            },
+        ];
+        run_settings_migrations(&db.pool, MIGRATIONS).await.unwrap();
+
+        assert_eq!(value(&db.pool, "theme").await, None);
+        assert_eq!(value(&db.pool, "theme_mode").await.as_deref(), Some("dark"));
+        assert_eq!(value(&db.pool, "font_size").await.as_deref(), Some("14"));
+        assert_eq!(value(&db.pool, "window_mode").await, None);
+        assert_eq!(value(&db.pool, "legacy_flag").await, None);
+
+        // Applied versions are recorded and not run again
+        set(&db.pool, "theme", "light").await;
+        run_settings_migrations(&db.pool, MIGRATIONS).await.unwrap();
+        assert_eq!(value(&db.pool, "theme").await.as_deref(), Some("light"));
+        let versions: Vec<i64> = sqlx::query_scalar("SELECT version FROM settings_migrations ORDER BY version")
+            .fetch_all(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(versions, vec![1, 2]);
+
+        db.close().await;
+    }
+
+    #[tokio::test]
+    async fn rename_keeps_an_existing_target() {
+        let Some(db) = TestDb::create().await else { return };
+        set(&db.pool, "theme", "dark").await;
+        set(&db.pool, "theme_mode", "light").await;
+
+        const MIGRATIONS: &[SettingsMigration] = &[SettingsMigration {
+            version: 1,
+            description: "Rename theme to theme_mode",
+            ops: &[SettingsOp::Rename { from: "theme", to: "theme_mode" }],
+        }];
+        run_settings_migrations(&db.pool, MIGRATIONS).await.unwrap();
+
+        assert_eq!(value(&db.pool, "theme").await, None);
+        assert_eq!(value(&db.pool, "theme_mode").await.as_deref(), Some("light"));
+
+        db.close().await;
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use async_trait::async_trait;
+use sqlx::PgPool;
+use crate::domain::config::IConfigRepository;
+use crate::error::AppError;
+use crate::infra::query_stats::QueryStats;
+
+const UPSERT_SQL: &str = "INSERT INTO system_settings (key, value, updated_at) VALUES ($1, $2, now()) ON CONFLICT (key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at";
+
+pub struct PostgresConfigRepository {
+    pool: PgPool,
+    queries: Arc<QueryStats>,
+}
+
+impl PostgresConfigRepository {
+    pub fn new(pool: PgPool, queries: Arc<QueryStats>) -> Self {
+        Self { pool, queries }
+    }
+}
+
+#[async_trait]
+impl IConfigRepository for PostgresConfigRepository {
+    async fn get(&self, key: &str) -> Result<Option<String>, AppError> {
+        let result = self.queries.observe("SELECT value FROM system_settings WHERE key = $1", |sql| {
+            sqlx::query_scalar::<_, String>(sql)
+                .bind(key)
+                .fetch_optional(&self.pool)
+        }).await?;
+        Ok(result)
+    }
+
+    async fn set(&self, key: &str, value: &str) -> Result<(), AppError> {
+        self.queries.observe(UPSERT_SQL, |sql| {
+            sqlx::query(sql)
+                .bind(key)
+                .bind(value)
+                .execute(&self.pool)
+        }).await?;
+        Ok(())
+    }
+
+    async fn get_all(&self) -> Result<HashMap<String, String>, AppError> {
+        let rows = self.queries.observe("SELECT key, value FROM system_settings", |sql| {
+            sqlx::query_as::<_, (String, String)>(sql)
+                .fetch_all(&self.pool)
+        }).await?;
+        Ok(rows.into_iter().collect())
+    }
+
+    async fn set_many(&self, values: &HashMap<String, String>) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+        for (key, value) in values {
+            self.queries.observe(UPSERT_SQL, |sql| {
+                sqlx::query(sql)
+                    .bind(key)
+                    .bind(value)
+                    .execute(&mut *tx)
+            }).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        let result = self.queries.observe("DELETE FROM system_settings WHERE key = $1", |sql| {
+            sqlx::query(sql)
+                .bind(key)
+                .execute(&self.pool)
+        }).await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("Setting {} not found", key)));
+        }
+        Ok(())
+    }
+
+    async fn clear(&self, keep: &[&str]) -> Result<u64, AppError> {
+        let result = self.queries.observe("DELETE FROM system_settings WHERE key <> ALL($1)", |sql| {
+            sqlx::query(sql)
+                .bind(keep)
+                .execute(&self.pool)
+        }).await?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::postgres::TestDb;
+
+    #[tokio::test]
+    async fn settings_round_trip() {
+        let Some(db) = TestDb::create().await else { return };
+        let repo = PostgresConfigRepository::new(db.pool.clone(), Arc::new(QueryStats::new(200)));
+
+        assert_eq!(repo.get("theme_mode").await.unwrap(), None);
+        repo.set("theme_mode", "light").await.unwrap();
+        repo.set("theme_mode", "dark").await.unwrap();
+        assert_eq!(repo.get("theme_mode").await.unwrap().as_deref(), Some("dark"));
+
+        let batch = HashMap::from([
+            ("language".to_string(), "zh".to_string()),
+            ("theme_mode".to_string(), "system".to_string()),
+        ]);
+        repo.set_many(&batch).await.unwrap();
+        assert_eq!(repo.get_all().await.unwrap(), batch);
+
+        repo.delete("language").await.unwrap();
+        assert!(matches!(repo.delete("language").await, Err(AppError::NotFound(_))));
+
+        db.close().await;
+    }
+
+    #[tokio::test]
+    async fn clear_keeps_listed_keys() {
+        let Some(db) = TestDb::create().await else { return };
+        let repo = PostgresConfigRepository::new(db.pool.clone(), Arc::new(QueryStats::new(200)));
+        repo.set("theme_mode", "dark").await.unwrap();
+        repo.set("language", "zh").await.unwrap();
+        repo.set("maintenance_last_run", "2025-01-01T00:00:00Z").await.unwrap();
+
+        assert_eq!(repo.clear(&["maintenance_last_run"]).await.unwrap(), 2);
+        assert_eq!(repo.get_all().await.unwrap().into_keys().collect::<Vec<_>>(), vec!["maintenance_last_run"]);
+        assert_eq!(repo.clear(&[]).await.unwrap(), 1);
+
+        db.close().await;
+    }
+}
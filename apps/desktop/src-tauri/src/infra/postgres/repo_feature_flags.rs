@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use async_trait::async_trait;
+use sqlx::PgPool;
+use crate::domain::feature_flags::{FeatureFlag, IFeatureFlagRepository, UpsertFeatureFlagCmd};
+use crate::error::AppError;
+use crate::infra::query_stats::QueryStats;
+
+const SELECT_FLAG: &str = "SELECT key, description, default_enabled, rollout_percentage, expires_at, \
+    to_char(created_at AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS') AS created_at, \
+    to_char(updated_at AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS') AS updated_at \
+    FROM feature_flags";
+
+pub struct PostgresFeatureFlagRepository {
+    pool: PgPool,
+    queries: Arc<QueryStats>,
+}
+
+impl PostgresFeatureFlagRepository {
+    pub fn new(pool: PgPool, queries: Arc<QueryStats>) -> Self {
+        Self { pool, queries }
+    }
+}
+
+#[async_trait]
+impl IFeatureFlagRepository for PostgresFeatureFlagRepository {
+    async fn list(&self) -> Result<Vec<FeatureFlag>, AppError> {
+        let flags = self.queries.observe(&format!("{} ORDER BY key", SELECT_FLAG), |sql| {
+            sqlx::query_as::<_, FeatureFlag>(sql)
+                .fetch_all(&self.pool)
+        }).await?;
+        Ok(flags)
+    }
+
+    async fn find(&self, key: &str) -> Result<Option<FeatureFlag>, AppError> {
+        let flag = self.queries.observe(&format!("{} WHERE key = $1", SELECT_FLAG), |sql| {
+            sqlx::query_as::<_, FeatureFlag>(sql)
+                .bind(key)
+                .fetch_optional(&self.pool)
+        }).await?;
+        Ok(flag)
+    }
+
+    async fn upsert(&self, cmd: &UpsertFeatureFlagCmd) -> Result<FeatureFlag, AppError> {
+        self.queries.observe("INSERT INTO feature_flags (key, description, default_enabled, rollout_percentage, expires_at, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, now(), now()) ON CONFLICT (key) DO UPDATE SET description = excluded.description, default_enabled = excluded.default_enabled, rollout_percentage = excluded.rollout_percentage, expires_at = excluded.expires_at, updated_at = excluded.updated_at", |sql| {
+            sqlx::query(sql)
+                .bind(&cmd.key)
+                .bind(&cmd.description)
+                .bind(cmd.default_enabled)
+                .bind(cmd.rollout_percentage)
+                .bind(&cmd.expires_at)
+                .execute(&self.pool)
+        }).await?;
+
+        // Fetch back to get timestamps
+        let flag = self.queries.observe_one(&format!("{} WHERE key = $1", SELECT_FLAG), |sql| {
+            sqlx::query_as::<_, FeatureFlag>(sql)
+                .bind(&cmd.key)
+                .fetch_one(&self.pool)
+        }).await?;
+        Ok(flag)
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        self.queries.observe("DELETE FROM feature_flag_overrides WHERE flag_key = $1", |sql| {
+            sqlx::query(sql)
+                .bind(key)
+                .execute(&mut *tx)
+        }).await?;
+
+        let result = self.queries.observe("DELETE FROM feature_flags WHERE key = $1", |sql| {
+            sqlx::query(sql)
+                .bind(key)
+                .execute(&mut *tx)
+        }).await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("Feature flag {} not found", key)));
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_override(&self, key: &str, user_id: &str) -> Result<Option<bool>, AppError> {
+        let enabled = self.queries.observe("SELECT enabled FROM feature_flag_overrides WHERE flag_key = $1 AND user_id = $2", |sql| {
+            sqlx::query_scalar::<_, bool>(sql)
+                .bind(key)
+                .bind(user_id)
+                .fetch_optional(&self.pool)
+        }).await?;
+        Ok(enabled)
+    }
+
+    async fn list_overrides(&self, user_id: &str) -> Result<HashMap<String, bool>, AppError> {
+        let rows = self.queries.observe("SELECT flag_key, enabled FROM feature_flag_overrides WHERE user_id = $1", |sql| {
+            sqlx::query_as::<_, (String, bool)>(sql)
+                .bind(user_id)
+                .fetch_all(&self.pool)
+        }).await?;
+        Ok(rows.into_iter().collect())
+    }
+
+    async fn set_override(&self, key: &str, user_id: &str, enabled: bool) -> Result<(), AppError> {
+        self.queries.observe("INSERT INTO feature_flag_overrides (flag_key, user_id, enabled) VALUES ($1, $2, $3) ON CONFLICT (flag_key, user_id) DO UPDATE SET enabled = excluded.enabled", |sql| {
+            sqlx::query(sql)
+                .bind(key)
+                .bind(user_id)
+                .bind(enabled)
+                .execute(&self.pool)
+        }).await?;
+        Ok(())
+    }
+
+    async fn clear_override(&self, key: &str, user_id: &str) -> Result<(), AppError> {
+        self.queries.observe("DELETE FROM feature_flag_overrides WHERE flag_key = $1 AND user_id = $2", |sql| {
+            sqlx::query(sql)
+                .bind(key)
+                .bind(user_id)
+                .execute(&self.pool)
+        }).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::postgres::TestDb;
+
+    fn flag(key: &str, rollout_percentage: Option<i64>) -> UpsertFeatureFlagCmd {
+        UpsertFeatureFlagCmd {
+            key: key.to_string(),
+            description: String::new(),
+            default_enabled: false,
+            rollout_percentage,
+            expires_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn flags_and_overrides_round_trip() {
+        let Some(db) = TestDb::create().await else { return };
+        let repo = PostgresFeatureFlagRepository::new(db.pool.clone(), Arc::new(QueryStats::new(200)));
+
+        repo.upsert(&flag("new_ui", None)).await.unwrap();
+        let updated = repo.upsert(&flag("new_ui", Some(25))).await.unwrap();
+        assert_eq!(updated.rollout_percentage, Some(25));
+        repo.upsert(&flag("beta", None)).await.unwrap();
+        assert_eq!(repo.list().await.unwrap().iter().map(|f| f.key.as_str()).collect::<Vec<_>>(), ["beta", "new_ui"]);
+
+        repo.set_override("new_ui", "alice", true).await.unwrap();
+        repo.set_override("new_ui", "alice", false).await.unwrap();
+        repo.set_override("beta", "alice", true).await.unwrap();
+        assert_eq!(repo.get_override("new_ui", "alice").await.unwrap(), Some(false));
+        assert_eq!(repo.get_override("new_ui", "bob").await.unwrap(), None);
+        assert_eq!(repo.list_overrides("alice").await.unwrap().len(), 2);
+
+        repo.clear_override("beta", "alice").await.unwrap();
+        assert_eq!(repo.get_override("beta", "alice").await.unwrap(), None);
+
+        // Deleting a flag takes its overrides along
+        repo.delete("new_ui").await.unwrap();
+        assert!(repo.find("new_ui").await.unwrap().is_none());
+        assert!(repo.list_overrides("alice").await.unwrap().is_empty());
+        assert!(matches!(repo.delete("new_ui").await, Err(AppError::NotFound(_))));
+
+        db.close().await;
+    }
+}
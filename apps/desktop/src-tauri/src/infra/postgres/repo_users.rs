@@ -0,0 +1,115 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use sqlx::PgPool;
+use crate::domain::users::{IUserRepository, User};
+use crate::error::AppError;
+use crate::infra::query_stats::QueryStats;
+
+const SELECT_USER: &str = "SELECT id, username, email, role, \
+    to_char(created_at AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS') AS created_at, \
+    to_char(updated_at AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS') AS updated_at \
+    FROM users";
+
+pub struct PostgresUserRepository {
+    pool: PgPool,
+    queries: Arc<QueryStats>,
+}
+
+impl PostgresUserRepository {
+    pub fn new(pool: PgPool, queries: Arc<QueryStats>) -> Self {
+        Self { pool, queries }
+    }
+}
+
+#[async_trait]
+impl IUserRepository for PostgresUserRepository {
+    async fn create(&self, user: User) -> Result<User, AppError> {
+        self.queries.observe("INSERT INTO users (id, username, email, role, created_at, updated_at) VALUES ($1, $2, $3, $4, now(), now())", |sql| {
+            sqlx::query(sql)
+                .bind(&user.id)
+                .bind(&user.username)
+                .bind(&user.email)
+                .bind(&user.role)
+                .execute(&self.pool)
+        }).await?;
+
+        // Fetch back to get timestamps
+        let created: User = self.queries.observe_one(&format!("{} WHERE id = $1", SELECT_USER), |sql| {
+            sqlx::query_as(sql)
+                .bind(&user.id)
+                .fetch_one(&self.pool)
+        }).await?;
+        Ok(created)
+    }
+
+    async fn list(&self) -> Result<Vec<User>, AppError> {
+        let users = self.queries.observe(&format!("{} ORDER BY users.created_at DESC", SELECT_USER), |sql| {
+            sqlx::query_as::<_, User>(sql)
+                .fetch_all(&self.pool)
+        }).await?;
+        Ok(users)
+    }
+
+    async fn find_by_id(&self, id: &str) -> Result<Option<User>, AppError> {
+        let user = self.queries.observe(&format!("{} WHERE id = $1", SELECT_USER), |sql| {
+            sqlx::query_as::<_, User>(sql)
+                .bind(id)
+                .fetch_optional(&self.pool)
+        }).await?;
+        Ok(user)
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), AppError> {
+        let result = self.queries.observe("DELETE FROM users WHERE id = $1", |sql| {
+            sqlx::query(sql)
+                .bind(id)
+                .execute(&self.pool)
+        }).await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("User {} not found", id)));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::postgres::TestDb;
+
+    fn user(id: &str, username: &str) -> User {
+        User {
+            id: id.to_string(),
+            username: username.to_string(),
+            email: Some(format!("{}@example.com", username)),
+            role: "user".to_string(),
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn users_round_trip() {
+        let Some(db) = TestDb::create().await else { return };
+        let repo = PostgresUserRepository::new(db.pool.clone(), Arc::new(QueryStats::new(200)));
+
+        let created = repo.create(user("u1", "alice")).await.unwrap();
+        assert_eq!(created.username, "alice");
+        // Timestamps come back in SQLite's CURRENT_TIMESTAMP form
+        assert_eq!(created.created_at.len(), "YYYY-MM-DD HH:MM:SS".len());
+        repo.create(user("u2", "bob")).await.unwrap();
+
+        assert_eq!(repo.list().await.unwrap().len(), 2);
+        assert_eq!(repo.find_by_id("u2").await.unwrap().map(|u| u.username).as_deref(), Some("bob"));
+
+        // Emails are unique
+        assert!(repo.create(user("u3", "alice")).await.is_err());
+
+        repo.delete("u1").await.unwrap();
+        assert!(repo.find_by_id("u1").await.unwrap().is_none());
+        assert!(matches!(repo.delete("u1").await, Err(AppError::NotFound(_))));
+
+        db.close().await;
+    }
+}
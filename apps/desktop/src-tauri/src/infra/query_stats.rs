@@ -0,0 +1,226 @@
+//! Per-statement query timing for the repositories.
+//!
+//! Every repository query runs through `QueryStats::observe`, which wraps it in
+//! a `db.query` span (statement, duration, row count), logs statements slower
+//! than `db_slow_query_ms` at WARN and aggregates statistics per statement.
+//! The span is at INFO so the default log filter keeps it.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::field::Empty;
+use tracing::{info_span, warn, Instrument};
+
+use crate::domain::diagnostics::{IQueryStats, QueryStat};
+use crate::error::AppError;
+
+#[derive(Default)]
+struct Totals {
+    calls: u64,
+    errors: u64,
+    slow: u64,
+    rows: u64,
+    total: Duration,
+    max: Duration,
+}
+
+pub struct QueryStats {
+    slow_threshold: Duration,
+    totals: Mutex<HashMap<String, Totals>>,
+}
+
+impl QueryStats {
+    pub fn new(slow_threshold_ms: u64) -> Self {
+        Self {
+            slow_threshold: Duration::from_millis(slow_threshold_ms),
+            totals: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Run the query built by `query` (which receives `statement`) and record it.
+    pub async fn observe<'q, T, F, Fut>(&self, statement: &'q str, query: F) -> Result<T, AppError>
+    where
+        T: RowCount,
+        F: FnOnce(&'q str) -> Fut,
+        Fut: Future<Output = Result<T, sqlx::Error>>,
+    {
+        self.run(statement, query(statement), T::row_count).await
+    }
+
+    /// Like `observe`, for `fetch_one` queries.
+    pub async fn observe_one<'q, T, F, Fut>(&self, statement: &'q str, query: F) -> Result<T, AppError>
+    where
+        F: FnOnce(&'q str) -> Fut,
+        Fut: Future<Output = Result<T, sqlx::Error>>,
+    {
+        self.run(statement, query(statement), |_| 1).await
+    }
+
+    async fn run<T>(
+        &self,
+        statement: &str,
+        query: impl Future<Output = Result<T, sqlx::Error>>,
+        row_count: fn(&T) -> u64,
+    ) -> Result<T, AppError> {
+        let span = info_span!("db.query", statement = %statement, duration_ms = Empty, rows = Empty);
+        let started = Instant::now();
+        let result = query.instrument(span.clone()).await;
+        let elapsed = started.elapsed();
+
+        let rows = result.as_ref().map(row_count).unwrap_or(0);
+        let duration_ms = elapsed.as_secs_f64() * 1000.0;
+        span.record("duration_ms", duration_ms);
+        span.record("rows", rows);
+
+        let slow = elapsed >= self.slow_threshold;
+        if slow {
+            warn!(statement, duration_ms, rows, "Slow query");
+        }
+
+        {
+            let mut totals = self.totals.lock().unwrap_or_else(|e| e.into_inner());
+            let t = totals.entry(statement.to_string()).or_default();
+            t.calls += 1;
+            t.errors += result.is_err() as u64;
+            t.slow += slow as u64;
+            t.rows += rows;
+            t.total += elapsed;
+            t.max = t.max.max(elapsed);
+        }
+
+        Ok(result?)
+    }
+}
+
+impl IQueryStats for QueryStats {
+    fn snapshot(&self) -> Vec<QueryStat> {
+        let totals = self.totals.lock().unwrap_or_else(|e| e.into_inner());
+        let mut stats: Vec<QueryStat> = totals
+            .iter()
+            .map(|(statement, t)| {
+                let total_ms = t.total.as_secs_f64() * 1000.0;
+                QueryStat {
+                    statement: statement.clone(),
+                    calls: t.calls,
+                    errors: t.errors,
+                    slow: t.slow,
+                    rows: t.rows,
+                    total_ms,
+                    mean_ms: if t.calls > 0 { total_ms / t.calls as f64 } else { 0.0 },
+                    max_ms: t.max.as_secs_f64() * 1000.0,
+                }
+            })
+            .collect();
+        stats.sort_by(|a, b| b.total_ms.total_cmp(&a.total_ms));
+        stats
+    }
+
+    fn reset(&self) {
+        self.totals.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+/// Rows returned or affected by a query, for the span and statistics.
+pub trait RowCount {
+    fn row_count(&self) -> u64;
+}
+
+impl<T> RowCount for Vec<T> {
+    fn row_count(&self) -> u64 {
+        self.len() as u64
+    }
+}
+
+impl<T> RowCount for Option<T> {
+    fn row_count(&self) -> u64 {
+        self.is_some() as u64
+    }
+}
+
+impl RowCount for sqlx::sqlite::SqliteQueryResult {
+    fn row_count(&self) -> u64 {
+        self.rows_affected()
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl RowCount for sqlx::postgres::PgQueryResult {
+    fn row_count(&self) -> u64 {
+        self.rows_affected()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn rows(n: usize) -> Result<Vec<u8>, sqlx::Error> {
+        Ok(vec![0; n])
+    }
+
+    async fn slow_rows(n: usize) -> Result<Vec<u8>, sqlx::Error> {
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        rows(n).await
+    }
+
+    async fn failing() -> Result<Vec<u8>, sqlx::Error> {
+        Err(sqlx::Error::RowNotFound)
+    }
+
+    fn stat<'a>(stats: &'a [QueryStat], statement: &str) -> &'a QueryStat {
+        stats.iter().find(|s| s.statement == statement).unwrap()
+    }
+
+    #[tokio::test]
+    async fn counts_calls_and_rows_per_statement() {
+        let stats = QueryStats::new(1000);
+        stats.observe("SELECT a", |_| rows(2)).await.unwrap();
+        stats.observe("SELECT a", |_| rows(3)).await.unwrap();
+        stats.observe_one("SELECT b", |_| async { Ok::<_, sqlx::Error>(7) }).await.unwrap();
+
+        let snapshot = stats.snapshot();
+        let a = stat(&snapshot, "SELECT a");
+        assert_eq!((a.calls, a.rows, a.errors, a.slow), (2, 5, 0, 0));
+        let b = stat(&snapshot, "SELECT b");
+        assert_eq!((b.calls, b.rows), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn queries_at_or_above_the_threshold_are_slow() {
+        let stats = QueryStats::new(20);
+        stats.observe("SELECT fast", |_| rows(1)).await.unwrap();
+        stats.observe("SELECT slow", |_| slow_rows(1)).await.unwrap();
+
+        let snapshot = stats.snapshot();
+        assert_eq!(stat(&snapshot, "SELECT fast").slow, 0);
+        let slow = stat(&snapshot, "SELECT slow");
+        assert_eq!(slow.slow, 1);
+        assert!(slow.max_ms >= 20.0);
+        // Sorted by total time, slowest first
+        assert_eq!(snapshot[0].statement, "SELECT slow");
+    }
+
+    #[tokio::test]
+    async fn errors_are_counted_and_returned() {
+        let stats = QueryStats::new(1000);
+        let result = stats.observe("SELECT missing", |_| failing()).await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+        stats.observe("SELECT missing", |_| rows(1)).await.unwrap();
+
+        let snapshot = stats.snapshot();
+        let missing = stat(&snapshot, "SELECT missing");
+        assert_eq!((missing.calls, missing.errors, missing.rows), (2, 1, 1));
+    }
+
+    #[tokio::test]
+    async fn reset_clears_every_statement() {
+        let stats = QueryStats::new(1000);
+        stats.observe("SELECT a", |_| rows(1)).await.unwrap();
+        stats.reset();
+        assert!(stats.snapshot().is_empty());
+
+        stats.observe("SELECT a", |_| rows(1)).await.unwrap();
+        assert_eq!(stat(&stats.snapshot(), "SELECT a").calls, 1);
+    }
+}
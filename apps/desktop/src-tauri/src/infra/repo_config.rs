@@ -1,45 +1,95 @@
 use std::collections::HashMap;
+use std::sync::Arc;
 use async_trait::async_trait;
-use sqlx::SqlitePool;
 use crate::domain::config::IConfigRepository;
 use crate::error::AppError;
+use crate::infra::db::Database;
+
+const UPSERT_SQL: &str = "INSERT INTO system_settings (key, value, updated_at) VALUES (?, ?, CURRENT_TIMESTAMP) ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at";
 
 pub struct SqliteConfigRepository {
-    pool: SqlitePool,
+    db: Arc<Database>,
 }
 
 impl SqliteConfigRepository {
-    pub fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
     }
 }
 
 #[async_trait]
 impl IConfigRepository for SqliteConfigRepository {
     async fn get(&self, key: &str) -> Result<Option<String>, AppError> {
-        let result = sqlx::query_scalar::<_, String>("SELECT value FROM system_settings WHERE key = ?")
-            .bind(key)
-            .fetch_optional(&self.pool)
-            .await?;
+        let pool = self.db.pool().await;
+        let result = self.db.queries().observe("SELECT value FROM system_settings WHERE key = ?", |sql| {
+            sqlx::query_scalar::<_, String>(sql)
+                .bind(key)
+                .fetch_optional(&*pool)
+        }).await?;
         Ok(result)
     }
 
     async fn set(&self, key: &str, value: &str) -> Result<(), AppError> {
         // Upsert (Insert or Update)
-        sqlx::query("INSERT INTO system_settings (key, value, updated_at) VALUES (?, ?, CURRENT_TIMESTAMP) ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at")
-            .bind(key)
-            .bind(value)
-            .execute(&self.pool)
-            .await?;
+        let pool = self.db.pool().await;
+        self.db.queries().observe(UPSERT_SQL, |sql| {
+            sqlx::query(sql)
+                .bind(key)
+                .bind(value)
+                .execute(&*pool)
+        }).await?;
         Ok(())
     }
 
     async fn get_all(&self) -> Result<HashMap<String, String>, AppError> {
-        let rows = sqlx::query_as::<_, (String, String)>("SELECT key, value FROM system_settings")
-            .fetch_all(&self.pool)
-            .await?;
+        let pool = self.db.pool().await;
+        let rows = self.db.queries().observe("SELECT key, value FROM system_settings", |sql| {
+            sqlx::query_as::<_, (String, String)>(sql)
+                .fetch_all(&*pool)
+        }).await?;
         
         let map = rows.into_iter().collect();
         Ok(map)
     }
+
+    async fn set_many(&self, values: &HashMap<String, String>) -> Result<(), AppError> {
+        let pool = self.db.pool().await;
+        let mut tx = pool.begin().await?;
+        for (key, value) in values {
+            self.db.queries().observe(UPSERT_SQL, |sql| {
+                sqlx::query(sql)
+                    .bind(key)
+                    .bind(value)
+                    .execute(&mut *tx)
+            }).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        let pool = self.db.pool().await;
+        let result = self.db.queries().observe("DELETE FROM system_settings WHERE key = ?", |sql| {
+            sqlx::query(sql)
+                .bind(key)
+                .execute(&*pool)
+        }).await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("Setting {} not found", key)));
+        }
+        Ok(())
+    }
+
+    async fn clear(&self, keep: &[&str]) -> Result<u64, AppError> {
+        let pool = self.db.pool().await;
+        let keep = serde_json::to_string(keep)
+            .map_err(|e| AppError::Unknown(format!("Failed to serialize kept keys: {}", e)))?;
+        let result = self.db.queries().observe("DELETE FROM system_settings WHERE key NOT IN (SELECT value FROM json_each(?))", |sql| {
+            sqlx::query(sql)
+                .bind(&keep)
+                .execute(&*pool)
+        }).await?;
+        Ok(result.rows_affected())
+    }
 }
@@ -0,0 +1,115 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use crate::domain::downloads::{Download, DownloadState, IDownloadRepository, TransferProgress};
+use crate::error::AppError;
+use crate::infra::db::Database;
+
+pub struct SqliteDownloadRepository {
+    db: Arc<Database>,
+}
+
+impl SqliteDownloadRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl IDownloadRepository for SqliteDownloadRepository {
+    async fn insert(&self, download: &Download) -> Result<Download, AppError> {
+        let pool = self.db.pool().await;
+        self.db.queries().observe("INSERT INTO downloads (id, url, path, state, bytes_done, total_bytes, etag, last_modified, sha256, overwrite, error, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)", |sql| {
+            sqlx::query(sql)
+                .bind(&download.id)
+                .bind(&download.url)
+                .bind(&download.path)
+                .bind(download.state)
+                .bind(download.bytes_done)
+                .bind(download.total_bytes)
+                .bind(&download.etag)
+                .bind(&download.last_modified)
+                .bind(&download.sha256)
+                .bind(download.overwrite)
+                .bind(&download.error)
+                .execute(&*pool)
+        }).await?;
+
+        // Fetch back to get timestamps
+        let created = self.db.queries().observe_one("SELECT * FROM downloads WHERE id = ?", |sql| {
+            sqlx::query_as::<_, Download>(sql)
+                .bind(&download.id)
+                .fetch_one(&*pool)
+        }).await?;
+        Ok(created)
+    }
+
+    async fn list(&self) -> Result<Vec<Download>, AppError> {
+        let pool = self.db.pool().await;
+        let downloads = self.db.queries().observe("SELECT * FROM downloads ORDER BY created_at DESC, rowid DESC", |sql| {
+            sqlx::query_as::<_, Download>(sql)
+                .fetch_all(&*pool)
+        }).await?;
+        Ok(downloads)
+    }
+
+    async fn find(&self, id: &str) -> Result<Option<Download>, AppError> {
+        let pool = self.db.pool().await;
+        let download = self.db.queries().observe("SELECT * FROM downloads WHERE id = ?", |sql| {
+            sqlx::query_as::<_, Download>(sql)
+                .bind(id)
+                .fetch_optional(&*pool)
+        }).await?;
+        Ok(download)
+    }
+
+    async fn list_queued(&self) -> Result<Vec<Download>, AppError> {
+        let pool = self.db.pool().await;
+        let downloads = self.db.queries().observe("SELECT * FROM downloads WHERE state = ? ORDER BY created_at, rowid", |sql| {
+            sqlx::query_as::<_, Download>(sql)
+                .bind(DownloadState::Queued)
+                .fetch_all(&*pool)
+        }).await?;
+        Ok(downloads)
+    }
+
+    async fn set_state(&self, id: &str, state: DownloadState, error: Option<&str>) -> Result<(), AppError> {
+        let pool = self.db.pool().await;
+        let result = self.db.queries().observe("UPDATE downloads SET state = ?, error = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?", |sql| {
+            sqlx::query(sql)
+                .bind(state)
+                .bind(error)
+                .bind(id)
+                .execute(&*pool)
+        }).await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("Download {} not found", id)));
+        }
+        Ok(())
+    }
+
+    async fn save_progress(&self, id: &str, progress: &TransferProgress) -> Result<(), AppError> {
+        let pool = self.db.pool().await;
+        self.db.queries().observe("UPDATE downloads SET bytes_done = ?, total_bytes = ?, etag = ?, last_modified = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?", |sql| {
+            sqlx::query(sql)
+                .bind(progress.bytes as i64)
+                .bind(progress.total.map(|total| total as i64))
+                .bind(&progress.etag)
+                .bind(&progress.last_modified)
+                .bind(id)
+                .execute(&*pool)
+        }).await?;
+        Ok(())
+    }
+
+    async fn requeue_interrupted(&self) -> Result<u64, AppError> {
+        let pool = self.db.pool().await;
+        let result = self.db.queries().observe("UPDATE downloads SET state = ?, updated_at = CURRENT_TIMESTAMP WHERE state = ?", |sql| {
+            sqlx::query(sql)
+                .bind(DownloadState::Queued)
+                .bind(DownloadState::Running)
+                .execute(&*pool)
+        }).await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use async_trait::async_trait;
+use crate::domain::feature_flags::{FeatureFlag, IFeatureFlagRepository, UpsertFeatureFlagCmd};
+use crate::error::AppError;
+use crate::infra::db::Database;
+
+pub struct SqliteFeatureFlagRepository {
+    db: Arc<Database>,
+}
+
+impl SqliteFeatureFlagRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl IFeatureFlagRepository for SqliteFeatureFlagRepository {
+    async fn list(&self) -> Result<Vec<FeatureFlag>, AppError> {
+        let pool = self.db.pool().await;
+        let flags = self.db.queries().observe("SELECT * FROM feature_flags ORDER BY key", |sql| {
+            sqlx::query_as::<_, FeatureFlag>(sql)
+                .fetch_all(&*pool)
+        }).await?;
+        Ok(flags)
+    }
+
+    async fn find(&self, key: &str) -> Result<Option<FeatureFlag>, AppError> {
+        let pool = self.db.pool().await;
+        let flag = self.db.queries().observe("SELECT * FROM feature_flags WHERE key = ?", |sql| {
+            sqlx::query_as::<_, FeatureFlag>(sql)
+                .bind(key)
+                .fetch_optional(&*pool)
+        }).await?;
+        Ok(flag)
+    }
+
+    async fn upsert(&self, cmd: &UpsertFeatureFlagCmd) -> Result<FeatureFlag, AppError> {
+        let pool = self.db.pool().await;
+        self.db.queries().observe("INSERT INTO feature_flags (key, description, default_enabled, rollout_percentage, expires_at, created_at, updated_at) VALUES (?, ?, ?, ?, ?, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP) ON CONFLICT(key) DO UPDATE SET description = excluded.description, default_enabled = excluded.default_enabled, rollout_percentage = excluded.rollout_percentage, expires_at = excluded.expires_at, updated_at = excluded.updated_at", |sql| {
+            sqlx::query(sql)
+                .bind(&cmd.key)
+                .bind(&cmd.description)
+                .bind(cmd.default_enabled)
+                .bind(cmd.rollout_percentage)
+                .bind(&cmd.expires_at)
+                .execute(&*pool)
+        }).await?;
+
+        // Fetch back to get timestamps
+        let flag = self.db.queries().observe_one("SELECT * FROM feature_flags WHERE key = ?", |sql| {
+            sqlx::query_as::<_, FeatureFlag>(sql)
+                .bind(&cmd.key)
+                .fetch_one(&*pool)
+        }).await?;
+        Ok(flag)
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        let pool = self.db.pool().await;
+        let mut tx = pool.begin().await?;
+
+        self.db.queries().observe("DELETE FROM feature_flag_overrides WHERE flag_key = ?", |sql| {
+            sqlx::query(sql)
+                .bind(key)
+                .execute(&mut *tx)
+        }).await?;
+
+        let result = self.db.queries().observe("DELETE FROM feature_flags WHERE key = ?", |sql| {
+            sqlx::query(sql)
+                .bind(key)
+                .execute(&mut *tx)
+        }).await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("Feature flag {} not found", key)));
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_override(&self, key: &str, user_id: &str) -> Result<Option<bool>, AppError> {
+        let pool = self.db.pool().await;
+        let enabled = self.db.queries().observe("SELECT enabled FROM feature_flag_overrides WHERE flag_key = ? AND user_id = ?", |sql| {
+            sqlx::query_scalar::<_, bool>(sql)
+                .bind(key)
+                .bind(user_id)
+                .fetch_optional(&*pool)
+        }).await?;
+        Ok(enabled)
+    }
+
+    async fn list_overrides(&self, user_id: &str) -> Result<HashMap<String, bool>, AppError> {
+        let pool = self.db.pool().await;
+        let rows = self.db.queries().observe("SELECT flag_key, enabled FROM feature_flag_overrides WHERE user_id = ?", |sql| {
+            sqlx::query_as::<_, (String, bool)>(sql)
+                .bind(user_id)
+                .fetch_all(&*pool)
+        }).await?;
+        Ok(rows.into_iter().collect())
+    }
+
+    async fn set_override(&self, key: &str, user_id: &str, enabled: bool) -> Result<(), AppError> {
+        let pool = self.db.pool().await;
+        self.db.queries().observe("INSERT INTO feature_flag_overrides (flag_key, user_id, enabled) VALUES (?, ?, ?) ON CONFLICT(flag_key, user_id) DO UPDATE SET enabled = excluded.enabled", |sql| {
+            sqlx::query(sql)
+                .bind(key)
+                .bind(user_id)
+                .bind(enabled)
+                .execute(&*pool)
+        }).await?;
+        Ok(())
+    }
+
+    async fn clear_override(&self, key: &str, user_id: &str) -> Result<(), AppError> {
+        let pool = self.db.pool().await;
+        self.db.queries().observe("DELETE FROM feature_flag_overrides WHERE flag_key = ? AND user_id = ?", |sql| {
+            sqlx::query(sql)
+                .bind(key)
+                .bind(user_id)
+                .execute(&*pool)
+        }).await?;
+        Ok(())
+    }
+}
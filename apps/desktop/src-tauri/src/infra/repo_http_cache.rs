@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use async_trait::async_trait;
+use crate::domain::http::{CachedResponse, HttpCacheEntry, IHttpCacheRepository};
+use crate::error::AppError;
+use crate::infra::db::Database;
+
+pub struct SqliteHttpCacheRepository {
+    db: Arc<Database>,
+}
+
+impl SqliteHttpCacheRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct CachedRow {
+    method: String,
+    url: String,
+    status: i64,
+    headers: String,
+    body: Vec<u8>,
+    vary: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    expires_at: i64,
+}
+
+fn cache_key(method: &str, url: &str) -> String {
+    format!("{} {}", method, url)
+}
+
+fn to_json(map: &HashMap<String, String>) -> Result<String, AppError> {
+    serde_json::to_string(map).map_err(|e| AppError::Unknown(format!("Failed to serialize cache entry: {}", e)))
+}
+
+#[async_trait]
+impl IHttpCacheRepository for SqliteHttpCacheRepository {
+    async fn find(&self, method: &str, url: &str) -> Result<Option<CachedResponse>, AppError> {
+        let pool = self.db.pool().await;
+        let key = cache_key(method, url);
+        let row = self.db.queries().observe("SELECT method, url, status, headers, body, vary, etag, last_modified, expires_at FROM http_cache WHERE key = ?", |sql| {
+            sqlx::query_as::<_, CachedRow>(sql)
+                .bind(&key)
+                .fetch_optional(&*pool)
+        }).await?;
+
+        // An unreadable entry is treated as missing and replaced on the next store
+        Ok(row.and_then(|row| {
+            Some(CachedResponse {
+                method: row.method,
+                url: row.url,
+                status: u16::try_from(row.status).ok()?,
+                headers: serde_json::from_str(&row.headers).ok()?,
+                body: row.body,
+                vary: serde_json::from_str(&row.vary).ok()?,
+                etag: row.etag,
+                last_modified: row.last_modified,
+                expires_at: row.expires_at,
+            })
+        }))
+    }
+
+    async fn store(&self, response: &CachedResponse, max_entries: u32, max_bytes: u64) -> Result<(), AppError> {
+        let pool = self.db.pool().await;
+        let key = cache_key(&response.method, &response.url);
+        let headers = to_json(&response.headers)?;
+        let vary = to_json(&response.vary)?;
+        self.db.queries().observe("INSERT OR REPLACE INTO http_cache (key, method, url, status, headers, body, vary, etag, last_modified, expires_at, stored_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)", |sql| {
+            sqlx::query(sql)
+                .bind(&key)
+                .bind(&response.method)
+                .bind(&response.url)
+                .bind(response.status as i64)
+                .bind(&headers)
+                .bind(&response.body)
+                .bind(&vary)
+                .bind(&response.etag)
+                .bind(&response.last_modified)
+                .bind(response.expires_at)
+                .execute(&*pool)
+        }).await?;
+
+        self.db.queries().observe("DELETE FROM http_cache WHERE key NOT IN (SELECT key FROM http_cache ORDER BY stored_at DESC, rowid DESC LIMIT ?)", |sql| {
+            sqlx::query(sql)
+                .bind(max_entries as i64)
+                .execute(&*pool)
+        }).await?;
+
+        // Newest first, keep entries while the running total of their bodies fits
+        self.db.queries().observe("DELETE FROM http_cache WHERE key IN (SELECT key FROM (SELECT key, SUM(length(body)) OVER (ORDER BY stored_at DESC, rowid DESC) AS total FROM http_cache) WHERE total > ?)", |sql| {
+            sqlx::query(sql)
+                .bind(max_bytes.min(i64::MAX as u64) as i64)
+                .execute(&*pool)
+        }).await?;
+        Ok(())
+    }
+
+    async fn remove(&self, method: &str, url: &str) -> Result<(), AppError> {
+        let pool = self.db.pool().await;
+        let key = cache_key(method, url);
+        self.db.queries().observe("DELETE FROM http_cache WHERE key = ?", |sql| {
+            sqlx::query(sql)
+                .bind(&key)
+                .execute(&*pool)
+        }).await?;
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<HttpCacheEntry>, AppError> {
+        let pool = self.db.pool().await;
+        let entries = self.db.queries().observe("SELECT method, url, status, length(body) AS size, etag, last_modified, expires_at, stored_at FROM http_cache ORDER BY stored_at DESC, rowid DESC", |sql| {
+            sqlx::query_as::<_, HttpCacheEntry>(sql)
+                .fetch_all(&*pool)
+        }).await?;
+        Ok(entries)
+    }
+
+    async fn clear(&self) -> Result<u64, AppError> {
+        let pool = self.db.pool().await;
+        let result = self.db.queries().observe("DELETE FROM http_cache", |sql| {
+            sqlx::query(sql)
+                .execute(&*pool)
+        }).await?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::db::DbOptions;
+
+    fn response(url: &str, size: usize) -> CachedResponse {
+        CachedResponse {
+            method: "GET".to_string(),
+            url: url.to_string(),
+            status: 200,
+            headers: HashMap::new(),
+            body: vec![b'x'; size],
+            vary: HashMap::new(),
+            etag: None,
+            last_modified: None,
+            expires_at: 0,
+        }
+    }
+
+    async fn urls(repo: &SqliteHttpCacheRepository) -> Vec<String> {
+        repo.list().await.unwrap().into_iter().map(|entry| entry.url).collect()
+    }
+
+    #[tokio::test]
+    async fn store_evicts_the_oldest_beyond_the_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(Database::open(dir.path().join("app.db"), DbOptions::default()).await.unwrap());
+        let repo = SqliteHttpCacheRepository::new(db);
+
+        for (url, size) in [("https://a/1", 400), ("https://a/2", 300), ("https://a/3", 200)] {
+            repo.store(&response(url, size), 10, 1000).await.unwrap();
+        }
+        assert_eq!(urls(&repo).await, ["https://a/3", "https://a/2", "https://a/1"]);
+
+        // 500 + 200 + 300 > 1000: the oldest goes
+        repo.store(&response("https://a/4", 500), 10, 1000).await.unwrap();
+        assert_eq!(urls(&repo).await, ["https://a/4", "https://a/3", "https://a/2"]);
+
+        // Replacing an entry frees its old size
+        repo.store(&response("https://a/2", 100), 10, 1000).await.unwrap();
+        assert_eq!(urls(&repo).await, ["https://a/2", "https://a/4", "https://a/3"]);
+
+        repo.store(&response("https://a/5", 10), 2, 1000).await.unwrap();
+        assert_eq!(urls(&repo).await, ["https://a/5", "https://a/2"]);
+        assert!(repo.find("GET", "https://a/4").await.unwrap().is_none());
+    }
+}
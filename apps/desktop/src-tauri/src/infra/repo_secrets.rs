@@ -0,0 +1,75 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use crate::domain::secrets::{ISecretRepository, SecretInfo};
+use crate::error::AppError;
+use crate::infra::db::Database;
+use crate::infra::crypto::SecretCipher;
+
+pub struct SqliteSecretRepository {
+    db: Arc<Database>,
+    cipher: Arc<SecretCipher>,
+}
+
+impl SqliteSecretRepository {
+    pub fn new(db: Arc<Database>, cipher: Arc<SecretCipher>) -> Self {
+        Self { db, cipher }
+    }
+}
+
+#[async_trait]
+impl ISecretRepository for SqliteSecretRepository {
+    async fn get(&self, key: &str) -> Result<Option<String>, AppError> {
+        let pool = self.db.pool().await;
+        let row = self.db.queries().observe("SELECT nonce, ciphertext FROM secret_settings WHERE key = ?", |sql| {
+            sqlx::query_as::<_, (Vec<u8>, Vec<u8>)>(sql)
+                .bind(key)
+                .fetch_optional(&*pool)
+        }).await?;
+
+        let Some((nonce, ciphertext)) = row else {
+            return Ok(None);
+        };
+
+        let plaintext = self.cipher.decrypt(&nonce, &ciphertext, key.as_bytes())?;
+        let value = String::from_utf8(plaintext)
+            .map_err(|_| AppError::Crypto(format!("Secret {} is not valid UTF-8", key)))?;
+        Ok(Some(value))
+    }
+
+    async fn set(&self, key: &str, value: &str) -> Result<(), AppError> {
+        let (nonce, ciphertext) = self.cipher.encrypt(value.as_bytes(), key.as_bytes())?;
+
+        let pool = self.db.pool().await;
+        self.db.queries().observe("INSERT INTO secret_settings (key, nonce, ciphertext, updated_at) VALUES (?, ?, ?, CURRENT_TIMESTAMP) ON CONFLICT(key) DO UPDATE SET nonce = excluded.nonce, ciphertext = excluded.ciphertext, updated_at = excluded.updated_at", |sql| {
+            sqlx::query(sql)
+                .bind(key)
+                .bind(nonce)
+                .bind(ciphertext)
+                .execute(&*pool)
+        }).await?;
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        let pool = self.db.pool().await;
+        let result = self.db.queries().observe("DELETE FROM secret_settings WHERE key = ?", |sql| {
+            sqlx::query(sql)
+                .bind(key)
+                .execute(&*pool)
+        }).await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("Secret {} not found", key)));
+        }
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<SecretInfo>, AppError> {
+        let pool = self.db.pool().await;
+        let secrets = self.db.queries().observe("SELECT key, updated_at FROM secret_settings ORDER BY key", |sql| {
+            sqlx::query_as::<_, SecretInfo>(sql)
+                .fetch_all(&*pool)
+        }).await?;
+        Ok(secrets)
+    }
+}
@@ -1,58 +1,68 @@
+use std::sync::Arc;
 use async_trait::async_trait;
-use sqlx::SqlitePool;
 use crate::domain::users::{IUserRepository, User};
 use crate::error::AppError;
+use crate::infra::db::Database;
 
 pub struct SqliteUserRepository {
-    pool: SqlitePool,
+    db: Arc<Database>,
 }
 
 impl SqliteUserRepository {
-    pub fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
     }
 }
 
 #[async_trait]
 impl IUserRepository for SqliteUserRepository {
     async fn create(&self, user: User) -> Result<User, AppError> {
-        sqlx::query("INSERT INTO users (id, username, email, role, created_at, updated_at) VALUES (?, ?, ?, ?, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)")
-            .bind(&user.id)
-            .bind(&user.username)
-            .bind(&user.email)
-            .bind("user") // Default role or from struct if we add it column
-            .execute(&self.pool)
-            .await?;
+        let pool = self.db.pool().await;
+        self.db.queries().observe("INSERT INTO users (id, username, email, role, created_at, updated_at) VALUES (?, ?, ?, ?, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)", |sql| {
+            sqlx::query(sql)
+                .bind(&user.id)
+                .bind(&user.username)
+                .bind(&user.email)
+                .bind("user") // Default role or from struct if we add it column
+                .execute(&*pool)
+        }).await?;
         
         // Fetch back to get timestamps
-        let created: User = sqlx::query_as("SELECT * FROM users WHERE id = ?")
-            .bind(&user.id)
-            .fetch_one(&self.pool)
-            .await?;
+        let created: User = self.db.queries().observe_one("SELECT * FROM users WHERE id = ?", |sql| {
+            sqlx::query_as(sql)
+                .bind(&user.id)
+                .fetch_one(&*pool)
+        }).await?;
 
         Ok(created)
     }
 
     async fn list(&self) -> Result<Vec<User>, AppError> {
-        let users = sqlx::query_as::<_, User>("SELECT * FROM users ORDER BY created_at DESC")
-            .fetch_all(&self.pool)
-            .await?;
+        let pool = self.db.pool().await;
+        let users = self.db.queries().observe("SELECT * FROM users ORDER BY created_at DESC", |sql| {
+            sqlx::query_as::<_, User>(sql)
+                .fetch_all(&*pool)
+        }).await?;
         Ok(users)
     }
 
     async fn find_by_id(&self, id: &str) -> Result<Option<User>, AppError> {
-        let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = ?")
-            .bind(id)
-            .fetch_optional(&self.pool)
-            .await?;
+        let pool = self.db.pool().await;
+        let user = self.db.queries().observe("SELECT * FROM users WHERE id = ?", |sql| {
+            sqlx::query_as::<_, User>(sql)
+                .bind(id)
+                .fetch_optional(&*pool)
+        }).await?;
         Ok(user)
     }
 
     async fn delete(&self, id: &str) -> Result<(), AppError> {
-        let result = sqlx::query("DELETE FROM users WHERE id = ?")
-            .bind(id)
-            .execute(&self.pool)
-            .await?;
+        let pool = self.db.pool().await;
+        let result = self.db.queries().observe("DELETE FROM users WHERE id = ?", |sql| {
+            sqlx::query(sql)
+                .bind(id)
+                .execute(&*pool)
+        }).await?;
             
         if result.rows_affected() == 0 {
             return Err(AppError::NotFound(format!("User {} not found", id)));
@@ -0,0 +1,209 @@
+//! Settings key migrations.
+//!
+//! Schema migrations (`./migrations`) change tables; these versioned transforms change
+//! the *contents* of `system_settings` when a key is renamed or its value format changes
+//! between releases. Each migration runs once, inside a transaction, and its version is
+//! recorded in `settings_migrations`.
+//!
+//! To add one, append to `MIGRATIONS` with the next version number:
+//! ```ignore
+//! SettingsMigration {
+//!     version: 1,
+//!     description: "Rename theme to theme_mode",
+//!     ops: &[SettingsOp::Rename { from: "theme", to: "theme_mode" }],
+//! },
+//! ```
+
+use sqlx::{Sqlite, SqlitePool, Transaction};
+use tracing::info;
+
+use crate::error::AppError;
+
+/// A single transform applied to `system_settings`.
+#[allow(dead_code)] // Variants are used as migrations get added
+pub enum SettingsOp {
+    /// Move a value to a new key. If `to` already has a value, it wins and `from` is dropped.
+    Rename { from: &'static str, to: &'static str },
+    /// Rewrite a value. Returning `None` drops the key.
+    Convert { key: &'static str, convert: fn(&str) -> Option<String> },
+    /// Remove a key.
+    Drop { key: &'static str },
+}
+
+pub struct SettingsMigration {
+    pub version: i64,
+    pub description: &'static str,
+    pub ops: &'static [SettingsOp],
+}
+
+/// Ordered by version. Never edit or remove an entry that has shipped.
+pub const MIGRATIONS: &[SettingsMigration] = &[];
+
+/// Apply all pending settings migrations.
+pub async fn run(pool: &SqlitePool) -> Result<(), AppError> {
+    run_migrations(pool, MIGRATIONS).await
+}
+
+async fn run_migrations(pool: &SqlitePool, migrations: &[SettingsMigration]) -> Result<(), AppError> {
+    let applied: Vec<i64> = sqlx::query_scalar("SELECT version FROM settings_migrations")
+        .fetch_all(pool)
+        .await?;
+
+    for migration in migrations.iter().filter(|m| !applied.contains(&m.version)) {
+        let mut tx = pool.begin().await?;
+
+        for op in migration.ops {
+            apply(&mut tx, op).await?;
+        }
+
+        sqlx::query("INSERT INTO settings_migrations (version, description) VALUES (?, ?)")
+            .bind(migration.version)
+            .bind(migration.description)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        info!("Applied settings migration {}: {}", migration.version, migration.description);
+    }
+
+    Ok(())
+}
+
+async fn apply(tx: &mut Transaction<'_, Sqlite>, op: &SettingsOp) -> Result<(), AppError> {
+    match op {
+        SettingsOp::Rename { from, to } => {
+            sqlx::query("INSERT OR IGNORE INTO system_settings (key, value, updated_at) SELECT ?, value, CURRENT_TIMESTAMP FROM system_settings WHERE key = ?")
+                .bind(to)
+                .bind(from)
+                .execute(&mut **tx)
+                .await?;
+            delete(tx, from).await?;
+        }
+        SettingsOp::Convert { key, convert } => {
+            let value: Option<String> = sqlx::query_scalar("SELECT value FROM system_settings WHERE key = ?")
+                .bind(key)
+                .fetch_optional(&mut **tx)
+                .await?;
+
+            match value.as_deref().map(convert) {
+                Some(Some(converted)) => {
+                    sqlx::query("UPDATE system_settings SET value = ?, updated_at = CURRENT_TIMESTAMP WHERE key = ?")
+                        .bind(converted)
+                        .bind(key)
+                        .execute(&mut **tx)
+                        .await?;
+                }
+                Some(None) => delete(tx, key).await?,
+                None => {}
+            }
+        }
+        SettingsOp::Drop { key } => delete(tx, key).await?,
+    }
+    Ok(())
+}
+
+async fn delete(tx: &mut Transaction<'_, Sqlite>, key: &str) -> Result<(), AppError> {
+    sqlx::query("DELETE FROM system_settings WHERE key = ?")
+        .bind(key)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    fn upper(value: &str) -> Option<String> {
+        Some(value.to_uppercase())
+    }
+
+    fn drop_legacy(value: &str) -> Option<String> {
+        (value != "legacy").then(|| value.to_string())
+    }
+
+    const TEST_MIGRATIONS: &[SettingsMigration] = &[
+        SettingsMigration {
+            version: 1,
+            description: "Rename theme to theme_mode, lang to language",
+            ops: &[
+                SettingsOp::Rename { from: "theme", to: "theme_mode" },
+                SettingsOp::Rename { from: "lang", to: "language" },
+            ],
+        },
+        SettingsMigration {
+            version: 2,
+            description: "Upper-case the region, drop legacy layouts",
+            ops: &[
+                SettingsOp::Convert { key: "region", convert: upper },
+                SettingsOp::Convert { key: "layout", convert: drop_legacy },
+                SettingsOp::Convert { key: "missing", convert: upper },
+            ],
+        },
+        SettingsMigration {
+            version: 3,
+            description: "Drop telemetry",
+            ops: &[SettingsOp::Drop { key: "telemetry" }],
+        },
+    ];
+
+    async fn pool(settings: &[(&str, &str)]) -> SqlitePool {
+        let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+        crate::infra::db_migrations::MIGRATOR.run(&pool).await.unwrap();
+        for (key, value) in settings {
+            sqlx::query("INSERT INTO system_settings (key, value) VALUES (?, ?)")
+                .bind(key)
+                .bind(value)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        pool
+    }
+
+    async fn settings(pool: &SqlitePool) -> Vec<(String, String)> {
+        sqlx::query_as("SELECT key, value FROM system_settings ORDER BY key")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn ops_rewrite_the_stored_settings() {
+        let pool = pool(&[
+            ("theme", "dark"),
+            // Both keys set: the new one wins
+            ("lang", "de"),
+            ("language", "fr"),
+            ("region", "eu"),
+            ("layout", "legacy"),
+            ("telemetry", "true"),
+        ])
+        .await;
+
+        run_migrations(&pool, TEST_MIGRATIONS).await.unwrap();
+
+        let expected = [("language", "fr"), ("region", "EU"), ("theme_mode", "dark")];
+        let expected: Vec<(String, String)> = expected.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        assert_eq!(settings(&pool).await, expected);
+    }
+
+    #[tokio::test]
+    async fn each_version_runs_once() {
+        let pool = pool(&[("region", "eu")]).await;
+        run_migrations(&pool, &TEST_MIGRATIONS[..2]).await.unwrap();
+
+        // A later release adds version 3; 1 and 2 must not run again
+        sqlx::query("UPDATE system_settings SET value = 'us' WHERE key = 'region'").execute(&pool).await.unwrap();
+        run_migrations(&pool, TEST_MIGRATIONS).await.unwrap();
+        run_migrations(&pool, TEST_MIGRATIONS).await.unwrap();
+
+        assert_eq!(settings(&pool).await, [("region".to_string(), "us".to_string())]);
+        let versions: Vec<i64> = sqlx::query_scalar("SELECT version FROM settings_migrations ORDER BY version")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(versions, [1, 2, 3]);
+    }
+}
@@ -1,16 +1,49 @@
 use tauri::{AppHandle, Manager, State};
 use tracing::info;
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::process::Command;
 use crate::infra::logging::LogPayload;
 use crate::error::AppError;
 use crate::application::{
-    ConfigCommandHandler, ConfigQueryHandler,
+    ArchiveCommandHandler, AutostartService, BackupCommandHandler, BackupQueryHandler,
+    ConfigCommandHandler, ConfigQueryHandler, CookieCommandHandler, CookieQueryHandler, DiagnosticsQueryHandler, DownloadCommandHandler, DownloadManager, DownloadQueryHandler,
+    FeatureFlagCommandHandler, FeatureFlagQueryHandler, HttpCacheCommandHandler, HttpCacheQueryHandler, HttpSettingsService, MaintenanceCommandHandler, QueryStatsCommandHandler, QueryStatsQueryHandler, RecoveryService,
+    SecretCommandHandler, SecretQueryHandler,
     UserCommandHandler, UserQueryHandler,
 };
 use crate::domain::cqrs::{CommandHandler, QueryHandler};
 use crate::domain::users::{CreateUserCmd, DeleteUserCmd, ListUsersQuery, User};
-use crate::domain::config::{GetAllConfigQuery, GetConfigQuery, SetConfigCmd};
+use crate::domain::config::{
+    ConfigCacheStats, DeleteConfigCmd, GetAllConfigQuery, GetConfigCacheStatsQuery, GetConfigQuery,
+    GetResolvedConfigQuery, ResetConfigCmd, ResolvedSetting, SetConfigCmd, SetManyConfigCmd,
+};
+use crate::domain::archive::{ArchiveManifest, ExportArchiveCmd, ImportArchiveCmd};
+use crate::domain::diagnostics::{CheckDbHealthQuery, DbHealthReport, GetQueryStatsQuery, QueryStat, ResetQueryStatsCmd};
+use crate::domain::backups::{BackupInfo, CreateBackupCmd, ListBackupsQuery, RestoreBackupCmd};
+use crate::domain::autostart::{AutostartStatus, GetAutostartStatusQuery};
+use crate::domain::events::{DomainEvent, IEventPublisher};
+use crate::domain::downloads::{
+    CancelDownloadCmd, CancelManagedDownloadCmd, Download, DownloadResult, EnqueueDownloadCmd, ListDownloadsQuery,
+    PauseDownloadCmd, ResumeDownloadCmd, StartDownloadCmd,
+};
+use crate::domain::feature_flags::{
+    DeleteFeatureFlagCmd, FeatureFlag, FeatureFlagState, IsFlagEnabledQuery, ListFlagsQuery,
+    SetFlagOverrideCmd, UpsertFeatureFlagCmd,
+};
+use crate::domain::http::{
+    CircuitBreakerStatus, ClearCookiesCmd, ClearHttpCacheCmd, CookieInfo, HttpCacheEntry, ListCircuitBreakersQuery,
+    ListCookiesQuery, ListHttpCacheQuery,
+};
+use crate::domain::maintenance::{CompactDatabaseCmd, MaintenanceReport};
+use crate::domain::recovery::{
+    ExportSalvageCmd, RecoverFromBackupCmd, RecoveryStatus, RetryDatabaseCmd,
+    SalvageReport, StartFreshDatabaseCmd,
+};
+use crate::domain::secrets::{ClearSecretCmd, GetSecretQuery, ListSecretsQuery, SecretInfo, SetSecretCmd};
+use crate::infra::db::Database;
+use crate::infra::db_migrations::{self, MigrationStatus};
+use crate::infra::file_scope::FileScope;
 use crate::infra::http::{HttpClient, HttpRequest, HttpResponse};
 
 #[cfg(target_os = "macos")]
@@ -64,12 +97,152 @@ pub async fn open_log_folder(app: AppHandle) -> Result<(), AppError> {
 
 // Database Check Command
 #[tauri::command]
-pub async fn check_db_health(pool: State<'_, sqlx::SqlitePool>) -> Result<String, AppError> {
-    let result: (i32,) = sqlx::query_as("SELECT 1")
-        .fetch_one(pool.inner())
-        .await?;
+pub async fn check_db_health(handler: State<'_, DiagnosticsQueryHandler>) -> Result<DbHealthReport, AppError> {
+    handler.handle(CheckDbHealthQuery).await
+}
+
+/// Per-statement query statistics, slowest in total first.
+#[tauri::command]
+pub async fn get_query_stats(handler: State<'_, QueryStatsQueryHandler>) -> Result<Vec<QueryStat>, AppError> {
+    handler.handle(GetQueryStatsQuery).await
+}
+
+#[tauri::command]
+pub async fn reset_query_stats(handler: State<'_, QueryStatsCommandHandler>) -> Result<(), AppError> {
+    handler.handle(ResetQueryStatsCmd).await
+}
+
+/// Applied and pending schema migrations.
+#[tauri::command]
+pub async fn migration_status(db: State<'_, Arc<Database>>) -> Result<MigrationStatus, AppError> {
+    db_migrations::status(&db).await
+}
 
-    Ok(format!("Database is healthy! Result: {}", result.0))
+/// Development builds only: revert migrations above `target_version` (0 reverts all).
+/// A backup is taken first, and the rollback refused if that is not possible; the
+/// next launch applies the reverted migrations again.
+#[tauri::command]
+pub async fn rollback_migrations(
+    app: AppHandle,
+    db: State<'_, Arc<Database>>,
+    publisher: State<'_, Arc<dyn IEventPublisher>>,
+    target_version: i64,
+) -> Result<MigrationStatus, AppError> {
+    if !cfg!(debug_assertions) {
+        return Err(AppError::Domain("Migration rollback is only available in development builds".to_string()));
+    }
+
+    let backups = app
+        .try_state::<BackupCommandHandler>()
+        .ok_or_else(|| AppError::Domain("Backups are unavailable, so migrations cannot be rolled back".to_string()))?;
+    let backup = backups.handle(CreateBackupCmd).await?;
+    info!("Backup {} taken before rolling back to migration {}", backup.name, target_version);
+
+    let status = db_migrations::rollback(&db, target_version).await?;
+    publisher.publish(DomainEvent::MigrationsRolledBack { version: target_version });
+    Ok(status)
+}
+
+/// Fully compact the database (VACUUM). Reports how many bytes were reclaimed.
+#[tauri::command]
+pub async fn compact_database(
+    handler: State<'_, MaintenanceCommandHandler>,
+) -> Result<MaintenanceReport, AppError> {
+    handler.handle(CompactDatabaseCmd).await
+}
+
+// --- Recovery Commands (only available when the database failed to open) ---
+
+#[tauri::command]
+pub async fn get_recovery_status(
+    service: State<'_, RecoveryService>,
+) -> Result<RecoveryStatus, AppError> {
+    service.status()
+}
+
+/// Try opening the database again. On success the app finishes starting up.
+#[tauri::command]
+pub async fn recovery_retry(
+    service: State<'_, RecoveryService>,
+) -> Result<(), AppError> {
+    service.handle(RetryDatabaseCmd).await
+}
+
+/// Replace the broken database with a backup (the broken file is kept aside).
+#[tauri::command]
+pub async fn recovery_restore_backup(
+    service: State<'_, RecoveryService>,
+    name: String,
+) -> Result<(), AppError> {
+    service.handle(RecoverFromBackupCmd { name }).await
+}
+
+/// Move the broken database aside and start with an empty one. Returns the moved file name.
+#[tauri::command]
+pub async fn recovery_start_fresh(
+    service: State<'_, RecoveryService>,
+) -> Result<String, AppError> {
+    service.handle(StartFreshDatabaseCmd).await
+}
+
+/// Export every readable row of the broken database to a JSON file.
+#[tauri::command]
+pub async fn recovery_export(
+    service: State<'_, RecoveryService>,
+) -> Result<SalvageReport, AppError> {
+    service.handle(ExportSalvageCmd).await
+}
+
+// --- Backup Commands (CQRS) ---
+
+#[tauri::command]
+pub async fn create_backup(
+    handler: State<'_, BackupCommandHandler>,
+) -> Result<BackupInfo, AppError> {
+    handler.handle(CreateBackupCmd).await
+}
+
+#[tauri::command]
+pub async fn list_backups(
+    handler: State<'_, BackupQueryHandler>,
+) -> Result<Vec<BackupInfo>, AppError> {
+    handler.handle(ListBackupsQuery).await
+}
+
+/// Replace the live database with a backup. The current database is backed up first.
+#[tauri::command]
+pub async fn restore_backup(
+    handler: State<'_, BackupCommandHandler>,
+    name: String,
+) -> Result<(), AppError> {
+    handler.handle(RestoreBackupCmd { name }).await
+}
+
+// --- Archive Commands (CQRS) ---
+
+/// Export every table (except secrets) into a zip archive at `path`. The path must be
+/// one the app may write to (see `FileScope`); an existing file is only replaced with `overwrite`.
+#[tauri::command]
+pub async fn export_archive(
+    handler: State<'_, ArchiveCommandHandler>,
+    files: State<'_, FileScope>,
+    path: String,
+    overwrite: Option<bool>,
+) -> Result<ArchiveManifest, AppError> {
+    let path = files.check_write(&path, overwrite.unwrap_or(false))?;
+    handler.handle(ExportArchiveCmd { path: path.to_string_lossy().into_owned() }).await
+}
+
+/// Replace all data with an archive's contents. A backup is taken first. The path
+/// must be one the app may read (see `FileScope`).
+#[tauri::command]
+pub async fn import_archive(
+    handler: State<'_, ArchiveCommandHandler>,
+    files: State<'_, FileScope>,
+    path: String,
+) -> Result<ArchiveManifest, AppError> {
+    let path = files.check_read(&path)?;
+    handler.handle(ImportArchiveCmd { path: path.to_string_lossy().into_owned() }).await
 }
 
 // --- Configuration Commands (CQRS) ---
@@ -91,6 +264,30 @@ pub async fn set_app_setting(
     handler.handle(SetConfigCmd { key, value }).await
 }
 
+#[tauri::command]
+pub async fn set_app_settings(
+    handler: State<'_, ConfigCommandHandler>,
+    values: HashMap<String, String>,
+) -> Result<(), AppError> {
+    handler.handle(SetManyConfigCmd { values }).await
+}
+
+#[tauri::command]
+pub async fn delete_app_setting(
+    handler: State<'_, ConfigCommandHandler>,
+    key: String,
+) -> Result<(), AppError> {
+    handler.handle(DeleteConfigCmd { key }).await
+}
+
+/// Reset all settings to their defaults. Returns the number of removed values.
+#[tauri::command]
+pub async fn reset_app_settings(
+    handler: State<'_, ConfigCommandHandler>,
+) -> Result<u64, AppError> {
+    handler.handle(ResetConfigCmd).await
+}
+
 #[tauri::command]
 pub async fn get_all_settings(
     handler: State<'_, ConfigQueryHandler>,
@@ -98,16 +295,255 @@ pub async fn get_all_settings(
     handler.handle(GetAllConfigQuery).await
 }
 
+#[tauri::command]
+pub async fn get_resolved_settings(
+    handler: State<'_, ConfigQueryHandler>,
+) -> Result<Vec<ResolvedSetting>, AppError> {
+    handler.handle(GetResolvedConfigQuery).await
+}
+
+#[tauri::command]
+pub async fn get_config_cache_stats(
+    handler: State<'_, ConfigQueryHandler>,
+) -> Result<ConfigCacheStats, AppError> {
+    handler.handle(GetConfigCacheStatsQuery).await
+}
+
+// --- Autostart Commands ---
+
+/// Actual OS launch-on-startup state, which may differ from the stored setting.
+#[tauri::command]
+pub async fn get_autostart_status(
+    service: State<'_, AutostartService>,
+) -> Result<AutostartStatus, AppError> {
+    service.handle(GetAutostartStatusQuery).await
+}
+
+// --- Feature Flag Commands (CQRS) ---
+
+#[tauri::command]
+pub async fn is_feature_enabled(
+    handler: State<'_, FeatureFlagQueryHandler>,
+    key: String,
+    user_id: Option<String>,
+) -> Result<bool, AppError> {
+    handler.handle(IsFlagEnabledQuery { key, user_id }).await
+}
+
+#[tauri::command]
+pub async fn list_feature_flags(
+    handler: State<'_, FeatureFlagQueryHandler>,
+    user_id: Option<String>,
+) -> Result<Vec<FeatureFlagState>, AppError> {
+    handler.handle(ListFlagsQuery { user_id }).await
+}
+
+#[tauri::command]
+pub async fn upsert_feature_flag(
+    handler: State<'_, FeatureFlagCommandHandler>,
+    cmd: UpsertFeatureFlagCmd,
+) -> Result<FeatureFlag, AppError> {
+    handler.handle(cmd).await
+}
+
+#[tauri::command]
+pub async fn delete_feature_flag(
+    handler: State<'_, FeatureFlagCommandHandler>,
+    key: String,
+) -> Result<(), AppError> {
+    handler.handle(DeleteFeatureFlagCmd { key }).await
+}
+
+#[tauri::command]
+pub async fn set_feature_flag_override(
+    handler: State<'_, FeatureFlagCommandHandler>,
+    key: String,
+    user_id: String,
+    enabled: Option<bool>,
+) -> Result<(), AppError> {
+    handler.handle(SetFlagOverrideCmd { key, user_id, enabled }).await
+}
+
+// --- Secret Commands (CQRS, write-only from the frontend) ---
+
+#[tauri::command]
+pub async fn set_secret(
+    handler: State<'_, SecretCommandHandler>,
+    key: String,
+    value: String,
+) -> Result<(), AppError> {
+    handler.handle(SetSecretCmd { key, value }).await
+}
+
+#[tauri::command]
+pub async fn clear_secret(
+    handler: State<'_, SecretCommandHandler>,
+    key: String,
+) -> Result<(), AppError> {
+    handler.handle(ClearSecretCmd { key }).await
+}
+
+#[tauri::command]
+pub async fn list_secrets(
+    handler: State<'_, SecretQueryHandler>,
+) -> Result<Vec<SecretInfo>, AppError> {
+    handler.handle(ListSecretsQuery).await
+}
+
 // --- Network Commands ---
 
 #[tauri::command]
 pub async fn http_request(
+    app: AppHandle,
     client: State<'_, HttpClient>,
-    request: HttpRequest,
+    mut request: HttpRequest,
 ) -> Result<HttpResponse, AppError> {
+    // Inject the bearer token from the secret store, if the secret is bound to the
+    // request's host and the request uses https. Redirects to another scheme,
+    // host or port drop the header.
+    if let Some(key) = request.auth_secret.take() {
+        let secrets = app
+            .try_state::<SecretQueryHandler>()
+            .ok_or_else(|| AppError::Crypto("Secret store is unavailable".to_string()))?;
+        let url = reqwest::Url::parse(&request.url)
+            .map_err(|_| AppError::Unknown("Invalid URL".to_string()))?;
+        let host = url
+            .host_str()
+            .map(str::to_string)
+            .ok_or_else(|| AppError::Unknown("Invalid URL".to_string()))?;
+        let token = secrets
+            .handle(GetSecretQuery { key: key.clone(), scheme: url.scheme().to_string(), host })
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Secret {} not found", key)))?;
+
+        request
+            .headers
+            .get_or_insert_with(HashMap::new)
+            .insert("Authorization".to_string(), format!("Bearer {}", token));
+    }
+
     client.execute(request).await
 }
 
+/// Circuit breakers of hosts that failed recently (closed ones are dropped on success)
+#[tauri::command]
+pub async fn list_circuit_breakers(
+    service: State<'_, HttpSettingsService>,
+) -> Result<Vec<CircuitBreakerStatus>, AppError> {
+    service.handle(ListCircuitBreakersQuery).await
+}
+
+/// Cached HTTP responses (without bodies), most recently stored first
+#[tauri::command]
+pub async fn list_http_cache(
+    handler: State<'_, HttpCacheQueryHandler>,
+) -> Result<Vec<HttpCacheEntry>, AppError> {
+    handler.handle(ListHttpCacheQuery).await
+}
+
+/// Remove every cached HTTP response. Returns how many were removed.
+#[tauri::command]
+pub async fn clear_http_cache(
+    handler: State<'_, HttpCacheCommandHandler>,
+) -> Result<u64, AppError> {
+    handler.handle(ClearHttpCacheCmd).await
+}
+
+/// Cookies of the HTTP client, limited to `domain` and its subdomains if given.
+/// Values of HttpOnly cookies are left out.
+#[tauri::command]
+pub async fn list_cookies(
+    domain: Option<String>,
+    handler: State<'_, CookieQueryHandler>,
+) -> Result<Vec<CookieInfo>, AppError> {
+    handler.handle(ListCookiesQuery { domain }).await
+}
+
+/// Remove the cookies of `domain` and its subdomains, or all of them. Returns how many were removed.
+#[tauri::command]
+pub async fn clear_cookies(
+    domain: Option<String>,
+    handler: State<'_, CookieCommandHandler>,
+) -> Result<u64, AppError> {
+    handler.handle(ClearCookiesCmd { domain }).await
+}
+
+/// Download `url` to `path`, publishing `download:progress` events.
+/// Resolves once the file is complete; pass an `id` to be able to cancel it.
+/// `path` must be in the downloads directory or picked by the user (never in app data),
+/// and an existing file is only replaced with `overwrite`.
+#[tauri::command]
+pub async fn start_download(
+    handler: State<'_, DownloadCommandHandler>,
+    id: Option<String>,
+    url: String,
+    path: String,
+    sha256: Option<String>,
+    overwrite: Option<bool>,
+) -> Result<DownloadResult, AppError> {
+    handler
+        .handle(StartDownloadCmd { id, url, path, sha256, overwrite: overwrite.unwrap_or(false) })
+        .await
+}
+
+/// Cancel a download started with `start_download`.
+#[tauri::command]
+pub async fn cancel_download(
+    handler: State<'_, DownloadCommandHandler>,
+    id: String,
+) -> Result<(), AppError> {
+    handler.handle(CancelDownloadCmd { id }).await
+}
+
+// --- Download Manager Commands (CQRS) ---
+
+/// Add a download to the queue. It starts once a slot is free (`download_concurrency`).
+/// `path` is restricted like in `start_download`.
+#[tauri::command]
+pub async fn enqueue_download(
+    manager: State<'_, DownloadManager>,
+    url: String,
+    path: String,
+    sha256: Option<String>,
+    overwrite: Option<bool>,
+) -> Result<Download, AppError> {
+    manager
+        .handle(EnqueueDownloadCmd { url, path, sha256, overwrite: overwrite.unwrap_or(false) })
+        .await
+}
+
+#[tauri::command]
+pub async fn list_downloads(
+    handler: State<'_, DownloadQueryHandler>,
+) -> Result<Vec<Download>, AppError> {
+    handler.handle(ListDownloadsQuery).await
+}
+
+#[tauri::command]
+pub async fn pause_download(
+    manager: State<'_, DownloadManager>,
+    id: String,
+) -> Result<(), AppError> {
+    manager.handle(PauseDownloadCmd { id }).await
+}
+
+#[tauri::command]
+pub async fn resume_download(
+    manager: State<'_, DownloadManager>,
+    id: String,
+) -> Result<(), AppError> {
+    manager.handle(ResumeDownloadCmd { id }).await
+}
+
+/// Cancel a queued, running, paused or failed download and delete its partial file.
+#[tauri::command]
+pub async fn cancel_managed_download(
+    manager: State<'_, DownloadManager>,
+    id: String,
+) -> Result<(), AppError> {
+    manager.handle(CancelManagedDownloadCmd { id }).await
+}
+
 // --- User Domain Commands (CQRS) ---
 
 #[tauri::command]
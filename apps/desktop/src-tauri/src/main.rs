@@ -7,14 +7,17 @@ mod application;
 mod interface;
 mod error;
 
-use tauri::{Manager, WindowEvent};
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder, WindowEvent};
 use tauri_plugin_window_state::{AppHandleExt, StateFlags};
-use tracing::{info, error};
+use tracing::{info, warn, error};
 use std::sync::Arc;
 
 // State wrapper to keep the file logger guard alive
 struct LogGuardState(#[allow(dead_code)] infra::logging::WorkerGuard);
 
+/// Window shown instead of the main window while the database cannot be opened
+const RECOVERY_WINDOW: &str = "recovery";
+
 fn main() {
     tauri::Builder::default()
         .plugin(tauri_plugin_fs::init())
@@ -37,53 +40,83 @@ fn main() {
                 interface::tray::create_tray(app.handle())?;
             }
 
-            // 3. Initialize HTTP Client
-            let http_client = infra::http::HttpClient::new().expect("Failed to init HTTP client");
-            app.manage(http_client);
-
-            // 4. Initialize Database and CQRS Handlers (Async in setup)
+            // 3. Initialize Database and CQRS Handlers (Async in setup)
             let app_handle = app.handle().clone();
 
             // Create Event Publisher (Infra)
             let publisher = Arc::new(infra::event_publisher::TauriEventPublisher::new(app_handle.clone()));
 
+            // Defaults, config files, env and CLI layers below the database
+            let config_layers = Arc::new(infra::config_layers::load(&app_handle));
+            let db_options = infra::db::DbOptions::from_config(&config_layers);
+
+            // 4. Initialize HTTP Client (scope from the static config only; uploads and downloads limited to the
+            // downloads dir and picked files, never app data; circuit breaker changes are published as events)
+            // Demo mode (APP_DEMO_MODE=true or --config demo_mode=true) runs without a database
+            // and leaves nothing on disk, not even cookies
+            let demo_mode = config_layers
+                .get("demo_mode")
+                .is_some_and(|(value, _)| matches!(value.trim(), "true" | "1"));
+
+            let http_scope = infra::http_scope::HttpScope::from_config(&config_layers);
+            let cookie_jar = if demo_mode {
+                infra::cookie_jar::PersistentCookieJar::in_memory()
+            } else {
+                infra::cookie_jar::PersistentCookieJar::open(app.path().app_data_dir()?.join("cookies.json"))
+            };
+            let file_scope = infra::file_scope::FileScope::for_app(&app_handle);
+            app.manage(file_scope.clone());
+            let http_client = infra::http::HttpClient::new(http_scope, file_scope, cookie_jar.clone(), publisher.clone())
+                .expect("Failed to init HTTP client");
+            app.manage(http_client.clone());
+
+            // Cookies live in a file of their own, so they work without a database too
+            app.manage(application::CookieCommandHandler::new(cookie_jar.clone()));
+            app.manage(application::CookieQueryHandler::new(cookie_jar));
+
+            // Downloads (streamed to disk, progress published as events)
+            app.manage(application::DownloadCommandHandler::new(Arc::new(http_client), publisher.clone()));
+
+            if demo_mode {
+                tauri::async_runtime::block_on(init_demo_services(&app_handle, publisher, config_layers));
+                return Ok(());
+            }
+
+            // A postgres:// db_url points the app at a shared database instead of the local file
+            let db_url = config_layers
+                .get("db_url")
+                .map(|(value, _)| value.trim().to_string())
+                .filter(|value| !value.is_empty());
+            if let Some(url) = db_url {
+                #[cfg(feature = "postgres")]
+                if infra::postgres::is_postgres_url(&url) {
+                    if let Err(e) = tauri::async_runtime::block_on(init_postgres_services(
+                        &app_handle,
+                        &url,
+                        &db_options,
+                        publisher.clone(),
+                        config_layers.clone(),
+                    )) {
+                        error!("Failed to connect to PostgreSQL: {:?}", e);
+                        postgres_unavailable(&app_handle, url, db_options, publisher, config_layers, e);
+                    }
+                    return Ok(());
+                }
+                error!("Unsupported db_url {:?} (PostgreSQL needs the `postgres` feature); using the local database", url.split(':').next().unwrap_or_default());
+            }
+
             tauri::async_runtime::block_on(async move {
-                match infra::db::init_db(&app_handle).await {
-                    Ok(pool) => {
-                        info!("Database initialized successfully");
-                        app_handle.manage(pool.clone());
-
-                        // --- Config Domain (CQRS) ---
-                        let config_repo = Arc::new(infra::repo_config::SqliteConfigRepository::new(pool.clone()));
-
-                        // Command Handler (writes)
-                        let config_cmd_handler = application::ConfigCommandHandler::new(
-                            config_repo.clone(),
-                            publisher.clone()
-                        );
-                        app_handle.manage(config_cmd_handler);
-
-                        // Query Handler (reads)
-                        let config_query_handler = application::ConfigQueryHandler::new(config_repo);
-                        app_handle.manage(config_query_handler);
-
-                        // --- User Domain (CQRS) ---
-                        let user_repo = Arc::new(infra::repo_users::SqliteUserRepository::new(pool.clone()));
-
-                        // Command Handler (writes)
-                        let user_cmd_handler = application::UserCommandHandler::new(user_repo.clone());
-                        app_handle.manage(user_cmd_handler);
-
-                        // Query Handler (reads)
-                        let user_query_handler = application::UserQueryHandler::new(user_repo);
-                        app_handle.manage(user_query_handler);
+                match infra::db::init_db(&app_handle, db_options.clone()).await {
+                    Ok(db) => {
+                        init_services(&app_handle, db, publisher, config_layers).await;
+                        Ok(())
                     }
                     Err(e) => {
                         error!("Failed to initialize database: {:?}", e);
-                        panic!("Database initialization failed: {:?}", e);
+                        enter_recovery_mode(&app_handle, e, db_options, publisher, config_layers)
                     }
                 }
-            });
+            })?;
 
             Ok(())
         })
@@ -104,6 +137,15 @@ fn main() {
                             let _ = window.hide();
                             api.prevent_close();
                         }
+                    } else if window.label() == RECOVERY_WINDOW {
+                        // Nothing else can be shown while the database is still broken
+                        let recovering = window
+                            .app_handle()
+                            .try_state::<application::RecoveryService>()
+                            .is_some_and(|service| service.is_active());
+                        if recovering {
+                            window.app_handle().exit(0);
+                        }
                     }
                 }
                 _ => {}
@@ -114,10 +156,51 @@ fn main() {
             interface::commands::log_frontend_message,
             interface::commands::open_log_folder,
             interface::commands::check_db_health,
+            interface::commands::get_query_stats,
+            interface::commands::reset_query_stats,
             interface::commands::get_app_setting,
             interface::commands::set_app_setting,
+            interface::commands::set_app_settings,
+            interface::commands::delete_app_setting,
+            interface::commands::reset_app_settings,
             interface::commands::get_all_settings,
+            interface::commands::get_resolved_settings,
+            interface::commands::get_config_cache_stats,
+            interface::commands::migration_status,
+            interface::commands::rollback_migrations,
+            interface::commands::compact_database,
+            interface::commands::get_recovery_status,
+            interface::commands::recovery_retry,
+            interface::commands::recovery_restore_backup,
+            interface::commands::recovery_start_fresh,
+            interface::commands::recovery_export,
+            interface::commands::create_backup,
+            interface::commands::list_backups,
+            interface::commands::restore_backup,
+            interface::commands::export_archive,
+            interface::commands::import_archive,
+            interface::commands::get_autostart_status,
+            interface::commands::is_feature_enabled,
+            interface::commands::list_feature_flags,
+            interface::commands::upsert_feature_flag,
+            interface::commands::delete_feature_flag,
+            interface::commands::set_feature_flag_override,
+            interface::commands::set_secret,
+            interface::commands::clear_secret,
+            interface::commands::list_secrets,
             interface::commands::http_request,
+            interface::commands::list_circuit_breakers,
+            interface::commands::list_http_cache,
+            interface::commands::clear_http_cache,
+            interface::commands::list_cookies,
+            interface::commands::clear_cookies,
+            interface::commands::start_download,
+            interface::commands::cancel_download,
+            interface::commands::enqueue_download,
+            interface::commands::list_downloads,
+            interface::commands::pause_download,
+            interface::commands::resume_download,
+            interface::commands::cancel_managed_download,
             interface::commands::create_user,
             interface::commands::list_users,
             interface::commands::delete_user
@@ -125,3 +208,418 @@ fn main() {
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+/// Storage behind the CQRS handlers: SQLite normally, in memory in demo mode.
+struct Repositories {
+    config: Arc<dyn domain::config::IConfigRepository>,
+    users: Arc<dyn domain::users::IUserRepository>,
+    feature_flags: Arc<dyn domain::feature_flags::IFeatureFlagRepository>,
+    /// `None` when the secret store is unavailable
+    secrets: Option<Arc<dyn domain::secrets::ISecretRepository>>,
+    /// `None` when launch on startup is unsupported
+    autostart: Option<Arc<dyn domain::autostart::IAutostartManager>>,
+}
+
+/// Storage behind the database features: SQLite normally, in memory in demo mode.
+struct DataStores {
+    /// `None` when backups are unavailable
+    backups: Option<Arc<dyn domain::backups::IBackupStore>>,
+    archive: Arc<dyn domain::archive::IArchiveStore>,
+    downloads: Arc<dyn domain::downloads::IDownloadRepository>,
+    http_cache: Arc<dyn domain::http::IHttpCacheRepository>,
+    diagnostics: Arc<dyn domain::diagnostics::IDbDiagnostics>,
+    maintenance: Arc<dyn domain::maintenance::IDatabaseMaintenance>,
+}
+
+/// Wire up the repositories, CQRS handlers and background jobs on top of an open database.
+async fn init_services(
+    app_handle: &AppHandle,
+    db: infra::db::Database,
+    publisher: Arc<infra::event_publisher::TauriEventPublisher<tauri::Wry>>,
+    config_layers: Arc<domain::config::StaticConfig>,
+) {
+    info!("Database initialized successfully");
+    let db = Arc::new(db);
+    app_handle.manage(db.clone());
+    app_handle.manage(publisher.clone() as Arc<dyn domain::events::IEventPublisher>);
+    app_handle.manage(application::QueryStatsQueryHandler::new(db.queries().clone()));
+    app_handle.manage(application::QueryStatsCommandHandler::new(db.queries().clone()));
+
+    let repos = Repositories {
+        config: Arc::new(infra::repo_config::SqliteConfigRepository::new(db.clone())),
+        users: Arc::new(infra::repo_users::SqliteUserRepository::new(db.clone())),
+        feature_flags: Arc::new(infra::repo_feature_flags::SqliteFeatureFlagRepository::new(db.clone())),
+        // Secret Settings (encrypted at rest)
+        secrets: match infra::crypto::SecretCipher::load(app_handle) {
+            Ok(cipher) => Some(Arc::new(infra::repo_secrets::SqliteSecretRepository::new(
+                db.clone(),
+                Arc::new(cipher),
+            ))),
+            Err(e) => {
+                error!("Secret store disabled, failed to load master key: {:?}", e);
+                None
+            }
+        },
+        autostart: match infra::autostart::OsAutostartManager::new(app_handle) {
+            Ok(manager) => Some(Arc::new(manager)),
+            Err(e) => {
+                warn!("Autostart unavailable: {:?}", e);
+                None
+            }
+        },
+    };
+    let config_repo = repos.config.clone();
+    let config_query_handler = init_handlers(app_handle, repos, publisher.clone(), config_layers).await;
+
+    let stores = DataStores {
+        backups: match app_handle.path().app_data_dir() {
+            Ok(data_dir) => Some(Arc::new(infra::backup_store::SqliteBackupStore::new(
+                db.clone(),
+                data_dir.join("backups"),
+            ))),
+            Err(e) => {
+                warn!("Backups unavailable: {:?}", e);
+                None
+            }
+        },
+        archive: Arc::new(infra::archive::SqliteArchiveStore::new(db.clone())),
+        downloads: Arc::new(infra::repo_downloads::SqliteDownloadRepository::new(db.clone())),
+        http_cache: Arc::new(infra::repo_http_cache::SqliteHttpCacheRepository::new(db.clone())),
+        diagnostics: Arc::new(infra::db_diagnostics::SqliteDiagnostics::new(db.clone())),
+        maintenance: Arc::new(infra::db_maintenance::SqliteMaintenance::new(db)),
+    };
+    init_data_handlers(app_handle, stores, config_repo, config_query_handler, publisher).await;
+}
+
+/// Shared PostgreSQL database (`postgres` feature). Secrets stay in the local SQLite file.
+/// Backups, maintenance, diagnostics, the download queue and the HTTP cache work on the local
+/// SQLite file and are unavailable here.
+#[cfg(feature = "postgres")]
+async fn init_postgres_services(
+    app_handle: &AppHandle,
+    url: &str,
+    db_options: &infra::db::DbOptions,
+    publisher: Arc<infra::event_publisher::TauriEventPublisher<tauri::Wry>>,
+    config_layers: Arc<domain::config::StaticConfig>,
+) -> Result<(), error::AppError> {
+    let pool = infra::postgres::connect(url, db_options).await?;
+    info!("PostgreSQL database initialized successfully");
+    let queries = Arc::new(infra::query_stats::QueryStats::new(db_options.slow_query_ms));
+    app_handle.manage(application::QueryStatsQueryHandler::new(queries.clone()));
+    app_handle.manage(application::QueryStatsCommandHandler::new(queries.clone()));
+
+    let repos = Repositories {
+        config: Arc::new(infra::postgres::repo_config::PostgresConfigRepository::new(pool.clone(), queries.clone())),
+        users: Arc::new(infra::postgres::repo_users::PostgresUserRepository::new(pool.clone(), queries.clone())),
+        feature_flags: Arc::new(infra::postgres::repo_feature_flags::PostgresFeatureFlagRepository::new(pool, queries)),
+        // Secrets are encrypted with this machine's key, so they stay in the local database
+        secrets: match infra::crypto::SecretCipher::load(app_handle) {
+            Ok(cipher) => match infra::db::init_db(app_handle, db_options.clone()).await {
+                Ok(local) => Some(Arc::new(infra::repo_secrets::SqliteSecretRepository::new(
+                    Arc::new(local),
+                    Arc::new(cipher),
+                ))),
+                Err(e) => {
+                    error!("Secret store disabled, failed to open the local database: {:?}", e);
+                    None
+                }
+            },
+            Err(e) => {
+                error!("Secret store disabled, failed to load master key: {:?}", e);
+                None
+            }
+        },
+        autostart: match infra::autostart::OsAutostartManager::new(app_handle) {
+            Ok(manager) => Some(Arc::new(manager)),
+            Err(e) => {
+                warn!("Autostart unavailable: {:?}", e);
+                None
+            }
+        },
+    };
+    init_handlers(app_handle, repos, publisher, config_layers).await;
+    Ok(())
+}
+
+/// The shared database could not be reached. Startup carries on with the main window
+/// hidden behind a dialog that retries the connection or quits.
+#[cfg(feature = "postgres")]
+fn postgres_unavailable(
+    app_handle: &AppHandle,
+    url: String,
+    db_options: infra::db::DbOptions,
+    publisher: Arc<infra::event_publisher::TauriEventPublisher<tauri::Wry>>,
+    config_layers: Arc<domain::config::StaticConfig>,
+    error: error::AppError,
+) {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+    if let Some(main) = app_handle.get_webview_window("main") {
+        let _ = main.hide();
+    }
+    let handle = app_handle.clone();
+    app_handle
+        .dialog()
+        .message(format!("Could not connect to the shared PostgreSQL database.\n\n{}", error))
+        .title("Database Unavailable")
+        .kind(MessageDialogKind::Error)
+        .buttons(MessageDialogButtons::OkCancelCustom("Retry".to_string(), "Quit".to_string()))
+        .show(move |retry| {
+            if !retry {
+                handle.exit(1);
+                return;
+            }
+            tauri::async_runtime::spawn(async move {
+                match init_postgres_services(&handle, &url, &db_options, publisher.clone(), config_layers.clone()).await {
+                    Ok(()) => reload_main_window(&handle),
+                    Err(e) => {
+                        error!("Failed to connect to PostgreSQL: {:?}", e);
+                        postgres_unavailable(&handle, url, db_options, publisher, config_layers, e);
+                    }
+                }
+            });
+        });
+}
+
+/// Demo mode: the same handlers on in-memory storage. Nothing is read from or
+/// written to the app data directory, and everything is gone on exit.
+async fn init_demo_services(
+    app_handle: &AppHandle,
+    publisher: Arc<infra::event_publisher::TauriEventPublisher<tauri::Wry>>,
+    config_layers: Arc<domain::config::StaticConfig>,
+) {
+    info!("Demo mode: using in-memory storage");
+
+    let db = infra::memory::InMemoryDatabase::new();
+    let repos = Repositories {
+        config: db.config.clone(),
+        users: db.users.clone(),
+        feature_flags: db.feature_flags.clone(),
+        secrets: Some(db.secrets.clone()),
+        autostart: Some(Arc::new(infra::memory::InMemoryAutostartManager::new())),
+    };
+    let config_repo = repos.config.clone();
+    let config_query_handler = init_handlers(app_handle, repos, publisher.clone(), config_layers).await;
+
+    let stores = DataStores {
+        backups: Some(Arc::new(infra::memory::InMemoryBackupStore::new(db.clone()))),
+        archive: Arc::new(infra::memory::InMemoryArchiveStore::new(db.clone())),
+        downloads: Arc::new(infra::memory::InMemoryDownloadRepository::new()),
+        http_cache: Arc::new(infra::memory::InMemoryHttpCacheRepository::new()),
+        diagnostics: Arc::new(infra::memory::InMemoryDiagnostics::new(db.clone())),
+        maintenance: Arc::new(infra::memory::InMemoryMaintenance::new(db)),
+    };
+    init_data_handlers(app_handle, stores, config_repo, config_query_handler, publisher).await;
+}
+
+/// Create and manage the CQRS handlers shared by normal and demo mode.
+/// Returns the config query handler for services that need settings access.
+async fn init_handlers(
+    app_handle: &AppHandle,
+    repos: Repositories,
+    publisher: Arc<infra::event_publisher::TauriEventPublisher<tauri::Wry>>,
+    config_layers: Arc<domain::config::StaticConfig>,
+) -> application::ConfigQueryHandler {
+    // --- Config Domain (CQRS) ---
+    // Command Handler (writes)
+    let config_cmd_handler = application::ConfigCommandHandler::new(
+        repos.config.clone(),
+        publisher.clone(),
+        config_layers.clone(),
+    );
+
+    // Query Handler (reads, cached; invalidated by ConfigChanged events)
+    let config_query_handler = application::ConfigQueryHandler::new(repos.config, config_layers.clone());
+    publisher.subscribe(config_query_handler.cache());
+    match config_query_handler.cache().warm().await {
+        Ok(count) => info!("Config cache warmed with {} settings", count),
+        Err(e) => warn!("Failed to warm config cache: {:?}", e),
+    }
+    app_handle.manage(config_cmd_handler.clone());
+    app_handle.manage(config_query_handler.clone());
+
+    // --- Autostart (reacts to the launch_on_startup setting) ---
+    if let Some(manager) = repos.autostart {
+        let autostart = application::AutostartService::new(manager, config_query_handler.clone());
+        if let Err(e) = autostart.sync().await {
+            warn!("Failed to sync launch on startup: {:?}", e);
+        }
+        publisher.subscribe(Arc::new(autostart.clone()));
+        app_handle.manage(autostart);
+    }
+
+    // --- HTTP Client (retry and circuit breaker settings) ---
+    let http_settings = application::HttpSettingsService::new(
+        Arc::new(app_handle.state::<infra::http::HttpClient>().inner().clone()),
+        config_query_handler.clone(),
+        config_layers.clone(),
+    );
+    if let Err(e) = http_settings.sync().await {
+        warn!("Failed to apply HTTP settings: {:?}", e);
+    }
+    publisher.subscribe(Arc::new(http_settings.clone()));
+    app_handle.manage(http_settings);
+
+    // --- Feature Flags (CQRS) ---
+    let flag_evaluator = Arc::new(application::FeatureFlagEvaluator::new(
+        repos.feature_flags.clone(),
+        publisher.clone(),
+    ));
+    app_handle.manage(application::FeatureFlagCommandHandler::new(repos.feature_flags, flag_evaluator.clone()));
+    app_handle.manage(application::FeatureFlagQueryHandler::new(flag_evaluator.clone()));
+
+    // Re-evaluate observed flags periodically so expiry dates take effect
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            if let Err(e) = flag_evaluator.refresh().await {
+                warn!("Failed to refresh feature flags: {:?}", e);
+            }
+        }
+    });
+
+    // --- Secret Settings (CQRS) ---
+    if let Some(secret_repo) = repos.secrets {
+        app_handle.manage(application::SecretCommandHandler::new(secret_repo.clone()));
+        app_handle.manage(application::SecretQueryHandler::new(secret_repo, config_layers));
+    }
+
+    // --- User Domain (CQRS) ---
+    // Command Handler (writes)
+    let user_cmd_handler = application::UserCommandHandler::new(repos.users.clone());
+    app_handle.manage(user_cmd_handler);
+
+    // Query Handler (reads)
+    let user_query_handler = application::UserQueryHandler::new(repos.users);
+    app_handle.manage(user_query_handler);
+
+    config_query_handler
+}
+
+/// Create and manage the handlers and background jobs of the database features
+/// shared by normal and demo mode.
+async fn init_data_handlers(
+    app_handle: &AppHandle,
+    stores: DataStores,
+    config_repo: Arc<dyn domain::config::IConfigRepository>,
+    config_query_handler: application::ConfigQueryHandler,
+    publisher: Arc<infra::event_publisher::TauriEventPublisher<tauri::Wry>>,
+) {
+    // --- Backups (on-demand + scheduled, with rotation) ---
+    if let Some(backup_store) = stores.backups.clone() {
+        app_handle.manage(application::BackupCommandHandler::new(
+            backup_store.clone(),
+            publisher.clone(),
+            config_query_handler.clone(),
+        ));
+        app_handle.manage(application::BackupQueryHandler::new(backup_store));
+
+        let backup_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            let handler = backup_handle.state::<application::BackupCommandHandler>();
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(15 * 60));
+            loop {
+                interval.tick().await;
+                if let Err(e) = handler.backup_if_due().await {
+                    warn!("Scheduled backup failed: {:?}", e);
+                }
+            }
+        });
+    }
+
+    // --- Data Archives (export/import) ---
+    app_handle.manage(application::ArchiveCommandHandler::new(
+        stores.archive,
+        stores.backups,
+        publisher.clone(),
+    ));
+
+    // --- Download Manager (persistent queue, resumed on startup) ---
+    let downloads = application::DownloadManager::new(
+        stores.downloads.clone(),
+        Arc::new(app_handle.state::<infra::http::HttpClient>().inner().clone()),
+        publisher,
+        config_query_handler.clone(),
+    );
+    if let Err(e) = downloads.start().await {
+        warn!("Failed to resume downloads: {:?}", e);
+    }
+    app_handle.manage(downloads);
+    app_handle.manage(application::DownloadQueryHandler::new(stores.downloads));
+
+    // --- HTTP Response Cache (used by http_request once set) ---
+    app_handle.state::<infra::http::HttpClient>().set_cache(stores.http_cache.clone());
+    app_handle.manage(application::HttpCacheCommandHandler::new(stores.http_cache.clone()));
+    app_handle.manage(application::HttpCacheQueryHandler::new(stores.http_cache));
+
+    // --- Database Diagnostics ---
+    app_handle.manage(application::DiagnosticsQueryHandler::new(stores.diagnostics));
+
+    // --- Database Maintenance (scheduled when idle + manual compact) ---
+    app_handle.manage(application::MaintenanceCommandHandler::new(
+        stores.maintenance,
+        config_repo,
+        config_query_handler,
+    ));
+
+    let maintenance_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let handler = maintenance_handle.state::<application::MaintenanceCommandHandler>();
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(15 * 60));
+        loop {
+            interval.tick().await;
+            if let Err(e) = handler.run_if_due().await {
+                warn!("Scheduled database maintenance failed: {:?}", e);
+            }
+        }
+    });
+}
+
+/// Keep the app usable when the database cannot be opened: show the recovery
+/// window, and finish startup once one of the recovery commands opens it.
+fn enter_recovery_mode(
+    app_handle: &AppHandle,
+    error: error::AppError,
+    db_options: infra::db::DbOptions,
+    publisher: Arc<infra::event_publisher::TauriEventPublisher<tauri::Wry>>,
+    config_layers: Arc<domain::config::StaticConfig>,
+) -> Result<(), error::AppError> {
+    let db_path = infra::db::database_path(app_handle)?;
+    let backups_dir = app_handle.path().app_data_dir()?.join("backups");
+    let (recovery, ready) = infra::db_recovery::SqliteRecovery::new(db_path, backups_dir, db_options);
+    app_handle.manage(application::RecoveryService::new(Arc::new(recovery), error.to_string()));
+
+    if let Some(main) = app_handle.get_webview_window("main") {
+        let _ = main.hide();
+    }
+    WebviewWindowBuilder::new(app_handle, RECOVERY_WINDOW, WebviewUrl::App("recovery".into()))
+        .title("Database Recovery")
+        .inner_size(720.0, 600.0)
+        .min_inner_size(560.0, 480.0)
+        .build()?;
+
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let Ok(db) = ready.await else {
+            return;
+        };
+        init_services(&app_handle, db, publisher, config_layers).await;
+        reload_main_window(&app_handle);
+        // destroy() skips CloseRequested, which exits the app while recovery may still look active
+        if let Some(window) = app_handle.get_webview_window(RECOVERY_WINDOW) {
+            let _ = window.destroy();
+        }
+    });
+
+    Ok(())
+}
+
+/// The main window loaded without backend state; reload it now that everything is managed.
+fn reload_main_window(app_handle: &AppHandle) {
+    if let Some(main) = app_handle.get_webview_window("main") {
+        let _ = main.eval("window.location.reload()");
+        let _ = main.show();
+        let _ = main.set_focus();
+    }
+}
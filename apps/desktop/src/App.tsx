@@ -8,6 +8,7 @@ import { HomePage } from './pages/HomePage';
 import { SettingsPage } from './pages/SettingsPage';
 import { UsersPage } from './pages/UsersPage';
 import { ComponentShowcasePage } from './pages/ComponentShowcasePage';
+import { RecoveryPage } from './pages/RecoveryPage';
 import { TooltipProvider } from '@/components/ui/tooltip';
 
 function App() {
@@ -16,14 +17,23 @@ function App() {
       <ThemeProvider>
         <TooltipProvider delayDuration={0}>
           <BrowserRouter>
-            <MainLayout>
-              <Routes>
-                <Route path="/" element={<HomePage />} />
-                <Route path="/users" element={<UsersPage />} />
-                <Route path="/components" element={<ComponentShowcasePage />} />
-                <Route path="/settings" element={<SettingsPage />} />
-              </Routes>
-            </MainLayout>
+            <Routes>
+              {/* Standalone window opened when the database fails to open */}
+              <Route path="/recovery" element={<RecoveryPage />} />
+              <Route
+                path="*"
+                element={
+                  <MainLayout>
+                    <Routes>
+                      <Route path="/" element={<HomePage />} />
+                      <Route path="/users" element={<UsersPage />} />
+                      <Route path="/components" element={<ComponentShowcasePage />} />
+                      <Route path="/settings" element={<SettingsPage />} />
+                    </Routes>
+                  </MainLayout>
+                }
+              />
+            </Routes>
             <Toaster />
           </BrowserRouter>
         </TooltipProvider>
@@ -3,7 +3,16 @@
 
 export type AppEvent = 
   | { event: 'config:changed'; payload: { key: string; value: string } }
-  // | { event: 'download:progress'; payload: { id: string; progress: number } }
+  | { event: 'config:batch-changed'; payload: { values: Record<string, string> } }
+  | { event: 'config:deleted'; payload: { key: string } }
+  | { event: 'config:reset'; payload: undefined }
+  | { event: 'feature-flag:changed'; payload: { key: string; user_id: string | null; enabled: boolean } }
+  | { event: 'database:restored'; payload: { backup: string } }
+  | { event: 'archive:imported'; payload: { archive: string } }
+  | { event: 'database:migrations-rolled-back'; payload: { version: number } }
+  | { event: 'download:progress'; payload: { id: string; bytes: number; total: number | null } }
+  | { event: 'download:state-changed'; payload: { id: string; state: 'queued' | 'running' | 'paused' | 'completed' | 'failed' | 'cancelled' } }
+  | { event: 'http:circuit-changed'; payload: { host: string; state: 'closed' | 'open' | 'half_open' } }
 ;
 
 export type EventName = AppEvent['event'];
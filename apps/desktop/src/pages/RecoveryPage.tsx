@@ -0,0 +1,173 @@
+import { useEffect, useState, type ReactNode } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { toast } from 'sonner';
+import { useTranslation } from 'react-i18next';
+import { AlertTriangle, DatabaseBackup, Download, RefreshCw, Trash2 } from 'lucide-react';
+import { Alert, AlertDescription, AlertTitle } from '@/components/ui/alert';
+import { Button } from '@/components/ui/button';
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from '@/components/ui/card';
+import { formatBytes } from '@/lib/utils';
+
+// Mirrors domain/recovery.rs
+interface RecoveryStatus {
+  active: boolean;
+  error: string;
+  db_path: string;
+  backups: { name: string; size_bytes: number; created_at: string; pre_restore: boolean }[];
+}
+
+interface SalvageReport {
+  path: string;
+  tables: { name: string; rows: number; error: string | null }[];
+}
+
+/**
+ * Shown in its own window when the database fails to open at startup.
+ * The backend closes this window and shows the main one once recovery succeeds.
+ */
+export function RecoveryPage() {
+  const { t } = useTranslation();
+  const [status, setStatus] = useState<RecoveryStatus | null>(null);
+  const [busy, setBusy] = useState(false);
+
+  const refresh = async () => {
+    try {
+      setStatus(await invoke<RecoveryStatus>('get_recovery_status'));
+    } catch (e) {
+      toast.error(errorMessage(e));
+    }
+  };
+
+  useEffect(() => {
+    refresh();
+  }, []);
+
+  // Run a recovery action; on failure show the error and reload the status (the error text changes)
+  const run = async (action: () => Promise<void>) => {
+    setBusy(true);
+    try {
+      await action();
+    } catch (e) {
+      toast.error(errorMessage(e));
+      await refresh();
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const handleRetry = () => run(async () => {
+    await invoke('recovery_retry');
+    toast.success(t('recovery.recovered'));
+  });
+
+  const handleExport = () => run(async () => {
+    const report = await invoke<SalvageReport>('recovery_export');
+    toast.success(t('recovery.exportSuccess', { tables: report.tables.length, path: report.path }));
+  });
+
+  const handleRestore = (name: string) => run(async () => {
+    await invoke('recovery_restore_backup', { name });
+    toast.success(t('recovery.recovered'));
+  });
+
+  const handleStartFresh = () => run(async () => {
+    const name = await invoke<string>('recovery_start_fresh');
+    toast.success(t('recovery.startFreshSuccess', { name }));
+  });
+
+  return (
+    <div className="min-h-screen bg-background p-6 space-y-6">
+      <div>
+        <h1 className="text-2xl font-bold tracking-tight">{t('recovery.title')}</h1>
+        <p className="text-muted-foreground mt-1">{t('recovery.description')}</p>
+      </div>
+
+      {status && (
+        <Alert variant="destructive">
+          <AlertTriangle className="h-4 w-4" />
+          <AlertTitle>{t('recovery.errorTitle')}</AlertTitle>
+          <AlertDescription className="space-y-1">
+            <p className="font-mono text-xs break-all">{status.error}</p>
+            <p className="text-xs">{t('recovery.databaseFile')}: <span className="font-mono break-all">{status.db_path}</span></p>
+          </AlertDescription>
+        </Alert>
+      )}
+
+      <div className="grid gap-4">
+        <ActionCard title={t('recovery.retry')} description={t('recovery.retryDesc')}>
+          <Button variant="outline" disabled={busy} onClick={handleRetry}>
+            <RefreshCw className="h-4 w-4 mr-2" />
+            {t('recovery.retry')}
+          </Button>
+        </ActionCard>
+
+        <ActionCard title={t('recovery.export')} description={t('recovery.exportDesc')}>
+          <Button variant="outline" disabled={busy} onClick={handleExport}>
+            <Download className="h-4 w-4 mr-2" />
+            {t('recovery.export')}
+          </Button>
+        </ActionCard>
+
+        <Card>
+          <CardHeader>
+            <CardTitle className="text-base">{t('recovery.restore')}</CardTitle>
+            <CardDescription>{t('recovery.restoreDesc')}</CardDescription>
+          </CardHeader>
+          <CardContent className="space-y-2">
+            {status?.backups.length ? (
+              status.backups.map((backup) => (
+                <div key={backup.name} className="flex items-center justify-between rounded-md border p-3">
+                  <div className="space-y-0.5">
+                    <p className="font-mono text-sm">{backup.name}</p>
+                    <p className="text-xs text-muted-foreground">
+                      {new Date(backup.created_at).toLocaleString()} · {formatBytes(backup.size_bytes)}
+                    </p>
+                  </div>
+                  <Button variant="outline" size="sm" disabled={busy} onClick={() => handleRestore(backup.name)}>
+                    <DatabaseBackup className="h-4 w-4 mr-2" />
+                    {t('recovery.restore')}
+                  </Button>
+                </div>
+              ))
+            ) : (
+              <p className="text-sm text-muted-foreground">{t('recovery.noBackups')}</p>
+            )}
+          </CardContent>
+        </Card>
+
+        <Card className="border-destructive/20 bg-destructive/5">
+          <CardContent className="flex items-center justify-between gap-4 pt-6">
+            <div className="space-y-1">
+              <p className="font-medium text-destructive">{t('recovery.startFresh')}</p>
+              <p className="text-sm text-muted-foreground">{t('recovery.startFreshDesc')}</p>
+            </div>
+            <Button variant="destructive" disabled={busy} onClick={handleStartFresh}>
+              <Trash2 className="h-4 w-4 mr-2" />
+              {t('recovery.startFresh')}
+            </Button>
+          </CardContent>
+        </Card>
+      </div>
+    </div>
+  );
+}
+
+function ActionCard({ title, description, children }: { title: string; description: string; children: ReactNode }) {
+  return (
+    <Card>
+      <CardContent className="flex items-center justify-between gap-4 pt-6">
+        <div className="space-y-1">
+          <p className="font-medium">{title}</p>
+          <p className="text-sm text-muted-foreground">{description}</p>
+        </div>
+        {children}
+      </CardContent>
+    </Card>
+  );
+}
+
+// AppError serializes as { type, message }
+function errorMessage(e: unknown) {
+  if (e && typeof e === 'object' && 'message' in e) return String((e as { message: unknown }).message);
+  return String(e);
+}
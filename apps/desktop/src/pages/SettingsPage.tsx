@@ -10,7 +10,7 @@ import { useTheme } from '@/hooks/useTheme';
 import { useState } from 'react';
 import { toast } from 'sonner';
 import { invoke } from '@tauri-apps/api/core';
-import { cn } from '@/lib/utils';
+import { cn, formatBytes } from '@/lib/utils';
 import { User, Settings, Shield, Palette, Bell, HelpCircle, LogOut } from 'lucide-react';
 import { useTranslation } from 'react-i18next';
 
@@ -79,6 +79,8 @@ export function SettingsPage() {
 
 function GeneralSettings() {
   const { t } = useTranslation();
+  const { getSetting, updateSetting } = useAppConfig();
+
   return (
     <div className="space-y-6">
       <SectionHeader title={t('settings.general')} description={t('settings.generalDescription')} />
@@ -93,7 +95,10 @@ function GeneralSettings() {
                 {t('settings.launchOnStartupDesc')}
               </p>
             </div>
-            <Switch />
+            <Switch
+              checked={getSetting('launch_on_startup') === 'true'}
+              onCheckedChange={(checked) => updateSetting({ key: 'launch_on_startup', value: String(checked) })}
+            />
           </div>
         </SettingCard>
 
@@ -241,6 +246,20 @@ function AdvancedSettings() {
     }
   };
 
+  const handleCheckDb = async () => {
+    try {
+      const report = await invoke<DbHealthReport>('check_db_health');
+      const summary = `${report.integrity_check.join(', ')} · ${formatBytes(report.file_size_bytes)} · ${report.pragmas.journal_mode}`;
+      if (report.healthy) {
+        toast.success(summary);
+      } else {
+        toast.error(summary);
+      }
+    } catch (e) {
+      toast.error(String(e));
+    }
+  };
+
   return (
     <div className="space-y-6">
       <SectionHeader title={t('settings.advanced')} description={t('settings.advancedDescription')} />
@@ -262,7 +281,7 @@ function AdvancedSettings() {
               <p className="font-medium">{t('settings.databaseHealth')}</p>
               <p className="text-sm text-muted-foreground">{t('settings.databaseHealthDesc')}</p>
             </div>
-            <Button variant="outline" onClick={() => invoke('check_db_health').then(res => toast.success(res as string))}>
+            <Button variant="outline" onClick={handleCheckDb}>
               {t('settings.checkConnection')}
             </Button>
           </div>
@@ -288,6 +307,14 @@ function AdvancedSettings() {
 
 // --- Helpers ---
 
+// Mirrors infra/db_diagnostics.rs (fields used here)
+interface DbHealthReport {
+  healthy: boolean;
+  integrity_check: string[];
+  file_size_bytes: number;
+  pragmas: { journal_mode: string };
+}
+
 function SectionHeader({ title, description }: { title: string, description: string }) {
   return (
     <div className="pb-2">